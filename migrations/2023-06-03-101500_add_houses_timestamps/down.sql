@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE houses DROP COLUMN updated_at;
+ALTER TABLE houses DROP COLUMN created_at;
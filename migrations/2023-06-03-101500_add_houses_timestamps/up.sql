@@ -0,0 +1,4 @@
+-- Your SQL goes here
+ALTER TABLE houses ADD COLUMN created_at TIMESTAMP NOT NULL DEFAULT '1970-01-01 00:00:00';
+ALTER TABLE houses ADD COLUMN updated_at TIMESTAMP NOT NULL DEFAULT '1970-01-01 00:00:00';
+UPDATE houses SET created_at = CURRENT_TIMESTAMP, updated_at = CURRENT_TIMESTAMP;
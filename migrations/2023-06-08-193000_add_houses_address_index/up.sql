@@ -0,0 +1,5 @@
+-- Your SQL goes here
+-- Not UNIQUE: under the warn policy a user may save a second house at the
+-- same address, and duplicates are compared on the normalized address. The
+-- refuse policy checks for one inside the write transaction that inserts.
+CREATE INDEX houses_address_idx ON houses (street_number, postal_code);
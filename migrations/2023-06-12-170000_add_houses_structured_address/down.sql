@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+UPDATE houses SET street_floor = street_floor || street_unit;
+
+ALTER TABLE houses DROP COLUMN country;
+ALTER TABLE houses DROP COLUMN province;
+ALTER TABLE houses DROP COLUMN city;
+ALTER TABLE houses DROP COLUMN street_unit;
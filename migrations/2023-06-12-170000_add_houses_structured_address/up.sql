@@ -0,0 +1,12 @@
+-- Your SQL goes here
+ALTER TABLE houses ADD COLUMN street_unit VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE houses ADD COLUMN city VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE houses ADD COLUMN province VARCHAR NOT NULL DEFAULT '';
+ALTER TABLE houses ADD COLUMN country VARCHAR NOT NULL DEFAULT '';
+
+-- Split floors like "3B" or "12 c" into floor "3" / "12" and unit "B" / "C"
+UPDATE houses
+SET street_unit = upper(trim(ltrim(trim(street_floor), '0123456789'))),
+    street_floor = rtrim(trim(street_floor), 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz ')
+WHERE trim(street_floor) GLOB '[0-9]*[A-Za-z]'
+  AND rtrim(rtrim(trim(street_floor), 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz '), '0123456789') = '';
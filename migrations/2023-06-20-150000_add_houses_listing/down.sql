@@ -0,0 +1,10 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE houses_status_history;
+
+ALTER TABLE houses DROP COLUMN status_id;
+ALTER TABLE houses DROP COLUMN operation_id;
+ALTER TABLE houses DROP COLUMN currency;
+ALTER TABLE houses DROP COLUMN price;
+
+DROP TABLE houses_status;
+DROP TABLE houses_operation;
@@ -0,0 +1,38 @@
+-- Your SQL goes here
+CREATE TABLE houses_operation (
+  id         INTEGER PRIMARY KEY,
+  operation  VARCHAR NOT NULL
+);
+
+INSERT INTO houses_operation (id, operation) VALUES
+  (0, 'Venta'),
+  (1, 'Alquiler');
+
+CREATE TABLE houses_status (
+  id      INTEGER PRIMARY KEY,
+  status  VARCHAR NOT NULL
+);
+
+INSERT INTO houses_status (id, status) VALUES
+  (0, 'Disponible'),
+  (1, 'Reservado'),
+  (2, 'Vendido'),
+  (3, 'Alquilado'),
+  (4, 'Retirado');
+
+ALTER TABLE houses ADD COLUMN price BIGINT;
+ALTER TABLE houses ADD COLUMN currency VARCHAR NOT NULL DEFAULT 'ARS';
+ALTER TABLE houses ADD COLUMN operation_id INTEGER NOT NULL DEFAULT 0 REFERENCES houses_operation(id);
+ALTER TABLE houses ADD COLUMN status_id INTEGER NOT NULL DEFAULT 0 REFERENCES houses_status(id);
+
+CREATE TABLE houses_status_history (
+  id          INTEGER PRIMARY KEY,
+  house_id    INTEGER NOT NULL,
+  status_id   INTEGER NOT NULL,
+  changed_at  TIMESTAMP NOT NULL,
+  FOREIGN KEY(house_id) REFERENCES houses(id),
+  FOREIGN KEY(status_id) REFERENCES houses_status(id)
+);
+
+INSERT INTO houses_status_history (house_id, status_id, changed_at)
+SELECT id, status_id, created_at FROM houses;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE houses_contacts;
+DROP TABLE contacts_role;
+DROP TABLE contacts;
@@ -0,0 +1,28 @@
+-- Your SQL goes here
+CREATE TABLE contacts (
+  id     INTEGER PRIMARY KEY,
+  name   VARCHAR NOT NULL,
+  phone  VARCHAR NOT NULL,
+  email  VARCHAR NOT NULL,
+  notes  TEXT NOT NULL
+);
+
+CREATE TABLE contacts_role (
+  id    INTEGER PRIMARY KEY,
+  role  VARCHAR NOT NULL
+);
+
+INSERT INTO contacts_role (id, role) VALUES
+  (0, 'Propietario'),
+  (1, 'Inquilino'),
+  (2, 'Agente');
+
+CREATE TABLE houses_contacts (
+  house_id    INTEGER NOT NULL,
+  contact_id  INTEGER NOT NULL,
+  role_id     INTEGER NOT NULL,
+  PRIMARY KEY (house_id, contact_id, role_id),
+  FOREIGN KEY(house_id) REFERENCES houses(id),
+  FOREIGN KEY(contact_id) REFERENCES contacts(id),
+  FOREIGN KEY(role_id) REFERENCES contacts_role(id)
+);
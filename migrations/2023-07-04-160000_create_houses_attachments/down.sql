@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX houses_attachments_house_id;
+DROP TABLE houses_attachments;
@@ -0,0 +1,14 @@
+-- Your SQL goes here
+CREATE TABLE houses_attachments (
+  id          INTEGER PRIMARY KEY,
+  house_id    INTEGER NOT NULL,
+  file_name   VARCHAR NOT NULL,
+  mime_type   VARCHAR NOT NULL,
+  caption     VARCHAR NOT NULL,
+  position    INTEGER NOT NULL,
+  data        BLOB NOT NULL,
+  created_at  TIMESTAMP NOT NULL,
+  FOREIGN KEY(house_id) REFERENCES houses(id)
+);
+
+CREATE INDEX houses_attachments_house_id ON houses_attachments (house_id, position);
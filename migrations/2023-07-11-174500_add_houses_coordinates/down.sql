@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX houses_coordinates;
+ALTER TABLE houses DROP COLUMN longitude;
+ALTER TABLE houses DROP COLUMN latitude;
@@ -0,0 +1,5 @@
+-- Your SQL goes here
+ALTER TABLE houses ADD COLUMN latitude DOUBLE;
+ALTER TABLE houses ADD COLUMN longitude DOUBLE;
+
+CREATE INDEX houses_coordinates ON houses (latitude, longitude);
@@ -0,0 +1,2 @@
+-- This file should undo anything in `up.sql`
+-- The kinds stay: houses and custom fields refer to them.
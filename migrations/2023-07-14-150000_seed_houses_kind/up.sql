@@ -0,0 +1,5 @@
+-- Your SQL goes here
+INSERT OR IGNORE INTO houses_kind (id, kind) VALUES
+  (0, 'Casa'),
+  (1, 'Departamento'),
+  (2, 'Otro');
@@ -0,0 +1,12 @@
+-- This file should undo anything in `up.sql`
+DROP TRIGGER houses_search_attachment_delete;
+DROP TRIGGER houses_search_attachment_update;
+DROP TRIGGER houses_search_attachment_insert;
+DROP TRIGGER houses_search_kind_update;
+DROP TRIGGER houses_search_delete;
+DROP TRIGGER houses_search_update;
+DROP TRIGGER houses_search_insert;
+DROP TABLE houses_search;
+DROP VIEW houses_search_source;
+
+ALTER TABLE houses DROP COLUMN description;
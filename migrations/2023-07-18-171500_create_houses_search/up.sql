@@ -0,0 +1,75 @@
+-- Your SQL goes here
+ALTER TABLE houses ADD COLUMN description TEXT NOT NULL DEFAULT '';
+
+CREATE VIEW houses_search_source AS
+SELECT
+  houses.id,
+  houses.street || ' ' || houses.street_number,
+  houses.street_floor || ' ' || houses.street_unit,
+  houses.postal_code,
+  houses.city,
+  houses_kind.kind,
+  COALESCE(
+    (SELECT group_concat(caption, ' ') FROM houses_attachments
+     WHERE houses_attachments.house_id = houses.id),
+    ''
+  ),
+  houses.description
+FROM houses
+INNER JOIN houses_kind ON houses_kind.id = houses.kind_id;
+
+CREATE VIRTUAL TABLE houses_search USING fts5(
+  street,
+  apartment,
+  postal_code,
+  city,
+  kind,
+  captions,
+  description,
+  tokenize = 'unicode61 remove_diacritics 2'
+);
+
+INSERT INTO houses_search (rowid, street, apartment, postal_code, city, kind, captions, description)
+SELECT * FROM houses_search_source;
+
+CREATE TRIGGER houses_search_insert AFTER INSERT ON houses BEGIN
+  INSERT INTO houses_search (rowid, street, apartment, postal_code, city, kind, captions, description)
+  SELECT * FROM houses_search_source WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER houses_search_update AFTER UPDATE ON houses BEGIN
+  DELETE FROM houses_search WHERE rowid = OLD.id;
+  INSERT INTO houses_search (rowid, street, apartment, postal_code, city, kind, captions, description)
+  SELECT * FROM houses_search_source WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER houses_search_delete AFTER DELETE ON houses BEGIN
+  DELETE FROM houses_search WHERE rowid = OLD.id;
+END;
+
+CREATE TRIGGER houses_search_kind_update AFTER UPDATE OF kind ON houses_kind BEGIN
+  DELETE FROM houses_search
+  WHERE rowid IN (SELECT id FROM houses WHERE kind_id = NEW.id);
+  INSERT INTO houses_search (rowid, street, apartment, postal_code, city, kind, captions, description)
+  SELECT * FROM houses_search_source
+  WHERE id IN (SELECT id FROM houses WHERE kind_id = NEW.id);
+END;
+
+CREATE TRIGGER houses_search_attachment_insert AFTER INSERT ON houses_attachments BEGIN
+  DELETE FROM houses_search WHERE rowid = NEW.house_id;
+  INSERT INTO houses_search (rowid, street, apartment, postal_code, city, kind, captions, description)
+  SELECT * FROM houses_search_source WHERE id = NEW.house_id;
+END;
+
+CREATE TRIGGER houses_search_attachment_update
+AFTER UPDATE OF house_id, caption ON houses_attachments BEGIN
+  DELETE FROM houses_search WHERE rowid IN (OLD.house_id, NEW.house_id);
+  INSERT INTO houses_search (rowid, street, apartment, postal_code, city, kind, captions, description)
+  SELECT * FROM houses_search_source WHERE id IN (OLD.house_id, NEW.house_id);
+END;
+
+CREATE TRIGGER houses_search_attachment_delete AFTER DELETE ON houses_attachments BEGIN
+  DELETE FROM houses_search WHERE rowid = OLD.house_id;
+  INSERT INTO houses_search (rowid, street, apartment, postal_code, city, kind, captions, description)
+  SELECT * FROM houses_search_source WHERE id = OLD.house_id;
+END;
@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE saved_searches (
+  id            INTEGER PRIMARY KEY,
+  name          VARCHAR NOT NULL UNIQUE,
+  query         TEXT NOT NULL,
+  search        TEXT NOT NULL,
+  operation_id  INTEGER,
+  status_id     INTEGER,
+  near          VARCHAR NOT NULL,
+  sort_order    VARCHAR NOT NULL,
+  FOREIGN KEY(operation_id) REFERENCES houses_operation(id),
+  FOREIGN KEY(status_id) REFERENCES houses_status(id)
+);
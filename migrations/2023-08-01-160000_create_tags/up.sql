@@ -0,0 +1,13 @@
+-- Your SQL goes here
+CREATE TABLE tags (
+  id    INTEGER PRIMARY KEY,
+  name  VARCHAR NOT NULL UNIQUE COLLATE NOCASE
+);
+
+CREATE TABLE houses_tags (
+  house_id  INTEGER NOT NULL,
+  tag_id    INTEGER NOT NULL,
+  PRIMARY KEY (house_id, tag_id),
+  FOREIGN KEY(house_id) REFERENCES houses(id),
+  FOREIGN KEY(tag_id) REFERENCES tags(id)
+);
@@ -0,0 +1,28 @@
+-- Your SQL goes here
+CREATE TABLE amenities (
+  id         INTEGER PRIMARY KEY,
+  amenity    VARCHAR NOT NULL,
+  countable  BOOLEAN NOT NULL
+);
+
+INSERT INTO amenities (id, amenity, countable) VALUES
+  (0, 'Cochera', 1),
+  (1, 'Ascensor', 0),
+  (2, 'Pileta', 0),
+  (3, 'Balcón', 0),
+  (4, 'Terraza', 0),
+  (5, 'Patio', 0),
+  (6, 'Jardín', 0),
+  (7, 'Parrilla', 0),
+  (8, 'Baulera', 0),
+  (9, 'Aire acondicionado', 1),
+  (10, 'Seguridad', 0);
+
+CREATE TABLE houses_amenities (
+  house_id    INTEGER NOT NULL,
+  amenity_id  INTEGER NOT NULL,
+  quantity    INTEGER,
+  PRIMARY KEY (house_id, amenity_id),
+  FOREIGN KEY(house_id) REFERENCES houses(id),
+  FOREIGN KEY(amenity_id) REFERENCES amenities(id)
+);
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE houses_custom_values;
+DROP TABLE custom_fields_kind;
+DROP TABLE custom_fields;
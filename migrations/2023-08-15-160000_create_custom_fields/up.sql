@@ -0,0 +1,25 @@
+-- Your SQL goes here
+CREATE TABLE custom_fields (
+  id          INTEGER PRIMARY KEY,
+  name        VARCHAR NOT NULL UNIQUE COLLATE NOCASE,
+  field_type  VARCHAR NOT NULL,
+  required    BOOLEAN NOT NULL,
+  options     VARCHAR NOT NULL DEFAULT ''
+);
+
+CREATE TABLE custom_fields_kind (
+  field_id  INTEGER NOT NULL,
+  kind_id   INTEGER NOT NULL,
+  PRIMARY KEY (field_id, kind_id),
+  FOREIGN KEY(field_id) REFERENCES custom_fields(id),
+  FOREIGN KEY(kind_id) REFERENCES houses_kind(id)
+);
+
+CREATE TABLE houses_custom_values (
+  house_id  INTEGER NOT NULL,
+  field_id  INTEGER NOT NULL,
+  value     VARCHAR NOT NULL,
+  PRIMARY KEY (house_id, field_id),
+  FOREIGN KEY(house_id) REFERENCES houses(id),
+  FOREIGN KEY(field_id) REFERENCES custom_fields(id)
+);
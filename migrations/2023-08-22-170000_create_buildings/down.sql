@@ -0,0 +1,6 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX houses_building_idx;
+
+ALTER TABLE houses DROP COLUMN building_id;
+
+DROP TABLE buildings;
@@ -0,0 +1,45 @@
+-- Your SQL goes here
+CREATE TABLE buildings (
+  id             INTEGER PRIMARY KEY,
+  street         VARCHAR NOT NULL,
+  street_number  INTEGER NOT NULL,
+  postal_code    VARCHAR NOT NULL,
+  city           VARCHAR NOT NULL,
+  province       VARCHAR NOT NULL,
+  country        VARCHAR NOT NULL,
+  year_built     INTEGER,
+  elevator       BOOLEAN NOT NULL DEFAULT 0,
+  UNIQUE (street, street_number, postal_code, city, province, country)
+);
+
+-- One building for each address shared by apartments, in order of their
+-- first unit. Apartments are kind 1, as in utils::APARTMENT: the code
+-- identifies the kind by id, while its name can be edited.
+INSERT INTO buildings (street, street_number, postal_code, city, province, country)
+SELECT street, street_number, postal_code, city, province, country
+FROM houses
+WHERE kind_id = 1
+GROUP BY street, street_number, postal_code, city, province, country
+ORDER BY MIN(id);
+
+ALTER TABLE houses ADD COLUMN building_id INTEGER REFERENCES buildings(id);
+
+UPDATE houses SET building_id = (
+  SELECT b.id FROM buildings b
+  WHERE b.street = houses.street
+    AND b.street_number = houses.street_number
+    AND b.postal_code = houses.postal_code
+    AND b.city = houses.city
+    AND b.province = houses.province
+    AND b.country = houses.country
+)
+WHERE kind_id = 1;
+
+-- Buildings where any unit has the elevator amenity.
+UPDATE buildings SET elevator = 1 WHERE id IN (
+  SELECT h.building_id FROM houses h
+  JOIN houses_amenities a ON a.house_id = h.id
+  WHERE a.amenity_id = (SELECT id FROM amenities WHERE amenity = 'Ascensor')
+);
+
+CREATE INDEX houses_building_idx ON houses (building_id);
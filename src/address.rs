@@ -0,0 +1,206 @@
+use std::fmt;
+
+use crate::utils;
+
+const ABBREVIATIONS: [(&str, &str); 16] = [
+    ("av", "Avenida"),
+    ("avda", "Avenida"),
+    ("avd", "Avenida"),
+    ("bv", "Bulevar"),
+    ("bvar", "Bulevar"),
+    ("blvd", "Bulevar"),
+    ("cnel", "Coronel"),
+    ("dr", "Doctor"),
+    ("gral", "General"),
+    ("ing", "Ingeniero"),
+    ("pje", "Pasaje"),
+    ("pres", "Presidente"),
+    ("pte", "Presidente"),
+    ("sta", "Santa"),
+    ("sto", "Santo"),
+    ("tte", "Teniente"),
+];
+
+const LOWERCASE_WORDS: [&str; 7] = ["de", "del", "la", "las", "los", "el", "y"];
+
+/// Structured address of a house.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Address {
+    pub street: String,
+    pub street_number: i32,
+    pub floor: String,
+    pub unit: String,
+    pub postal_code: String,
+    pub city: String,
+    pub province: String,
+    pub country: String,
+}
+
+impl Address {
+    pub fn normalized(&self) -> Address {
+        Address {
+            street: normalize_name(&self.street),
+            street_number: self.street_number,
+            floor: normalize_code(&self.floor),
+            unit: normalize_code(&self.unit),
+            postal_code: normalize_code(&self.postal_code).replace(' ', ""),
+            city: normalize_name(&self.city),
+            province: normalize_name(&self.province),
+            country: normalize_name(&self.country),
+        }
+    }
+
+    pub fn key(&self) -> String {
+        let address = self.normalized();
+        format!(
+            "{}|{}|{}|{}|{}",
+            utils::normalize_text(&address.street),
+            address.street_number,
+            utils::normalize_text(&address.floor),
+            utils::normalize_text(&address.unit),
+            utils::normalize_text(&address.postal_code)
+        )
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.street, self.street_number)?;
+        let mut apartment = vec![];
+        if !self.floor.is_empty() {
+            apartment.push(format!("piso {}", self.floor));
+        }
+        if !self.unit.is_empty() {
+            apartment.push(format!("depto {}", self.unit));
+        }
+        if !apartment.is_empty() {
+            write!(f, " ({})", apartment.join(", "))?;
+        }
+        for part in [&self.city, &self.province, &self.country] {
+            if !part.is_empty() {
+                write!(f, ", {}", part)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn normalize_name(text: &str) -> String {
+    text.split_whitespace()
+        .enumerate()
+        .map(|(position, word)| {
+            let lowercase = word.to_lowercase();
+            let bare = lowercase.trim_end_matches('.');
+            if let Some((_, expanded)) = ABBREVIATIONS.iter().find(|(short, _)| *short == bare) {
+                expanded.to_string()
+            } else if position > 0 && LOWERCASE_WORDS.contains(&bare) {
+                lowercase
+            } else {
+                capitalize(&lowercase)
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub(crate) fn normalize_code(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_uppercase()
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn address(street: &str, floor: &str, unit: &str, postal_code: &str) -> Address {
+        Address {
+            street: street.to_string(),
+            street_number: 1234,
+            floor: floor.to_string(),
+            unit: unit.to_string(),
+            postal_code: postal_code.to_string(),
+            ..Address::default()
+        }
+    }
+
+    #[test]
+    fn normalizes_whitespace_case_and_codes() {
+        let normalized = Address {
+            city: "  buenos   AIRES ".to_string(),
+            province: "tierra del fuego".to_string(),
+            country: "ARGENTINA".to_string(),
+            ..address("  san   martín ", " pb ", "b", "c1043 aab")
+        }
+        .normalized();
+        assert_eq!(normalized.street, "San Martín");
+        assert_eq!(normalized.floor, "PB");
+        assert_eq!(normalized.unit, "B");
+        assert_eq!(normalized.postal_code, "C1043AAB");
+        assert_eq!(normalized.city, "Buenos Aires");
+        assert_eq!(normalized.province, "Tierra del Fuego");
+        assert_eq!(normalized.country, "Argentina");
+        assert_eq!(normalized.street_number, 1234);
+    }
+
+    #[test]
+    fn keeps_connecting_words_lowercase_except_first() {
+        assert_eq!(
+            address("DE LA SERNA Y LOS ANDES", "", "", "")
+                .normalized()
+                .street,
+            "De la Serna y los Andes"
+        );
+    }
+
+    #[test]
+    fn expands_every_abbreviation_with_or_without_dot() {
+        for (short, expanded) in ABBREVIATIONS {
+            for written in [short.to_string(), format!("{short}."), short.to_uppercase()] {
+                let street = address(&format!("{written} belgrano"), "", "", "")
+                    .normalized()
+                    .street;
+                assert_eq!(street, format!("{expanded} Belgrano"), "{written}");
+            }
+        }
+    }
+
+    #[test]
+    fn abbreviations_only_expand_whole_words() {
+        assert_eq!(
+            address("avellaneda", "", "", "").normalized().street,
+            "Avellaneda"
+        );
+    }
+
+    #[test]
+    fn normalizing_twice_changes_nothing() {
+        let normalized = address("av.  de mayo", "3", "b", "c1043").normalized();
+        assert_eq!(normalized.normalized(), normalized);
+    }
+
+    #[test]
+    fn key_ignores_spelling_of_the_same_address() {
+        assert_eq!(
+            address("Av. Corrientes", "3", "b", "c1043").key(),
+            address("avenida  CORRIENTES", " 3", "B", "C1043").key()
+        );
+    }
+
+    #[test]
+    fn key_tells_apart_units_and_postal_codes() {
+        let key = address("Corrientes", "3", "B", "C1043").key();
+        assert_ne!(key, address("Corrientes", "3", "C", "C1043").key());
+        assert_ne!(key, address("Corrientes", "4", "B", "C1043").key());
+        assert_ne!(key, address("Corrientes", "3", "B", "C1044").key());
+    }
+}
@@ -0,0 +1,362 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::json;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use app_gui::draft::HouseDraft;
+use app_gui::geo;
+use app_gui::models::{House, HouseFilter, HouseOrder, HouseWithKind};
+use app_gui::query::Query;
+use app_gui::service::{HouseService, ServiceError};
+use app_gui::types::HouseId;
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+const DEFAULT_LIMIT: usize = 50;
+const MAX_LIMIT: usize = 500;
+
+type ApiResult = Result<(u16, serde_json::Value), ApiError>;
+
+#[derive(Debug)]
+enum ApiError {
+    BadRequest(String),
+    NotFound,
+    MethodNotAllowed,
+    Service(ServiceError),
+}
+
+impl From<ServiceError> for ApiError {
+    fn from(error: ServiceError) -> Self {
+        ApiError::Service(error)
+    }
+}
+
+impl From<diesel::result::Error> for ApiError {
+    fn from(error: diesel::result::Error) -> Self {
+        ApiError::Service(error.into())
+    }
+}
+
+impl ApiError {
+    fn status(&self) -> u16 {
+        match self {
+            ApiError::BadRequest(_) => 400,
+            ApiError::NotFound => 404,
+            ApiError::MethodNotAllowed => 405,
+            ApiError::Service(ServiceError::Validation(_)) => 400,
+            ApiError::Service(ServiceError::NotFound) => 404,
+            ApiError::Service(ServiceError::Conflict) => 409,
+            ApiError::Service(ServiceError::Duplicate(_)) => 409,
+            ApiError::Service(ServiceError::Query(_)) => 400,
+            ApiError::Service(ServiceError::TooLong(..)) => 400,
+            ApiError::Service(ServiceError::CustomField(..)) => 400,
+            ApiError::Service(ServiceError::Database) => 500,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(message) => message.clone(),
+            ApiError::NotFound => "Recurso no encontrado".to_string(),
+            ApiError::MethodNotAllowed => "Método no permitido".to_string(),
+            ApiError::Service(error) => error.to_string(),
+        }
+    }
+}
+
+/// Fields accepted when creating or patching a house. Missing fields keep
+/// their current value on a patch; `null` clears the optional ones.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct HouseBody {
+    street: Option<String>,
+    street_number: Option<i32>,
+    street_floor: Option<String>,
+    street_unit: Option<String>,
+    postal_code: Option<String>,
+    city: Option<String>,
+    province: Option<String>,
+    country: Option<String>,
+    #[serde(default, deserialize_with = "nullable")]
+    latitude: Option<Option<f64>>,
+    #[serde(default, deserialize_with = "nullable")]
+    longitude: Option<Option<f64>>,
+    surface_square_meters: Option<i32>,
+    bathrooms: Option<i32>,
+    rooms: Option<i32>,
+    kind_id: Option<i32>,
+    #[serde(default, deserialize_with = "nullable")]
+    price: Option<Option<i64>>,
+    currency: Option<String>,
+    operation_id: Option<i32>,
+    status_id: Option<i32>,
+    description: Option<String>,
+    version: Option<i32>,
+}
+
+#[derive(Serialize)]
+struct Page {
+    total: usize,
+    offset: usize,
+    limit: usize,
+    items: Vec<HouseWithKind>,
+}
+
+/// Serves the JSON API until the process is stopped. Requests are handled
+/// one at a time on the calling thread.
+pub fn serve(service: &mut HouseService, address: &str) -> Result<(), String> {
+    let server = Server::http(address).map_err(|e| format!("Error iniciando el servidor: {e}"))?;
+    println!("Escuchando en http://{address}");
+    for mut request in server.incoming_requests() {
+        let (status, body) = match handle(service, &mut request) {
+            Ok(response) => response,
+            Err(error) => (error.status(), json!({ "error": error.message() })),
+        };
+        let response = if status == 204 {
+            Response::from_data(vec![]).with_status_code(status)
+        } else {
+            Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(json_header())
+        };
+        if let Err(e) = request.respond(response) {
+            eprintln!("Error respondiendo: {e}");
+        }
+    }
+    Ok(())
+}
+
+fn handle(service: &mut HouseService, request: &mut Request) -> ApiResult {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    match (request.method(), segments.as_slice()) {
+        (Method::Get, ["kinds"]) => Ok((200, json!(service.get_houses_kind()?))),
+        (Method::Get, ["houses"]) => list_houses(service, query),
+        (Method::Post, ["houses"]) => {
+            let body = read_body(request)?;
+            create_house(service, body)
+        }
+        (Method::Get, ["houses", id]) => {
+            Ok((200, json!(service.get_house_with_kind(parse_id(id)?)?)))
+        }
+        (Method::Patch, ["houses", id]) => {
+            let id = parse_id(id)?;
+            let body = read_body(request)?;
+            update_house(service, id, body)
+        }
+        (Method::Delete, ["houses", id]) => match service.delete_house(parse_id(id)?)? {
+            0 => Err(ApiError::NotFound),
+            _ => Ok((204, json!(null))),
+        },
+        (_, ["kinds"] | ["houses"] | ["houses", _]) => Err(ApiError::MethodNotAllowed),
+        _ => Err(ApiError::NotFound),
+    }
+}
+
+fn list_houses(service: &mut HouseService, query: &str) -> ApiResult {
+    let mut filter = HouseFilter::default();
+    let mut order = HouseOrder::Id;
+    let mut offset = 0;
+    let mut limit = DEFAULT_LIMIT;
+    for (key, value) in query_pairs(query) {
+        match key.as_str() {
+            "kind" => filter.kind_id = Some(parse_param(&key, &value)?),
+            "operation" => filter.operation_id = Some(parse_param(&key, &value)?),
+            "status" => filter.status_id = Some(parse_param(&key, &value)?),
+            "q" => {
+                filter.query = Query::parse(&value)
+                    .map_err(|e| ApiError::BadRequest(format!("Parámetro q: {e}")))?
+            }
+            "near" => {
+                filter.near = geo::parse_radius(&value);
+                filter.bounds = geo::parse_bounding_box(&value);
+                if filter.near.is_none() && filter.bounds.is_none() {
+                    return Err(bad_param(&key));
+                }
+            }
+            "order" => order = parse_param(&key, &value)?,
+            "offset" => offset = parse_param(&key, &value)?,
+            "limit" => limit = parse_param::<usize>(&key, &value)?.min(MAX_LIMIT),
+            _ => {
+                return Err(ApiError::BadRequest(format!(
+                    "Parámetro desconocido: {key}"
+                )))
+            }
+        }
+    }
+
+    let houses = service.get_houses_filtered(order, &filter)?;
+    let page = Page {
+        total: houses.len(),
+        offset,
+        limit,
+        items: houses.into_iter().skip(offset).take(limit).collect(),
+    };
+    Ok((200, json!(page)))
+}
+
+fn create_house(service: &mut HouseService, body: HouseBody) -> ApiResult {
+    let draft = body.apply(HouseDraft::new(), None)?;
+    let id = service.create_from_draft(&draft)?;
+    Ok((201, json!(service.get_house_with_kind(id)?)))
+}
+
+fn update_house(service: &mut HouseService, id: HouseId, body: HouseBody) -> ApiResult {
+    let current = service.get_house(id)?;
+    let version = body.version.unwrap_or(current.version);
+    let draft = body.apply(HouseDraft::from(&current), Some(&current))?;
+    service.update_from_draft(id, version, &draft)?;
+    Ok((200, json!(service.get_house_with_kind(id)?)))
+}
+
+impl HouseBody {
+    /// Sets the given fields on `draft`. The coordinates of `current` fill
+    /// in when only one of latitude and longitude is sent.
+    fn apply(self, mut draft: HouseDraft, current: Option<&House>) -> Result<HouseDraft, ApiError> {
+        if let Some(street) = self.street {
+            draft = draft.street(&street);
+        }
+        if let Some(street_number) = self.street_number {
+            draft = draft.street_number(street_number);
+        }
+        if let Some(floor) = self.street_floor {
+            draft = draft.floor(&floor);
+        }
+        if let Some(unit) = self.street_unit {
+            draft = draft.unit(&unit);
+        }
+        if let Some(postal_code) = self.postal_code {
+            draft = draft.postal_code(&postal_code);
+        }
+        if let Some(city) = self.city {
+            draft = draft.city(&city);
+        }
+        if let Some(province) = self.province {
+            draft = draft.province(&province);
+        }
+        if let Some(country) = self.country {
+            draft = draft.country(&country);
+        }
+        if let Some(surface_square_meters) = self.surface_square_meters {
+            draft = draft.surface_square_meters(surface_square_meters);
+        }
+        if let Some(bathrooms) = self.bathrooms {
+            draft = draft.bathrooms(bathrooms);
+        }
+        if let Some(rooms) = self.rooms {
+            draft = draft.rooms(rooms);
+        }
+        if let Some(kind_id) = self.kind_id {
+            draft = draft.kind(kind_id);
+        }
+        if let Some(price) = self.price {
+            draft = draft.price(price);
+        }
+        if let Some(currency) = self.currency {
+            draft = draft.currency(&currency);
+        }
+        if let Some(operation_id) = self.operation_id {
+            draft = draft.operation(operation_id);
+        }
+        if let Some(status_id) = self.status_id {
+            draft = draft.status(status_id);
+        }
+        if let Some(description) = self.description {
+            draft = draft.description(&description);
+        }
+        if self.latitude.is_some() || self.longitude.is_some() {
+            let latitude = self
+                .latitude
+                .unwrap_or_else(|| current.and_then(|h| h.latitude));
+            let longitude = self
+                .longitude
+                .unwrap_or_else(|| current.and_then(|h| h.longitude));
+            draft = match (latitude, longitude) {
+                (Some(latitude), Some(longitude)) => draft.coordinates(latitude, longitude),
+                (None, None) => draft.without_coordinates(),
+                _ => {
+                    return Err(ApiError::BadRequest(
+                        "Complete la latitud y la longitud o ninguna".to_string(),
+                    ))
+                }
+            };
+        }
+        Ok(draft)
+    }
+}
+
+fn read_body(request: &mut Request) -> Result<HouseBody, ApiError> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| ApiError::BadRequest(e.to_string()))?;
+    serde_json::from_str(&body).map_err(|e| ApiError::BadRequest(format!("JSON inválido: {e}")))
+}
+
+/// Tells an absent field apart from an explicit `null`.
+fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+fn parse_id(text: &str) -> Result<HouseId, ApiError> {
+    text.parse().map_err(|_| ApiError::NotFound)
+}
+
+fn parse_param<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, ApiError> {
+    value.parse().map_err(|_| bad_param(key))
+}
+
+fn bad_param(key: &str) -> ApiError {
+    ApiError::BadRequest(format!("Valor inválido para {key}"))
+}
+
+fn query_pairs(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+/// Decodes `+` and `%XX` escapes of a query string component.
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                let escaped = text
+                    .get(index + 1..index + 3)
+                    .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                if let Some(byte) = escaped {
+                    decoded.push(byte);
+                    index += 2;
+                } else {
+                    decoded.push(b'%');
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        index += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn json_header() -> Header {
+    Header::from_bytes(
+        &b"Content-Type"[..],
+        &b"application/json; charset=utf-8"[..],
+    )
+    .expect("valid header")
+}
@@ -0,0 +1,128 @@
+use std::fs::File;
+use std::io::BufWriter;
+
+use fltk::{
+    draw,
+    enums::{Align, Color, ColorDepth, Font},
+    prelude::{ImageExt, SurfaceDevice},
+    surface::ImageSurface,
+};
+
+use app_gui::models::HouseWithKind;
+use app_gui::report::{self, Count, Report};
+
+const MARGIN: i32 = 40;
+const HISTOGRAM_BINS: usize = 10;
+const BAR_COLOR: Color = Color::from_rgb(70, 130, 180);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChartKind {
+    ByKind,
+    ByPostalCode,
+    SurfaceHistogram,
+}
+
+#[derive(Debug, Default)]
+pub struct Chart {
+    pub title: String,
+    pub bars: Vec<Count>,
+}
+
+impl Chart {
+    pub fn build(kind: ChartKind, houses: &[HouseWithKind]) -> Chart {
+        match kind {
+            ChartKind::ByKind => Chart {
+                title: "Elementos por tipo".to_string(),
+                bars: Report::build(houses).by_kind,
+            },
+            ChartKind::ByPostalCode => Chart {
+                title: "Elementos por código postal".to_string(),
+                bars: Report::build(houses).by_postal_code,
+            },
+            ChartKind::SurfaceHistogram => Chart {
+                title: "Superficie (m2)".to_string(),
+                bars: report::surface_histogram(houses, HISTOGRAM_BINS),
+            },
+        }
+    }
+
+    /// Draws the chart on the current drawing surface.
+    pub fn draw(&self, x: i32, y: i32, w: i32, h: i32) {
+        draw::draw_rect_fill(x, y, w, h, Color::White);
+        draw::set_draw_color(Color::Black);
+        draw::set_font(Font::HelveticaBold, 14);
+        draw::draw_text2(&self.title, x, y, w, MARGIN, Align::Center);
+        draw::set_font(Font::Helvetica, 12);
+        if self.bars.is_empty() {
+            draw::draw_text2("Sin elementos", x, y, w, h, Align::Center);
+            return;
+        }
+
+        let (left, top) = (x + MARGIN, y + MARGIN);
+        let (width, height) = (w - MARGIN * 2, h - MARGIN * 2);
+        let bottom = top + height;
+        let max = self.bars.iter().map(|b| b.count).max().unwrap_or(0).max(1);
+        draw::draw_line(left, top, left, bottom);
+        draw::draw_line(left, bottom, left + width, bottom);
+        for tick in [0, max / 2, max] {
+            let tick_y = bottom - (tick * height as usize / max) as i32;
+            draw::draw_text2(
+                &tick.to_string(),
+                x,
+                tick_y - 6,
+                MARGIN - 4,
+                12,
+                Align::Right,
+            );
+        }
+
+        let slot = width / self.bars.len() as i32;
+        let bar_width = (slot * 3 / 4).max(1);
+        for (index, bar) in self.bars.iter().enumerate() {
+            let bar_height = (bar.count * height as usize / max) as i32;
+            let bar_x = left + slot * index as i32 + (slot - bar_width) / 2;
+            draw::draw_rect_fill(bar_x, bottom - bar_height, bar_width, bar_height, BAR_COLOR);
+            draw::set_draw_color(Color::Black);
+            draw::draw_text2(
+                &bar.count.to_string(),
+                bar_x,
+                bottom - bar_height - 14,
+                bar_width,
+                12,
+                Align::Center,
+            );
+            draw::draw_text2(
+                &bar.label,
+                left + slot * index as i32,
+                bottom + 4,
+                slot,
+                12,
+                Align::Center | Align::Clip,
+            );
+        }
+    }
+
+    pub fn export_png(&self, path: &str, w: i32, h: i32) -> Result<(), String> {
+        let surface = ImageSurface::new(w, h, false);
+        ImageSurface::push_current(&surface);
+        self.draw(0, 0, w, h);
+        let image = surface.image();
+        ImageSurface::pop_current();
+
+        let image = image
+            .and_then(|image| image.convert(ColorDepth::Rgb8).ok())
+            .ok_or("Error dibujando el gráfico")?;
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(file),
+            image.data_w() as u32,
+            image.data_h() as u32,
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&image.to_rgb_data()))
+            .map_err(|e| e.to_string())
+    }
+}
@@ -0,0 +1,216 @@
+#[cfg(feature = "server")]
+use crate::api;
+#[cfg(feature = "tui")]
+use crate::tui;
+use app_gui::models::{CustomFieldType, HouseFilter, HouseOrder, HouseWithKind};
+use app_gui::query::Query;
+use app_gui::report::Report;
+use app_gui::service::HouseService;
+use app_gui::sheet;
+use app_gui::types::HouseId;
+use app_gui::utils;
+
+const USAGE: &str =
+    "Uso: app_gui [stats [--json] | list [consulta] | saved [nombre] | buildings [id] | fields [add|remove|set] | sheet <archivo.html|archivo.pdf> <id>... | serve [dirección] | tui]";
+
+const FIELDS_USAGE: &str = "Uso: app_gui fields [add <nombre> <integer|decimal|text|bool|date|enum> <tipos de casa separados por comas> [--required] [opción]... | remove <nombre> | set <id> <nombre> <valor>]";
+
+pub fn run(service: &mut HouseService, args: &[String]) -> Result<(), String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["stats"] => {
+            print!("{}", report(service)?);
+            Ok(())
+        }
+        ["stats", "--json"] => {
+            let json = serde_json::to_string_pretty(&report(service)?)
+                .map_err(|e| format!("Error generando el JSON: {e}"))?;
+            println!("{json}");
+            Ok(())
+        }
+        ["list", query @ ..] => list(service, &query.join(" ")),
+        ["saved"] => {
+            let searches = service
+                .get_saved_search_counts()
+                .map_err(|e| e.to_string())?;
+            for (search, count) in searches {
+                println!("{search} ({count})");
+            }
+            Ok(())
+        }
+        ["saved", name @ ..] => {
+            let search = service
+                .find_saved_search(&name.join(" "))
+                .map_err(|e| e.to_string())?;
+            let houses = service
+                .run_saved_search(&search)
+                .map_err(|e| e.to_string())?;
+            print_houses(&houses);
+            Ok(())
+        }
+        ["buildings"] => {
+            let buildings = service.get_buildings().map_err(|e| e.to_string())?;
+            for building in buildings {
+                let units = service
+                    .get_building_units(building.id)
+                    .map_err(|e| e.to_string())?;
+                println!("#{} {building} ({} unidades)", building.id, units.len());
+            }
+            Ok(())
+        }
+        ["buildings", id] => {
+            let id = id
+                .parse::<i32>()
+                .map_err(|_| format!("Id inválido\n{USAGE}"))?;
+            let units = service.get_building_units(id).map_err(|e| e.to_string())?;
+            print_houses(&units);
+            Ok(())
+        }
+        ["fields", args @ ..] => fields(service, args),
+        ["sheet", path, ids @ ..] if !ids.is_empty() => {
+            let ids = ids
+                .iter()
+                .map(|id| id.parse::<HouseId>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| format!("Id inválido\n{USAGE}"))?;
+            let sheets = service
+                .get_listing_sheets(&ids)
+                .map_err(|e| e.to_string())?;
+            sheet::export(&sheets, path)
+        }
+        #[cfg(feature = "tui")]
+        ["tui"] => tui::run(service),
+        #[cfg(not(feature = "tui"))]
+        ["tui"] => Err("Compilado sin la interfaz de terminal (feature \"tui\")".to_string()),
+        #[cfg(feature = "server")]
+        ["serve"] => api::serve(service, api::DEFAULT_ADDRESS),
+        #[cfg(feature = "server")]
+        ["serve", address] => api::serve(service, address),
+        #[cfg(not(feature = "server"))]
+        ["serve", ..] => Err("Compilado sin el servidor (feature \"server\")".to_string()),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+/// Prints the houses matching the query `text`, or marks the position of its
+/// syntax error with a caret.
+fn list(service: &mut HouseService, text: &str) -> Result<(), String> {
+    let query = Query::parse(text).map_err(|e| {
+        let caret = " ".repeat(e.position - 1);
+        format!("{text}\n{caret}^\n{e}")
+    })?;
+    let filter = HouseFilter {
+        query,
+        ..HouseFilter::default()
+    };
+    let houses = service
+        .get_houses_filtered(HouseOrder::Id, &filter)
+        .map_err(|e| e.to_string())?;
+    print_houses(&houses);
+    Ok(())
+}
+
+/// Lists, defines or removes the custom fields, or sets one on a house.
+fn fields(service: &mut HouseService, args: &[&str]) -> Result<(), String> {
+    let fields = service.get_custom_fields().map_err(|e| e.to_string())?;
+    let kinds = service.get_houses_kind().map_err(|e| e.to_string())?;
+    let fold = |text: &str| utils::strip_accents(&utils::normalize_text(text));
+    let find_field = |name: &str| {
+        fields
+            .iter()
+            .find(|f| fold(&f.name) == fold(name))
+            .ok_or(format!("No existe el campo \"{name}\""))
+    };
+    match args {
+        [] => {
+            let field_kinds = service
+                .get_custom_field_kinds()
+                .map_err(|e| e.to_string())?;
+            for field in &fields {
+                let applies: Vec<&str> = kinds
+                    .iter()
+                    .filter(|k| {
+                        field_kinds
+                            .iter()
+                            .any(|fk| fk.field_id == field.id && fk.kind_id == k.id.get())
+                    })
+                    .map(|k| k.kind.as_str())
+                    .collect();
+                let required = if field.required { ", obligatorio" } else { "" };
+                print!(
+                    "{} ({}{required}) - {}",
+                    field.name,
+                    field.field_type.as_str(),
+                    applies.join(", ")
+                );
+                if field.options.is_empty() {
+                    println!();
+                } else {
+                    println!(": {}", field.options().join(", "));
+                }
+            }
+            Ok(())
+        }
+        ["add", name, field_type, kind_names, rest @ ..] => {
+            let field_type: CustomFieldType = field_type.parse().map_err(str::to_string)?;
+            let kind_ids = kind_names
+                .split(',')
+                .map(|kind| {
+                    kinds
+                        .iter()
+                        .find(|k| fold(&k.kind) == fold(kind) || k.id.to_string() == kind.trim())
+                        .map(|k| k.id.get())
+                        .ok_or(format!("No existe el tipo de casa \"{kind}\""))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let required = rest.contains(&"--required");
+            let options: Vec<&str> = rest
+                .iter()
+                .copied()
+                .filter(|o| *o != "--required")
+                .collect();
+            service
+                .create_custom_field(name, field_type, required, &options, &kind_ids)
+                .map_err(|e| e.to_string())?;
+            Ok(())
+        }
+        ["remove", name] => {
+            let field = find_field(name)?;
+            service
+                .delete_custom_field(field.id)
+                .map_err(|e| e.to_string())?;
+            Ok(())
+        }
+        ["set", id, name, value] => {
+            let house_id = id
+                .parse::<HouseId>()
+                .map_err(|_| format!("Id inválido\n{FIELDS_USAGE}"))?;
+            let field = find_field(name)?;
+            let mut values: Vec<(i32, String)> = service
+                .get_custom_values(house_id)
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|v| v.field_id != field.id)
+                .map(|v| (v.field_id, v.value))
+                .collect();
+            values.push((field.id, value.to_string()));
+            service
+                .set_custom_values(house_id, &values)
+                .map_err(|e| e.to_string())?;
+            Ok(())
+        }
+        _ => Err(FIELDS_USAGE.to_string()),
+    }
+}
+
+fn print_houses(houses: &[HouseWithKind]) {
+    for house in houses {
+        println!("{house}");
+    }
+}
+
+fn report(service: &mut HouseService) -> Result<Report, String> {
+    service
+        .get_report(&HouseFilter::default())
+        .map_err(|e| e.to_string())
+}
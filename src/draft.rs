@@ -0,0 +1,328 @@
+use crate::address::Address;
+use crate::models::{House, NewHouse};
+use crate::service::ServiceError;
+use crate::types::{Floor, KindId, PostalCode, RoomCount, Surface};
+use crate::utils;
+
+/// Values of a house being created or edited, set by name:
+///
+/// ```
+/// use app_gui::draft::HouseDraft;
+/// use app_gui::utils::APARTMENT;
+///
+/// let draft = HouseDraft::new()
+///     .street("av. corrientes")
+///     .street_number(1234)
+///     .floor("3")
+///     .unit("b")
+///     .kind(APARTMENT)
+///     .surface_square_meters(60)
+///     .bathrooms(1)
+///     .rooms(2)
+///     .price(Some(100_000))
+///     .currency("usd");
+/// let house = draft.build().unwrap();
+/// assert_eq!(house.street, "Avenida Corrientes");
+/// assert_eq!(house.street_unit, "B");
+/// ```
+///
+/// [`build`](HouseDraft::build) checks everything that doesn't need the
+/// database; [`HouseService::create_from_draft`] and
+/// [`HouseService::update_from_draft`] also check the lookup ids and
+/// duplicates.
+///
+/// [`HouseService::create_from_draft`]: crate::service::HouseService::create_from_draft
+/// [`HouseService::update_from_draft`]: crate::service::HouseService::update_from_draft
+#[derive(Debug, Clone, PartialEq)]
+pub struct HouseDraft {
+    pub(crate) street: String,
+    pub(crate) street_number: Option<i32>,
+    pub(crate) street_floor: String,
+    pub(crate) street_unit: String,
+    pub(crate) postal_code: String,
+    pub(crate) city: String,
+    pub(crate) province: String,
+    pub(crate) country: String,
+    pub(crate) coordinates: Option<(f64, f64)>,
+    pub(crate) surface_square_meters: Option<i32>,
+    pub(crate) bathrooms: Option<i32>,
+    pub(crate) rooms: Option<i32>,
+    pub(crate) kind_id: Option<i32>,
+    pub(crate) price: Option<i64>,
+    pub(crate) currency: String,
+    pub(crate) operation_id: i32,
+    pub(crate) status_id: i32,
+    pub(crate) description: String,
+    /// Amenity ids and quantities; `None` keeps the current ones.
+    pub(crate) amenities: Option<Vec<(i32, Option<i32>)>>,
+    /// Custom field ids and their text; `None` keeps the current values.
+    pub(crate) custom_values: Option<Vec<(i32, String)>>,
+}
+
+impl Default for HouseDraft {
+    fn default() -> Self {
+        HouseDraft {
+            street: String::new(),
+            street_number: None,
+            street_floor: String::new(),
+            street_unit: String::new(),
+            postal_code: String::new(),
+            city: String::new(),
+            province: String::new(),
+            country: String::new(),
+            coordinates: None,
+            surface_square_meters: None,
+            bathrooms: None,
+            rooms: None,
+            kind_id: None,
+            price: None,
+            currency: utils::CURRENCIES[0].to_string(),
+            operation_id: utils::SALE,
+            status_id: utils::AVAILABLE,
+            description: String::new(),
+            amenities: None,
+            custom_values: None,
+        }
+    }
+}
+
+impl From<&House> for HouseDraft {
+    fn from(house: &House) -> Self {
+        HouseDraft {
+            street: house.street.clone(),
+            street_number: Some(house.street_number),
+            street_floor: house.street_floor.to_string(),
+            street_unit: house.street_unit.clone(),
+            postal_code: house.postal_code.to_string(),
+            city: house.city.clone(),
+            province: house.province.clone(),
+            country: house.country.clone(),
+            coordinates: house.latitude.zip(house.longitude),
+            surface_square_meters: Some(house.surface_square_meters.get()),
+            bathrooms: Some(house.bathrooms.get()),
+            rooms: Some(house.rooms.get()),
+            kind_id: Some(house.kind_id.get()),
+            price: house.price,
+            currency: house.currency.clone(),
+            operation_id: house.operation_id,
+            status_id: house.status_id,
+            description: house.description.clone(),
+            amenities: None,
+            custom_values: None,
+        }
+    }
+}
+
+impl HouseDraft {
+    /// Empty draft of a house for sale, available and priced in pesos.
+    pub fn new() -> HouseDraft {
+        HouseDraft::default()
+    }
+
+    pub fn street(mut self, street: &str) -> Self {
+        self.street = street.to_string();
+        self
+    }
+
+    pub fn street_number(mut self, street_number: i32) -> Self {
+        self.street_number = Some(street_number);
+        self
+    }
+
+    /// Ignored unless the kind is an apartment.
+    pub fn floor(mut self, floor: &str) -> Self {
+        self.street_floor = floor.to_string();
+        self
+    }
+
+    /// Ignored unless the kind is an apartment.
+    pub fn unit(mut self, unit: &str) -> Self {
+        self.street_unit = unit.to_string();
+        self
+    }
+
+    pub fn postal_code(mut self, postal_code: &str) -> Self {
+        self.postal_code = postal_code.to_string();
+        self
+    }
+
+    pub fn city(mut self, city: &str) -> Self {
+        self.city = city.to_string();
+        self
+    }
+
+    pub fn province(mut self, province: &str) -> Self {
+        self.province = province.to_string();
+        self
+    }
+
+    pub fn country(mut self, country: &str) -> Self {
+        self.country = country.to_string();
+        self
+    }
+
+    /// Location in decimal degrees.
+    pub fn coordinates(mut self, latitude: f64, longitude: f64) -> Self {
+        self.coordinates = Some((latitude, longitude));
+        self
+    }
+
+    pub fn without_coordinates(mut self) -> Self {
+        self.coordinates = None;
+        self
+    }
+
+    pub fn surface_square_meters(mut self, surface_square_meters: i32) -> Self {
+        self.surface_square_meters = Some(surface_square_meters);
+        self
+    }
+
+    pub fn bathrooms(mut self, bathrooms: i32) -> Self {
+        self.bathrooms = Some(bathrooms);
+        self
+    }
+
+    pub fn rooms(mut self, rooms: i32) -> Self {
+        self.rooms = Some(rooms);
+        self
+    }
+
+    pub fn kind(mut self, kind_id: i32) -> Self {
+        self.kind_id = Some(kind_id);
+        self
+    }
+
+    /// `None` when the price is not published.
+    pub fn price(mut self, price: Option<i64>) -> Self {
+        self.price = price;
+        self
+    }
+
+    pub fn currency(mut self, currency: &str) -> Self {
+        self.currency = currency.to_string();
+        self
+    }
+
+    pub fn operation(mut self, operation_id: i32) -> Self {
+        self.operation_id = operation_id;
+        self
+    }
+
+    pub fn status(mut self, status_id: i32) -> Self {
+        self.status_id = status_id;
+        self
+    }
+
+    /// Free text about the house, included in the full-text search.
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.trim().to_string();
+        self
+    }
+
+    /// Replaces the amenities of the house with these ids and quantities
+    /// when it is saved, in the same transaction.
+    pub fn amenities(mut self, amenities: &[(i32, Option<i32>)]) -> Self {
+        self.amenities = Some(amenities.to_vec());
+        self
+    }
+
+    /// Replaces the custom values of the house with these field ids and
+    /// texts when it is saved. They're checked for the kind of the house,
+    /// required fields included.
+    pub fn custom_values(mut self, values: &[(i32, String)]) -> Self {
+        self.custom_values = Some(values.to_vec());
+        self
+    }
+
+    /// Normalizes the address and checks every field that doesn't need the
+    /// database.
+    pub fn build(&self) -> Result<NewHouse, ServiceError> {
+        let street_number = required(
+            self.street_number,
+            "Error convritiendo el número de la calle",
+        )?;
+        let surface_square_meters = required(
+            self.surface_square_meters,
+            "Error convritiendo la superficie",
+        )?;
+        let bathrooms = required(self.bathrooms, "Error convritiendo los baños")?;
+        let rooms = required(self.rooms, "Error convritiendo las habitaciones")?;
+        let kind_id = KindId::new(required(
+            self.kind_id,
+            "Error convirtiendo el tipo de casa",
+        )?)
+        .map_err(ServiceError::Validation)?;
+        let (street_floor, street_unit) = if utils::requires_floor(kind_id.get()) {
+            (self.street_floor.as_str(), self.street_unit.as_str())
+        } else {
+            ("", "")
+        };
+        if matches!(self.price, Some(p) if p <= 0) {
+            return Err(ServiceError::Validation("El precio debe ser mayor a cero"));
+        }
+        let currency = self.currency.trim().to_uppercase();
+        if !utils::CURRENCIES.contains(&currency.as_str()) {
+            return Err(ServiceError::Validation("Moneda inválida"));
+        }
+        if self.status_id == utils::SOLD && self.operation_id != utils::SALE {
+            return Err(ServiceError::Validation(
+                "Solo se puede vender un elemento en venta",
+            ));
+        }
+        if self.status_id == utils::RENTED && self.operation_id != utils::RENT {
+            return Err(ServiceError::Validation(
+                "Solo se puede alquilar un elemento en alquiler",
+            ));
+        }
+        if let Some((latitude, longitude)) = self.coordinates {
+            if !(-90.0..=90.0).contains(&latitude) {
+                return Err(ServiceError::Validation(
+                    "La latitud debe estar entre -90 y 90",
+                ));
+            }
+            if !(-180.0..=180.0).contains(&longitude) {
+                return Err(ServiceError::Validation(
+                    "La longitud debe estar entre -180 y 180",
+                ));
+            }
+        }
+        let address = Address {
+            street: self.street.clone(),
+            street_number,
+            floor: street_floor.to_string(),
+            unit: street_unit.to_string(),
+            postal_code: self.postal_code.clone(),
+            city: self.city.clone(),
+            province: self.province.clone(),
+            country: self.country.clone(),
+        }
+        .normalized();
+        Ok(NewHouse {
+            street: address.street,
+            street_number,
+            street_floor: Floor::new(&address.floor).map_err(ServiceError::Validation)?,
+            postal_code: PostalCode::new(&address.postal_code).map_err(ServiceError::Validation)?,
+            surface_square_meters: Surface::new(surface_square_meters)
+                .map_err(ServiceError::Validation)?,
+            bathrooms: RoomCount::new(bathrooms).map_err(ServiceError::Validation)?,
+            rooms: RoomCount::new(rooms).map_err(ServiceError::Validation)?,
+            kind_id,
+            street_unit: address.unit,
+            city: address.city,
+            province: address.province,
+            country: address.country,
+            price: self.price,
+            currency,
+            operation_id: self.operation_id,
+            status_id: self.status_id,
+            latitude: self.coordinates.map(|(latitude, _)| latitude),
+            longitude: self.coordinates.map(|(_, longitude)| longitude),
+            description: self.description.clone(),
+            building_id: None,
+        })
+    }
+}
+
+fn required<T>(value: Option<T>, message: &'static str) -> Result<T, ServiceError> {
+    value.ok_or(ServiceError::Validation(message))
+}
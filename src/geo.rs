@@ -0,0 +1,274 @@
+const EARTH_RADIUS_KM: f64 = 6371.0;
+/// Added to each side of a radius' bounding box so rounding never leaves
+/// out a point on the circle. About 10 cm.
+const BOX_MARGIN_DEGREES: f64 = 1e-6;
+
+/// Point in decimal degrees.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// Area between two latitudes and two longitudes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub south: f64,
+    pub west: f64,
+    pub north: f64,
+    pub east: f64,
+}
+
+/// Circle of `km` kilometers around `center`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Radius {
+    pub center: Coordinates,
+    pub km: f64,
+}
+
+impl Coordinates {
+    pub fn new(latitude: f64, longitude: f64) -> Option<Coordinates> {
+        if (-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude) {
+            Some(Coordinates {
+                latitude,
+                longitude,
+            })
+        } else {
+            None
+        }
+    }
+
+    pub fn distance_km(&self, other: &Coordinates) -> f64 {
+        let (lat1, lat2) = (self.latitude.to_radians(), other.latitude.to_radians());
+        let delta_lat = lat2 - lat1;
+        let delta_lon = (other.longitude - self.longitude).to_radians();
+        let a = (delta_lat / 2.0).sin().powi(2)
+            + lat1.cos() * lat2.cos() * (delta_lon / 2.0).sin().powi(2);
+        2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+    }
+}
+
+impl BoundingBox {
+    pub fn new(south: f64, west: f64, north: f64, east: f64) -> Option<BoundingBox> {
+        let south_west = Coordinates::new(south, west)?;
+        let north_east = Coordinates::new(north, east)?;
+        if south_west.latitude > north_east.latitude {
+            return None;
+        }
+        Some(BoundingBox {
+            south,
+            west,
+            north,
+            east,
+        })
+    }
+
+    /// Whether the box wraps around the antimeridian, i.e. `west` is east of `east`.
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.west > self.east
+    }
+
+    pub fn center(&self) -> Coordinates {
+        let east = if self.crosses_antimeridian() {
+            self.east + 360.0
+        } else {
+            self.east
+        };
+        let mut longitude = (self.west + east) / 2.0;
+        if longitude > 180.0 {
+            longitude -= 360.0;
+        }
+        Coordinates {
+            latitude: (self.south + self.north) / 2.0,
+            longitude,
+        }
+    }
+}
+
+impl Radius {
+    /// Smallest box containing the circle, plus a small margin, used to
+    /// narrow the query before measuring exact distances. Measured on the
+    /// same sphere as [`Coordinates::distance_km`].
+    pub fn bounding_box(&self) -> BoundingBox {
+        let angle = self.km / EARTH_RADIUS_KM;
+        let delta_lat = angle.to_degrees() + BOX_MARGIN_DEGREES;
+        let south = (self.center.latitude - delta_lat).max(-90.0);
+        let north = (self.center.latitude + delta_lat).min(90.0);
+        // The widest point of the circle is not at the center's latitude but
+        // where the meridians are tangent to it.
+        let ratio = angle.sin() / self.center.latitude.to_radians().cos();
+        let delta_lon = if angle < std::f64::consts::FRAC_PI_2 && ratio < 1.0 {
+            ratio.asin().to_degrees() + BOX_MARGIN_DEGREES
+        } else {
+            180.0
+        };
+        if south == -90.0 || north == 90.0 || delta_lon >= 180.0 {
+            return BoundingBox {
+                south,
+                west: -180.0,
+                north,
+                east: 180.0,
+            };
+        }
+        BoundingBox {
+            south,
+            west: wrap_longitude(self.center.longitude - delta_lon),
+            north,
+            east: wrap_longitude(self.center.longitude + delta_lon),
+        }
+    }
+
+    pub fn contains(&self, point: &Coordinates) -> bool {
+        self.center.distance_km(point) <= self.km
+    }
+}
+
+/// Parses a decimal degree, accepting either `.` or `,` as decimal separator.
+pub fn parse_degrees(text: &str) -> Option<f64> {
+    text.trim()
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|value| value.is_finite())
+}
+
+/// Parses "latitude longitude km", separated by spaces or `;`.
+pub fn parse_radius(text: &str) -> Option<Radius> {
+    match parse_numbers(text)?.as_slice() {
+        [latitude, longitude, km] if *km > 0.0 => Some(Radius {
+            center: Coordinates::new(*latitude, *longitude)?,
+            km: *km,
+        }),
+        _ => None,
+    }
+}
+
+/// Parses "south west north east", separated by spaces or `;`.
+pub fn parse_bounding_box(text: &str) -> Option<BoundingBox> {
+    match parse_numbers(text)?.as_slice() {
+        [south, west, north, east] => BoundingBox::new(*south, *west, *north, *east),
+        _ => None,
+    }
+}
+
+fn parse_numbers(text: &str) -> Option<Vec<f64>> {
+    text.split(|c: char| c.is_whitespace() || c == ';')
+        .map(|part| part.trim_end_matches(','))
+        .filter(|part| !part.is_empty())
+        .map(parse_degrees)
+        .collect()
+}
+
+fn wrap_longitude(longitude: f64) -> f64 {
+    if longitude > 180.0 {
+        longitude - 360.0
+    } else if longitude < -180.0 {
+        longitude + 360.0
+    } else {
+        longitude
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Point `km` away from `origin` in the direction `bearing`, in degrees
+    /// clockwise from north.
+    fn destination(origin: &Coordinates, bearing: f64, km: f64) -> Coordinates {
+        let (lat, lon) = (origin.latitude.to_radians(), origin.longitude.to_radians());
+        let (bearing, angle) = (bearing.to_radians(), km / EARTH_RADIUS_KM);
+        let lat2 = (lat.sin() * angle.cos() + lat.cos() * angle.sin() * bearing.cos()).asin();
+        let lon2 = lon
+            + (bearing.sin() * angle.sin() * lat.cos()).atan2(angle.cos() - lat.sin() * lat2.sin());
+        Coordinates {
+            latitude: lat2.to_degrees(),
+            longitude: wrap_longitude(lon2.to_degrees()),
+        }
+    }
+
+    fn in_box(bounds: &BoundingBox, point: &Coordinates) -> bool {
+        let longitude = if bounds.crosses_antimeridian() {
+            point.longitude >= bounds.west || point.longitude <= bounds.east
+        } else {
+            (bounds.west..=bounds.east).contains(&point.longitude)
+        };
+        (bounds.south..=bounds.north).contains(&point.latitude) && longitude
+    }
+
+    #[test]
+    fn box_contains_points_on_the_edge_of_the_circle() {
+        for (latitude, longitude) in [(0.0, 0.0), (-34.6, -58.4), (45.0, 10.0), (-70.0, 120.0)] {
+            for km in [0.5, 10.0, 250.0] {
+                let radius = Radius {
+                    center: Coordinates::new(latitude, longitude).unwrap(),
+                    km,
+                };
+                let bounds = radius.bounding_box();
+                for step in 0..72 {
+                    let point = destination(&radius.center, step as f64 * 5.0, km * 0.999_999);
+                    assert!(radius.contains(&point), "{radius:?} {point:?}");
+                    assert!(in_box(&bounds, &point), "{radius:?} {bounds:?} {point:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn box_keeps_a_point_just_inside_the_north_edge() {
+        let radius = Radius {
+            center: Coordinates::new(0.0, 0.0).unwrap(),
+            km: 10.0,
+        };
+        let point = Coordinates::new(0.0899, 0.0).unwrap();
+        assert!(radius.contains(&point));
+        assert!(in_box(&radius.bounding_box(), &point));
+    }
+
+    #[test]
+    fn box_is_barely_larger_than_the_circle() {
+        let radius = Radius {
+            center: Coordinates::new(-34.6, -58.4).unwrap(),
+            km: 10.0,
+        };
+        let bounds = radius.bounding_box();
+        let north = destination(&radius.center, 0.0, 10.0);
+        assert!((bounds.north - north.latitude).abs() < 1e-5);
+        let outside = destination(&radius.center, 0.0, 10.01);
+        assert!(!in_box(&bounds, &outside));
+    }
+
+    #[test]
+    fn box_wraps_around_the_antimeridian() {
+        let radius = Radius {
+            center: Coordinates::new(-17.0, 179.95).unwrap(),
+            km: 20.0,
+        };
+        let bounds = radius.bounding_box();
+        assert!(bounds.crosses_antimeridian());
+        assert!(in_box(&bounds, &destination(&radius.center, 90.0, 19.0)));
+    }
+
+    #[test]
+    fn box_covers_every_longitude_near_a_pole() {
+        let radius = Radius {
+            center: Coordinates::new(89.95, 30.0).unwrap(),
+            km: 20.0,
+        };
+        let bounds = radius.bounding_box();
+        assert_eq!(
+            (bounds.west, bounds.north, bounds.east),
+            (-180.0, 90.0, 180.0)
+        );
+    }
+
+    #[test]
+    fn parses_radius_and_box_with_either_decimal_separator() {
+        let radius = parse_radius("-34,60; -58,38 5").unwrap();
+        assert_eq!(radius.center, Coordinates::new(-34.6, -58.38).unwrap());
+        assert_eq!(radius.km, 5.0);
+        assert!(parse_radius("-34.6 -58.38 0").is_none());
+        assert!(parse_bounding_box("-35 -59 -34 -58").is_some());
+        assert!(parse_bounding_box("-34 -59 -35 -58").is_none());
+    }
+}
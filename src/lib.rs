@@ -0,0 +1,43 @@
+//! Core of the house inventory: models, database access and the business
+//! rules shared by the GUI, the command line, the terminal interface and
+//! the REST API.
+//!
+//! The entry point is [`service::HouseService`], built on top of a
+//! [`repository::HouseRepository`] connected to the SQLite database:
+//!
+//! ```no_run
+//! use app_gui::models::{HouseFilter, HouseOrder};
+//! use app_gui::repository::HouseRepository;
+//! use app_gui::service::HouseService;
+//!
+//! let repository = HouseRepository::establish("house.sqlite").unwrap();
+//! let mut service = HouseService::new(repository);
+//! let houses = service
+//!     .get_houses_filtered(HouseOrder::Id, &HouseFilter::default())
+//!     .unwrap();
+//! ```
+
+/// Address normalization and duplicate detection keys.
+pub mod address;
+/// Builder for new and edited houses.
+pub mod draft;
+/// Coordinates, bounding boxes and distance search.
+pub mod geo;
+/// Database rows, lookup tables and query options.
+pub mod models;
+/// Filter language for house listings, e.g. `kind:departamento rooms>=3`.
+pub mod query;
+/// Inventory statistics.
+pub mod report;
+/// Queries over the SQLite database.
+pub mod repository;
+/// Diesel table definitions.
+pub mod schema;
+/// Validation and business rules on top of the repository.
+pub mod service;
+/// Printable HTML and PDF listing sheets.
+pub mod sheet;
+/// Validated domain values stored in the house columns.
+pub mod types;
+/// Lookup ids and small helpers shared by the front ends.
+pub mod utils;
@@ -1,22 +1,42 @@
-mod models;
+#[cfg(feature = "server")]
+mod api;
+#[cfg(feature = "gui")]
+mod chart;
+mod cli;
+#[cfg(feature = "gui")]
 mod presentation;
-mod repository;
-mod schema;
-mod service;
-mod utils;
-use diesel::prelude::*;
-use diesel::SqliteConnection;
+#[cfg(feature = "tui")]
+mod tui;
+use app_gui::repository::HouseRepository;
+use app_gui::service::{DuplicatePolicy, HouseService};
 
 use std::env;
+use std::process;
 
 fn main() {
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
-    let conn = SqliteConnection::establish(&database_url)
-        .expect(&format!("Error connecting to {}", database_url));
-    let repository = repository::HouseRepository { conn };
-    let service = service::HouseService::new(repository);
+    let repository = HouseRepository::establish(&database_url)
+        .unwrap_or_else(|_| panic!("Error connecting to {}", database_url));
+    let mut service = HouseService::new(repository);
+    if let Err(error) = service.upgrade_data() {
+        eprintln!("Error actualizando la DB: {error}");
+        process::exit(1);
+    }
+    if env::var("DUPLICATE_POLICY").as_deref() == Ok("refuse") {
+        service.duplicate_policy = DuplicatePolicy::Refuse;
+    }
 
-    let mut gui = presentation::GUI::new(service);
-    gui.build();
-    gui.show();
+    let args: Vec<String> = env::args().skip(1).collect();
+    #[cfg(feature = "gui")]
+    if args.is_empty() {
+        let mut gui = presentation::GUI::new(service);
+        gui.build();
+        gui.show();
+        return;
+    }
+
+    if let Err(message) = cli::run(&mut service, &args) {
+        eprintln!("{message}");
+        process::exit(1);
+    }
 }
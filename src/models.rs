@@ -1,73 +1,251 @@
-use diesel::{Insertable, Queryable, Selectable};
+use std::fmt;
+use std::str::FromStr;
 
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::backend::RawValue;
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::Text;
+use diesel::sqlite::Sqlite;
+use diesel::{AsChangeset, Insertable, Queryable, Selectable};
+use serde::Serialize;
+
+use crate::address::Address;
+use crate::geo::{BoundingBox, Coordinates, Radius};
+use crate::query::Query;
+use crate::schema::amenities;
+use crate::schema::buildings;
+use crate::schema::contacts;
+use crate::schema::contacts_role;
+use crate::schema::custom_fields;
+use crate::schema::custom_fields_kind;
 use crate::schema::houses;
+use crate::schema::houses_amenities;
+use crate::schema::houses_attachments;
+use crate::schema::houses_contacts;
+use crate::schema::houses_custom_values;
 use crate::schema::houses_kind;
-use crate::utils;
+use crate::schema::houses_operation;
+use crate::schema::houses_status;
+use crate::schema::houses_status_history;
+use crate::schema::houses_tags;
+use crate::schema::saved_searches;
+use crate::schema::tags;
+use crate::types::{Floor, HouseId, KindId, PostalCode, RoomCount, Surface};
 
-#[derive(Debug, Queryable, Selectable)]
+/// Kind of property, e.g. house or apartment.
+#[derive(Debug, Queryable, Selectable, Serialize)]
 #[diesel(table_name = houses_kind)]
 pub struct HouseKind {
-    pub id: i32,
+    pub id: KindId,
     pub kind: String,
 }
 
+/// Whether a house is listed for sale or for rent.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = houses_operation)]
+pub struct HouseOperation {
+    pub id: i32,
+    pub operation: String,
+}
+
+/// Listing status, e.g. available, reserved or sold.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = houses_status)]
+pub struct HouseStatus {
+    pub id: i32,
+    pub status: String,
+}
+
+/// Row of the `houses` table.
 #[derive(Debug, Queryable, Selectable)]
 #[diesel(table_name = houses)]
 #[diesel(belongs_to(HouseKind))]
 pub struct House {
-    pub id: i32,
+    pub id: HouseId,
     pub street: String,
     pub street_number: i32,
-    pub street_floor: String,
-    pub postal_code: String,
-    pub surface_square_meters: i32,
-    pub bathrooms: i32,
-    pub rooms: i32,
-    pub kind_id: i32,
+    pub street_floor: Floor,
+    pub postal_code: PostalCode,
+    pub surface_square_meters: Surface,
+    pub bathrooms: RoomCount,
+    pub rooms: RoomCount,
+    pub kind_id: KindId,
+    pub version: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub street_unit: String,
+    pub city: String,
+    pub province: String,
+    pub country: String,
+    pub price: Option<i64>,
+    pub currency: String,
+    pub operation_id: i32,
+    pub status_id: i32,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub description: String,
+    pub building_id: Option<i32>,
 }
 
+/// Values of a house to insert; the id and timestamps are set by the database.
 #[derive(Debug, Insertable)]
 #[diesel(table_name = houses)]
 #[diesel(belongs_to(HouseKind))]
 pub struct NewHouse {
     pub street: String,
     pub street_number: i32,
-    pub street_floor: String,
-    pub postal_code: String,
-    pub surface_square_meters: i32,
-    pub bathrooms: i32,
-    pub rooms: i32,
-    pub kind_id: i32,
+    pub street_floor: Floor,
+    pub postal_code: PostalCode,
+    pub surface_square_meters: Surface,
+    pub bathrooms: RoomCount,
+    pub rooms: RoomCount,
+    pub kind_id: KindId,
+    pub street_unit: String,
+    pub city: String,
+    pub province: String,
+    pub country: String,
+    pub price: Option<i64>,
+    pub currency: String,
+    pub operation_id: i32,
+    pub status_id: i32,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub description: String,
+    /// Set by the service for apartments, from their address.
+    pub building_id: Option<i32>,
 }
 
-#[derive(Debug)]
+/// House joined with the names of its kind, operation and status, as listed
+/// by the front ends.
+#[derive(Debug, Clone, Serialize)]
 pub struct HouseWithKind {
-    pub id: i32,
+    pub id: HouseId,
     pub street: String,
     pub street_number: i32,
-    pub street_floor: String,
-    pub postal_code: String,
-    pub surface_square_meters: i32,
-    pub bathrooms: i32,
-    pub rooms: i32,
-    pub kind_id: i32,
+    pub street_floor: Floor,
+    pub postal_code: PostalCode,
+    pub surface_square_meters: Surface,
+    pub bathrooms: RoomCount,
+    pub rooms: RoomCount,
+    pub kind_id: KindId,
     pub kind: String,
+    pub version: i32,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub street_unit: String,
+    pub city: String,
+    pub province: String,
+    pub country: String,
+    pub price: Option<i64>,
+    pub currency: String,
+    pub operation_id: i32,
+    pub status_id: i32,
+    pub operation: String,
+    pub status: String,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub description: String,
+    pub building_id: Option<i32>,
 }
 
-impl ToString for HouseWithKind {
-    fn to_string(&self) -> String {
-        let address = if utils::requires_floor(self.kind_id) {
-            let floor = if self.street_floor != "" {
-                format!(" (piso {}", self.street_floor)
-            } else {
-                format!("")
-            };
-            format!("{} {}{}", self.street, self.street_number, floor)
-        } else {
-            format!("{} {}", self.street, self.street_number)
+/// Entry to add to the status history of a house.
+#[derive(Debug, Insertable)]
+#[diesel(table_name = houses_status_history)]
+pub struct NewHouseStatusChange {
+    pub house_id: HouseId,
+    pub status_id: i32,
+    pub changed_at: NaiveDateTime,
+}
+
+/// Past status of a house and when it was set.
+#[derive(Debug, Queryable)]
+pub struct HouseStatusChange {
+    pub status: String,
+    pub changed_at: NaiveDateTime,
+}
+
+/// Order of house listings, stored by the name [`HouseOrder::as_str`] gives.
+#[derive(Debug, Clone, Copy, PartialEq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub enum HouseOrder {
+    Id,
+    RecentlyCreated,
+    RecentlyUpdated,
+    Distance,
+}
+
+/// Optional conditions on house listings; the default matches every house.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HouseFilter {
+    pub kind_id: Option<i32>,
+    pub operation_id: Option<i32>,
+    pub status_id: Option<i32>,
+    pub bounds: Option<BoundingBox>,
+    pub near: Option<Radius>,
+    pub query: Query,
+}
+
+impl House {
+    pub fn address(&self) -> Address {
+        Address {
+            street: self.street.clone(),
+            street_number: self.street_number,
+            floor: self.street_floor.to_string(),
+            unit: self.street_unit.clone(),
+            postal_code: self.postal_code.to_string(),
+            city: self.city.clone(),
+            province: self.province.clone(),
+            country: self.country.clone(),
+        }
+    }
+}
+
+impl NewHouse {
+    pub fn address(&self) -> Address {
+        Address {
+            street: self.street.clone(),
+            street_number: self.street_number,
+            floor: self.street_floor.to_string(),
+            unit: self.street_unit.clone(),
+            postal_code: self.postal_code.to_string(),
+            city: self.city.clone(),
+            province: self.province.clone(),
+            country: self.country.clone(),
+        }
+    }
+}
+
+impl HouseWithKind {
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        Coordinates::new(self.latitude?, self.longitude?)
+    }
+
+    pub fn address(&self) -> Address {
+        Address {
+            street: self.street.clone(),
+            street_number: self.street_number,
+            floor: self.street_floor.to_string(),
+            unit: self.street_unit.clone(),
+            postal_code: self.postal_code.to_string(),
+            city: self.city.clone(),
+            province: self.province.clone(),
+            country: self.country.clone(),
+        }
+    }
+}
+
+impl fmt::Display for HouseWithKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let address = self.address();
+        let price = match self.price {
+            Some(price) => format!(" {} {}", self.currency, price),
+            None => String::new(),
         };
-        format!(
-            "#{}: {} CP: {}. Con {} baño/s ,{} habitación/es. Tipo \"{}\" ({}m2)",
+        write!(
+            f,
+            "#{}: {} CP: {}. Con {} baño/s ,{} habitación/es. Tipo \"{}\" ({}m2). {}{} ({})",
             self.id,
             address,
             self.postal_code,
@@ -75,6 +253,472 @@ impl ToString for HouseWithKind {
             self.rooms,
             self.kind,
             self.surface_square_meters,
+            self.operation,
+            price,
+            self.status,
         )
     }
 }
+
+/// Field that can be set on several houses at once.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HouseField {
+    Street,
+    StreetNumber,
+    PostalCode,
+    SurfaceSquareMeters,
+    Bathrooms,
+    Rooms,
+}
+
+/// New value for a field of several houses at once.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HouseChange {
+    Street(String),
+    StreetNumber(i32),
+    PostalCode(PostalCode),
+    SurfaceSquareMeters(Surface),
+    Bathrooms(RoomCount),
+    Rooms(RoomCount),
+    Kind(KindId),
+}
+
+/// Person related to one or more houses.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = contacts)]
+pub struct Contact {
+    pub id: i32,
+    pub name: String,
+    pub phone: String,
+    pub email: String,
+    pub notes: String,
+}
+
+/// Values of a contact to insert.
+#[derive(Debug, Insertable)]
+#[diesel(table_name = contacts)]
+pub struct NewContact {
+    pub name: String,
+    pub phone: String,
+    pub email: String,
+    pub notes: String,
+}
+
+/// Role of a contact on a house, e.g. owner, tenant or agent.
+#[derive(Debug, Queryable, Selectable)]
+#[diesel(table_name = contacts_role)]
+pub struct ContactRole {
+    pub id: i32,
+    pub role: String,
+}
+
+/// Link between a house and a contact with a role.
+#[derive(Debug, Insertable, Queryable)]
+#[diesel(table_name = houses_contacts)]
+pub struct HouseContact {
+    pub house_id: HouseId,
+    pub contact_id: i32,
+    pub role_id: i32,
+}
+
+/// Contact of a house with the name of its role.
+#[derive(Debug, Clone, Queryable)]
+pub struct LinkedContact {
+    pub contact_id: i32,
+    pub name: String,
+    pub phone: String,
+    pub email: String,
+    pub role_id: i32,
+    pub role: String,
+}
+
+impl fmt::Display for Contact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{}: {}", self.id, self.name)?;
+        let details: Vec<&str> = [self.phone.as_str(), self.email.as_str()]
+            .into_iter()
+            .filter(|d| !d.is_empty())
+            .collect();
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for LinkedContact {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {} (#{})", self.role, self.name, self.contact_id)?;
+        let details: Vec<&str> = [self.phone.as_str(), self.email.as_str()]
+            .into_iter()
+            .filter(|d| !d.is_empty())
+            .collect();
+        if !details.is_empty() {
+            write!(f, " {}", details.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Photo or document stored with a house.
+#[derive(Debug, Clone, Queryable, Selectable)]
+#[diesel(table_name = houses_attachments)]
+pub struct Attachment {
+    pub id: i32,
+    pub house_id: HouseId,
+    pub file_name: String,
+    pub mime_type: String,
+    pub caption: String,
+    pub position: i32,
+    pub data: Vec<u8>,
+    pub created_at: NaiveDateTime,
+}
+
+/// Values of an attachment to insert.
+#[derive(Debug, Insertable)]
+#[diesel(table_name = houses_attachments)]
+pub struct NewAttachment {
+    pub house_id: HouseId,
+    pub file_name: String,
+    pub mime_type: String,
+    pub caption: String,
+    pub data: Vec<u8>,
+}
+
+impl HouseOrder {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HouseOrder::Id => "id",
+            HouseOrder::RecentlyCreated => "created",
+            HouseOrder::RecentlyUpdated => "updated",
+            HouseOrder::Distance => "distance",
+        }
+    }
+}
+
+impl FromStr for HouseOrder {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<HouseOrder, Self::Err> {
+        match text {
+            "id" => Ok(HouseOrder::Id),
+            "created" => Ok(HouseOrder::RecentlyCreated),
+            "updated" => Ok(HouseOrder::RecentlyUpdated),
+            "distance" => Ok(HouseOrder::Distance),
+            _ => Err("Orden inválido"),
+        }
+    }
+}
+
+impl ToSql<Text, Sqlite> for HouseOrder {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for HouseOrder {
+    fn from_sql(bytes: RawValue<'_, Sqlite>) -> deserialize::Result<Self> {
+        let text = <String as FromSql<Text, Sqlite>>::from_sql(bytes)?;
+        Ok(text.parse()?)
+    }
+}
+
+/// Filter and order of the house list saved under a name. `query` uses the
+/// [filter language](crate::query::Query), `search` is full text and `near`
+/// a radius or an area as typed in the "Cerca de" box.
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable)]
+#[diesel(table_name = saved_searches)]
+pub struct SavedSearch {
+    pub id: i32,
+    pub name: String,
+    pub query: String,
+    pub search: String,
+    pub operation_id: Option<i32>,
+    pub status_id: Option<i32>,
+    pub near: String,
+    pub sort_order: HouseOrder,
+}
+
+/// Values of a saved search to insert or replace by name.
+#[derive(Debug, Clone, PartialEq, Insertable, AsChangeset)]
+#[diesel(table_name = saved_searches, treat_none_as_null = true)]
+pub struct NewSavedSearch {
+    pub name: String,
+    pub query: String,
+    pub search: String,
+    pub operation_id: Option<i32>,
+    pub status_id: Option<i32>,
+    pub near: String,
+    pub sort_order: HouseOrder,
+}
+
+impl fmt::Display for SavedSearch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Free-form label shared by any number of houses.
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable)]
+#[diesel(table_name = tags)]
+pub struct Tag {
+    pub id: i32,
+    pub name: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = tags)]
+pub struct NewTag {
+    pub name: String,
+}
+
+/// Link between a house and a tag.
+#[derive(Debug, Insertable, Queryable)]
+#[diesel(table_name = houses_tags)]
+pub struct HouseTag {
+    pub house_id: HouseId,
+    pub tag_id: i32,
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Characteristic a house can have, e.g. a garage or a pool. Countable ones
+/// can say how many, e.g. parking spaces.
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable)]
+#[diesel(table_name = amenities)]
+pub struct Amenity {
+    pub id: i32,
+    pub amenity: String,
+    pub countable: bool,
+}
+
+/// Amenity of a house, with its quantity when it is countable and known.
+#[derive(Debug, Clone, PartialEq, Insertable, Queryable)]
+#[diesel(table_name = houses_amenities)]
+pub struct HouseAmenity {
+    pub house_id: HouseId,
+    pub amenity_id: i32,
+    pub quantity: Option<i32>,
+}
+
+/// Building shared by apartments. Its address is copied to each of its
+/// units, which add their floor and unit.
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable, Serialize)]
+#[diesel(table_name = buildings)]
+pub struct Building {
+    pub id: i32,
+    pub street: String,
+    pub street_number: i32,
+    pub postal_code: PostalCode,
+    pub city: String,
+    pub province: String,
+    pub country: String,
+    pub year_built: Option<i32>,
+    pub elevator: bool,
+}
+
+/// Values of a building to insert or update.
+#[derive(Debug, Clone, PartialEq, Insertable, AsChangeset)]
+#[diesel(table_name = buildings, treat_none_as_null = true)]
+pub struct NewBuilding {
+    pub street: String,
+    pub street_number: i32,
+    pub postal_code: PostalCode,
+    pub city: String,
+    pub province: String,
+    pub country: String,
+    pub year_built: Option<i32>,
+    pub elevator: bool,
+}
+
+impl Building {
+    pub fn address(&self) -> Address {
+        Address {
+            street: self.street.clone(),
+            street_number: self.street_number,
+            postal_code: self.postal_code.to_string(),
+            city: self.city.clone(),
+            province: self.province.clone(),
+            country: self.country.clone(),
+            ..Address::default()
+        }
+    }
+}
+
+impl fmt::Display for Building {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.address())
+    }
+}
+
+/// Type of the values of a custom field.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, AsExpression, FromSqlRow)]
+#[diesel(sql_type = Text)]
+pub enum CustomFieldType {
+    Integer,
+    Decimal,
+    Text,
+    Bool,
+    Date,
+    /// One of the options of the field.
+    Enum,
+}
+
+impl CustomFieldType {
+    pub const ALL: [CustomFieldType; 6] = [
+        CustomFieldType::Integer,
+        CustomFieldType::Decimal,
+        CustomFieldType::Text,
+        CustomFieldType::Bool,
+        CustomFieldType::Date,
+        CustomFieldType::Enum,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CustomFieldType::Integer => "integer",
+            CustomFieldType::Decimal => "decimal",
+            CustomFieldType::Text => "text",
+            CustomFieldType::Bool => "bool",
+            CustomFieldType::Date => "date",
+            CustomFieldType::Enum => "enum",
+        }
+    }
+}
+
+impl FromStr for CustomFieldType {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<CustomFieldType, Self::Err> {
+        CustomFieldType::ALL
+            .into_iter()
+            .find(|t| t.as_str() == text)
+            .ok_or("Tipo de campo inválido")
+    }
+}
+
+impl ToSql<Text, Sqlite> for CustomFieldType {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+        <str as ToSql<Text, Sqlite>>::to_sql(self.as_str(), out)
+    }
+}
+
+impl FromSql<Text, Sqlite> for CustomFieldType {
+    fn from_sql(bytes: RawValue<'_, Sqlite>) -> deserialize::Result<Self> {
+        let text = <String as FromSql<Text, Sqlite>>::from_sql(bytes)?;
+        Ok(text.parse()?)
+    }
+}
+
+/// Attribute defined by the administrators for some kinds of property, e.g.
+/// the monthly fees of an apartment. `options` holds the choices of an enum
+/// field separated by `|`.
+#[derive(Debug, Clone, PartialEq, Queryable, Selectable)]
+#[diesel(table_name = custom_fields)]
+pub struct CustomField {
+    pub id: i32,
+    pub name: String,
+    pub field_type: CustomFieldType,
+    pub required: bool,
+    pub options: String,
+}
+
+#[derive(Debug, Insertable)]
+#[diesel(table_name = custom_fields)]
+pub struct NewCustomField {
+    pub name: String,
+    pub field_type: CustomFieldType,
+    pub required: bool,
+    pub options: String,
+}
+
+/// Kind of property a custom field applies to.
+#[derive(Debug, Clone, PartialEq, Insertable, Queryable)]
+#[diesel(table_name = custom_fields_kind)]
+pub struct CustomFieldKind {
+    pub field_id: i32,
+    pub kind_id: i32,
+}
+
+/// Value of a custom field for a house, as returned by
+/// [`CustomField::parse_value`].
+#[derive(Debug, Clone, PartialEq, Insertable, Queryable)]
+#[diesel(table_name = houses_custom_values)]
+pub struct CustomValue {
+    pub house_id: HouseId,
+    pub field_id: i32,
+    pub value: String,
+}
+
+impl CustomField {
+    pub fn options(&self) -> Vec<&str> {
+        self.options.split('|').filter(|o| !o.is_empty()).collect()
+    }
+
+    /// Checks `text` as typed by a user and gives the value to store, `None`
+    /// when it's empty. Dates are stored as `aaaa-mm-dd`, booleans as `true`
+    /// or `false` and options as written in the field.
+    pub fn parse_value(&self, text: &str) -> Result<Option<String>, &'static str> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let value = match self.field_type {
+            CustomFieldType::Integer => text
+                .parse::<i64>()
+                .map_err(|_| "Número entero inválido")?
+                .to_string(),
+            CustomFieldType::Decimal => {
+                let number = text
+                    .replace(',', ".")
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|n| n.is_finite())
+                    .ok_or("Número decimal inválido")?;
+                number.to_string()
+            }
+            CustomFieldType::Text => text.to_string(),
+            CustomFieldType::Bool => match text.to_lowercase().as_str() {
+                "sí" | "si" | "s" | "true" | "1" => "true".to_string(),
+                "no" | "n" | "false" | "0" => "false".to_string(),
+                _ => return Err("Valor inválido, use sí o no"),
+            },
+            CustomFieldType::Date => NaiveDate::parse_from_str(text, "%d/%m/%Y")
+                .or_else(|_| NaiveDate::parse_from_str(text, "%Y-%m-%d"))
+                .map_err(|_| "Fecha inválida, use dd/mm/aaaa")?
+                .format("%Y-%m-%d")
+                .to_string(),
+            CustomFieldType::Enum => self
+                .options()
+                .into_iter()
+                .find(|o| o.to_lowercase() == text.to_lowercase())
+                .ok_or("Opción inválida")?
+                .to_string(),
+        };
+        Ok(Some(value))
+    }
+
+    /// Stored `value` as shown to users, the way [`parse_value`] accepts it.
+    ///
+    /// [`parse_value`]: CustomField::parse_value
+    pub fn format_value(&self, value: &str) -> String {
+        match self.field_type {
+            CustomFieldType::Bool if value == "true" => "Sí".to_string(),
+            CustomFieldType::Bool => "No".to_string(),
+            CustomFieldType::Date => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .map(|date| date.format("%d/%m/%Y").to_string())
+                .unwrap_or_else(|_| value.to_string()),
+            _ => value.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CustomField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
@@ -1,8 +1,19 @@
+use std::cell::RefCell;
+use std::fs;
+use std::path::Path;
+use std::rc::Rc;
+
 use fltk::{
     app::{self, App},
-    button::Button,
+    button::{Button, CheckButton},
+    dialog,
+    frame::Frame,
+    group::{Pack, PackType, Scroll, ScrollType},
+    image::{BmpImage, GifImage, JpegImage, PngImage},
     menu::Choice,
-    prelude::{GroupExt, WidgetExt},
+    misc::InputChoice,
+    prelude::{FltkError, GroupExt, ImageExt, WidgetExt},
+    text::{TextBuffer, TextDisplay},
     window::DoubleWindow,
 };
 
@@ -11,6 +22,7 @@ use fltk::{app::*, browser::*, enums::*, input::*, output::*, prelude::*, window
 const WIDGET_WIDTH: i32 = 120;
 const WIDGET_HEIGHT: i32 = 25;
 const WIDGET_PADDING: i32 = 10;
+const THUMBNAIL_SIZE: i32 = 80;
 
 #[derive(Clone, Copy)]
 enum Message {
@@ -19,10 +31,61 @@ enum Message {
     Delete,
     Select,
     Filter,
+    /// The houses changed: recount the saved searches, then filter.
+    Refresh,
     Save,
+    ChangeKind,
+    SetField,
+    Duplicates,
+    History,
+    ContactsOpen,
+    ContactSelect,
+    ContactNew,
+    ContactSave,
+    ContactDelete,
+    LinkedContactSelect,
+    LinkContact,
+    UnlinkContact,
+    AttachmentAdd,
+    AttachmentSelect(i32),
+    AttachmentMove(isize),
+    AttachmentRemove,
+    Stats,
+    ChartsOpen,
+    ChartRefresh,
+    ChartExport,
+    PrintSheet,
+    SavedSearchSelect,
+    SavedSearchSave,
+    SavedSearchDelete,
+    TagSelect,
+    TagAdd,
+    TagRemove,
+    TagRename,
+    KindChange,
+    BuildingsOpen,
+    BuildingSelect,
+    BuildingNew,
+    BuildingSave,
+    BuildingDelete,
+    BuildingUnits,
 }
 
-use crate::{service::HouseService, utils::APARTMENT};
+use crate::chart::{Chart, ChartKind};
+use app_gui::{
+    address::Address,
+    draft::HouseDraft,
+    geo,
+    models::{
+        Attachment, Building, Contact, CustomField, CustomFieldType, HouseField, HouseFilter,
+        HouseOrder, HouseWithKind, LinkedContact, NewSavedSearch, SavedSearch, Tag,
+    },
+    query::{ParseError, Query},
+    service::{DuplicatePolicy, HouseForm, HouseService, ServiceError},
+    sheet::{self, SheetFormat},
+    types::HouseId,
+    utils::{self, APARTMENT, AVAILABLE},
+};
 
 pub struct GUI {
     app: App,
@@ -31,39 +94,631 @@ pub struct GUI {
     receiver: Receiver<Message>,
     service: HouseService,
     filter_input: Input,
-    list_browser: HoldBrowser,
+    order_input: Choice,
+    operation_filter: Choice,
+    status_filter: Choice,
+    search_input: Input,
+    near_input: Input,
+    saved_searches_browser: HoldBrowser,
+    save_search_button: Button,
+    delete_search_button: Button,
+    list_browser: MultiBrowser,
     id_output: Output,
     street_input: Input,
     street_number_input: Input,
     street_floor_input: Input,
+    street_unit_input: Input,
     postal_code_input: Input,
+    city_input: Input,
+    province_input: Input,
+    country_input: Input,
+    latitude_input: Input,
+    longitude_input: Input,
     surface_input: Input,
     bathrooms_input: Input,
     rooms_input: Input,
     kind_input: Choice,
+    operation_input: Choice,
+    status_input: Choice,
+    price_input: Input,
+    currency_input: Choice,
+    history_button: Button,
+    created_output: Output,
+    updated_output: Output,
+    description_input: MultilineInput,
     create_button: Button,
     update_button: Button,
     delete_button: Button,
     save_button: Button,
+    change_kind_button: Button,
+    bulk_field_input: Choice,
+    set_field_button: Button,
+    duplicates_button: Button,
+    stats_button: Button,
+    charts_button: Button,
+    print_button: Button,
+    contacts_button: Button,
+    buildings_button: Button,
+    linked_contacts_browser: HoldBrowser,
+    contact_input: Choice,
+    role_input: Choice,
+    link_button: Button,
+    unlink_button: Button,
+    tags_browser: HoldBrowser,
+    tag_input: InputChoice,
+    tag_button: Button,
+    untag_button: Button,
+    rename_tag_button: Button,
+    attachments_scroll: Scroll,
+    attachments_pack: Pack,
+    attach_button: Button,
+    move_back_button: Button,
+    move_forward_button: Button,
+    remove_attachment_button: Button,
+    message_output: Output,
+    contacts_panel: ContactsPanel,
+    buildings_panel: BuildingsPanel,
+    chart_panel: ChartPanel,
+    contacts: Vec<Contact>,
+    buildings: Vec<Building>,
+    linked_contacts: Vec<LinkedContact>,
+    attachments: Vec<Attachment>,
+    selected_attachment: Option<i32>,
+    selected_version: i32,
+    saved_searches: Vec<SavedSearch>,
+    house_tags: Vec<Tag>,
+    amenity_inputs: Vec<AmenityInput>,
+    custom_fields_scroll: Scroll,
+    custom_fields_pack: Pack,
+    custom_inputs: Vec<CustomInput>,
+}
+
+/// Checkbox of an amenity in the form, with its quantity when countable.
+struct AmenityInput {
+    amenity_id: i32,
+    check: CheckButton,
+    quantity: Option<Input>,
+}
+
+/// Widget of a custom field in the form, chosen by the field type.
+struct CustomInput {
+    field: CustomField,
+    widget: CustomWidget,
+}
+
+enum CustomWidget {
+    Text(Input),
+    Check(CheckButton),
+    /// Options of the field, after an empty one when it isn't required.
+    Choice(Choice),
+}
+
+impl CustomInput {
+    /// Index of the first option in the choice, after the empty one.
+    fn first_option(&self) -> i32 {
+        if self.field.required {
+            0
+        } else {
+            1
+        }
+    }
+
+    /// Text of the value, as [`CustomField::parse_value`] takes it.
+    fn value(&self) -> String {
+        match &self.widget {
+            CustomWidget::Text(input) => input.value(),
+            CustomWidget::Check(check) => check.is_checked().to_string(),
+            CustomWidget::Choice(choice) => usize::try_from(choice.value() - self.first_option())
+                .ok()
+                .and_then(|i| self.field.options().get(i).map(|o| o.to_string()))
+                .unwrap_or_default(),
+        }
+    }
+
+    fn set_value(&mut self, text: &str) {
+        let parsed = self.field.parse_value(text).ok().flatten();
+        let first_option = self.first_option();
+        match &mut self.widget {
+            CustomWidget::Text(input) => match parsed {
+                Some(value) => input.set_value(&self.field.format_value(&value)),
+                None => input.set_value(text),
+            },
+            CustomWidget::Check(check) => check.set_checked(parsed.as_deref() == Some("true")),
+            CustomWidget::Choice(choice) => {
+                let position =
+                    parsed.and_then(|value| self.field.options().iter().position(|o| *o == value));
+                choice.set_value(position.map_or(first_option - 1, |p| p as i32 + first_option));
+            }
+        }
+    }
+}
+
+struct ContactsPanel {
+    wind: DoubleWindow,
+    list_browser: HoldBrowser,
+    id_output: Output,
+    name_input: Input,
+    phone_input: Input,
+    email_input: Input,
+    notes_input: MultilineInput,
+    new_button: Button,
+    save_button: Button,
+    delete_button: Button,
+    message_output: Output,
+}
+
+struct BuildingsPanel {
+    wind: DoubleWindow,
+    list_browser: HoldBrowser,
+    id_output: Output,
+    street_input: Input,
+    street_number_input: Input,
+    postal_code_input: Input,
+    city_input: Input,
+    province_input: Input,
+    country_input: Input,
+    year_built_input: Input,
+    elevator_check: CheckButton,
+    units_browser: HoldBrowser,
+    new_button: Button,
+    save_button: Button,
+    delete_button: Button,
+    units_button: Button,
     message_output: Output,
 }
 
+struct ChartPanel {
+    wind: DoubleWindow,
+    kind_input: Choice,
+    canvas: Frame,
+    export_button: Button,
+    chart: Rc<RefCell<Chart>>,
+}
+
+impl ChartPanel {
+    fn new() -> ChartPanel {
+        let wind = Window::default().with_label("Gráficos");
+
+        let mut kind_input = Choice::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .with_pos(WIDGET_PADDING + WIDGET_WIDTH, WIDGET_PADDING)
+            .with_label("Gráfico:");
+        kind_input.add_choice("Por tipo|Por código postal|Superficie");
+        kind_input.set_value(0);
+
+        let export_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&kind_input, WIDGET_PADDING)
+            .with_label("Exportar PNG");
+
+        let mut canvas = Frame::default()
+            .with_size(WIDGET_WIDTH * 6, WIDGET_HEIGHT * 16)
+            .with_pos(
+                WIDGET_PADDING,
+                kind_input.y() + kind_input.height() + WIDGET_PADDING,
+            );
+        let chart = Rc::new(RefCell::new(Chart::default()));
+        let drawn_chart = chart.clone();
+        canvas.draw(move |c| {
+            drawn_chart
+                .borrow()
+                .draw(c.x(), c.y(), c.width(), c.height())
+        });
+
+        let wind = wind.with_size(
+            canvas.x() + canvas.width() + WIDGET_PADDING,
+            canvas.y() + canvas.height() + WIDGET_PADDING,
+        );
+        wind.end();
+
+        ChartPanel {
+            wind,
+            kind_input,
+            canvas,
+            export_button,
+            chart,
+        }
+    }
+
+    fn build(&mut self, sender: Sender<Message>) {
+        self.kind_input.emit(sender, Message::ChartRefresh);
+        self.export_button.emit(sender, Message::ChartExport);
+    }
+
+    fn selected_kind(&self) -> ChartKind {
+        match self.kind_input.value() {
+            1 => ChartKind::ByPostalCode,
+            2 => ChartKind::SurfaceHistogram,
+            _ => ChartKind::ByKind,
+        }
+    }
+
+    fn update(&mut self, houses: &[HouseWithKind]) {
+        *self.chart.borrow_mut() = Chart::build(self.selected_kind(), houses);
+        self.canvas.redraw();
+    }
+}
+
+impl ContactsPanel {
+    fn new() -> ContactsPanel {
+        let wind = Window::default().with_label("Contactos");
+
+        let list_browser = HoldBrowser::default()
+            .with_pos(WIDGET_PADDING, WIDGET_PADDING)
+            .with_size(WIDGET_WIDTH * 3, WIDGET_HEIGHT * 10);
+
+        let mut id_output = Output::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .with_pos(
+                list_browser.x() + list_browser.width() + WIDGET_PADDING + WIDGET_WIDTH,
+                list_browser.y(),
+            )
+            .with_label("Id:");
+        id_output.deactivate();
+
+        let name_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&id_output, WIDGET_PADDING)
+            .with_label("Nombre:");
+
+        let phone_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&name_input, WIDGET_PADDING)
+            .with_label("Teléfono:");
+
+        let email_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&phone_input, WIDGET_PADDING)
+            .with_label("Email:");
+
+        let notes_input = MultilineInput::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT * 4)
+            .below_of(&email_input, WIDGET_PADDING)
+            .with_label("Notas:");
+
+        let new_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .with_pos(
+                WIDGET_PADDING,
+                list_browser.y() + list_browser.height() + WIDGET_PADDING,
+            )
+            .with_label("Nuevo");
+
+        let save_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&new_button, WIDGET_PADDING)
+            .with_label("Guardar");
+
+        let delete_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&save_button, WIDGET_PADDING)
+            .with_label("Borrar");
+
+        let message_output = Output::default()
+            .with_size(WIDGET_WIDTH * 3, WIDGET_HEIGHT)
+            .right_of(&delete_button, WIDGET_PADDING * 3);
+
+        let wind = wind.with_size(
+            id_output.x() + id_output.width() + WIDGET_PADDING,
+            new_button.y() + new_button.height() + WIDGET_PADDING,
+        );
+        wind.end();
+
+        ContactsPanel {
+            wind,
+            list_browser,
+            id_output,
+            name_input,
+            phone_input,
+            email_input,
+            notes_input,
+            new_button,
+            save_button,
+            delete_button,
+            message_output,
+        }
+    }
+
+    fn build(&mut self, sender: Sender<Message>) {
+        self.list_browser.emit(sender, Message::ContactSelect);
+        self.new_button.emit(sender, Message::ContactNew);
+        self.save_button.emit(sender, Message::ContactSave);
+        self.delete_button.emit(sender, Message::ContactDelete);
+        self.delete_button.deactivate();
+    }
+
+    fn clear_edit(&mut self) {
+        self.id_output.set_value("");
+        self.name_input.set_value("");
+        self.phone_input.set_value("");
+        self.email_input.set_value("");
+        self.notes_input.set_value("");
+        self.delete_button.deactivate();
+    }
+
+    fn fill_edit(&mut self, contact: &Contact) {
+        self.id_output.set_value(&contact.id.to_string());
+        self.name_input.set_value(&contact.name);
+        self.phone_input.set_value(&contact.phone);
+        self.email_input.set_value(&contact.email);
+        self.notes_input.set_value(&contact.notes);
+        self.delete_button.activate();
+    }
+
+    fn show_message(&mut self, message: &str) {
+        self.message_output.set_value(message);
+        println!("{message}");
+    }
+}
+
+impl BuildingsPanel {
+    fn new() -> BuildingsPanel {
+        let wind = Window::default().with_label("Edificios");
+
+        let list_browser = HoldBrowser::default()
+            .with_pos(WIDGET_PADDING, WIDGET_PADDING)
+            .with_size(WIDGET_WIDTH * 3, WIDGET_HEIGHT * 12);
+
+        let mut id_output = Output::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .with_pos(
+                list_browser.x() + list_browser.width() + WIDGET_PADDING + WIDGET_WIDTH,
+                list_browser.y(),
+            )
+            .with_label("Id:");
+        id_output.deactivate();
+
+        let street_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&id_output, WIDGET_PADDING)
+            .with_label("Calle:");
+
+        let street_number_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&street_input, WIDGET_PADDING)
+            .with_label("Número:");
+
+        let postal_code_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&street_number_input, WIDGET_PADDING)
+            .with_label("CP:");
+
+        let city_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&postal_code_input, WIDGET_PADDING)
+            .with_label("Ciudad:");
+
+        let province_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&city_input, WIDGET_PADDING)
+            .with_label("Provincia:");
+
+        let country_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&province_input, WIDGET_PADDING)
+            .with_label("País:");
+
+        let year_built_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&country_input, WIDGET_PADDING)
+            .with_label("Año de construcción:");
+
+        let elevator_check = CheckButton::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .below_of(&year_built_input, WIDGET_PADDING)
+            .with_label("Ascensor");
+
+        let units_browser = HoldBrowser::default()
+            .with_size(WIDGET_WIDTH * 3, list_browser.height())
+            .right_of(&id_output, WIDGET_PADDING)
+            .with_label("Unidades");
+
+        let new_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .with_pos(
+                WIDGET_PADDING,
+                list_browser.y() + list_browser.height() + WIDGET_PADDING * 3,
+            )
+            .with_label("Nuevo");
+
+        let save_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&new_button, WIDGET_PADDING)
+            .with_label("Guardar");
+
+        let delete_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&save_button, WIDGET_PADDING)
+            .with_label("Borrar");
+
+        let units_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&delete_button, WIDGET_PADDING)
+            .with_label("Ver unidades");
+
+        let message_output = Output::default()
+            .with_size(WIDGET_WIDTH * 3, WIDGET_HEIGHT)
+            .right_of(&units_button, WIDGET_PADDING * 3);
+
+        let wind = wind.with_size(
+            units_browser.x() + units_browser.width() + WIDGET_PADDING,
+            new_button.y() + new_button.height() + WIDGET_PADDING,
+        );
+        wind.end();
+
+        BuildingsPanel {
+            wind,
+            list_browser,
+            id_output,
+            street_input,
+            street_number_input,
+            postal_code_input,
+            city_input,
+            province_input,
+            country_input,
+            year_built_input,
+            elevator_check,
+            units_browser,
+            new_button,
+            save_button,
+            delete_button,
+            units_button,
+            message_output,
+        }
+    }
+
+    fn build(&mut self, sender: Sender<Message>) {
+        self.list_browser.emit(sender, Message::BuildingSelect);
+        self.new_button.emit(sender, Message::BuildingNew);
+        self.save_button.emit(sender, Message::BuildingSave);
+        self.delete_button.emit(sender, Message::BuildingDelete);
+        self.units_button.emit(sender, Message::BuildingUnits);
+        self.delete_button.deactivate();
+        self.units_button.deactivate();
+    }
+
+    fn clear_edit(&mut self) {
+        self.id_output.set_value("");
+        self.street_input.set_value("");
+        self.street_number_input.set_value("");
+        self.postal_code_input.set_value("");
+        self.city_input.set_value("");
+        self.province_input.set_value("");
+        self.country_input.set_value("");
+        self.year_built_input.set_value("");
+        self.elevator_check.set_checked(false);
+        self.units_browser.clear();
+        self.delete_button.deactivate();
+        self.units_button.deactivate();
+    }
+
+    fn fill_edit(&mut self, building: &Building, units: &[HouseWithKind]) {
+        self.id_output.set_value(&building.id.to_string());
+        self.street_input.set_value(&building.street);
+        self.street_number_input
+            .set_value(&building.street_number.to_string());
+        self.postal_code_input
+            .set_value(building.postal_code.as_str());
+        self.city_input.set_value(&building.city);
+        self.province_input.set_value(&building.province);
+        self.country_input.set_value(&building.country);
+        self.year_built_input.set_value(
+            &building
+                .year_built
+                .map_or(String::new(), |year| year.to_string()),
+        );
+        self.elevator_check.set_checked(building.elevator);
+        self.units_browser.clear();
+        for unit in units {
+            let floor = match (unit.street_floor.to_string(), unit.street_unit.as_str()) {
+                (floor, "") if floor.is_empty() => "-".to_string(),
+                (floor, unit) => format!("{floor} {unit}").trim().to_string(),
+            };
+            self.units_browser
+                .add(&format!("{floor} · #{} ({})", unit.id, unit.status));
+        }
+        self.delete_button.activate();
+        self.units_button.activate();
+    }
+
+    /// Address typed in the form, `None` when the street number isn't one.
+    fn address(&self) -> Option<Address> {
+        Some(Address {
+            street: self.street_input.value(),
+            street_number: self.street_number_input.value().trim().parse().ok()?,
+            postal_code: self.postal_code_input.value(),
+            city: self.city_input.value(),
+            province: self.province_input.value(),
+            country: self.country_input.value(),
+            ..Address::default()
+        })
+    }
+
+    fn show_message(&mut self, message: &str) {
+        self.message_output.set_value(message);
+    }
+}
+
 impl GUI {
     pub fn new(mut house_service: HouseService) -> GUI {
         let app = app::App::default().with_scheme(app::Scheme::Gtk);
         let wind = Window::default().with_label("CRUD");
         let (sender, receiver) = channel::<Message>();
 
-        let filter_input = Input::default()
-            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+        let mut filter_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
             .with_pos(WIDGET_PADDING + WIDGET_WIDTH * 2, WIDGET_PADDING)
-            .with_label("Id:");
+            .with_label("Filtro:");
+        filter_input.set_tooltip(
+            "Id, o condiciones como: kind:departamento rooms>=3 surface<80 cp:1425 street~\"rivadavia\"",
+        );
 
-        let list_browser = HoldBrowser::default()
+        let mut order_input = Choice::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .right_of(&filter_input, WIDGET_WIDTH)
+            .with_label("Orden:");
+        order_input.add_choice("Id|Recientes (alta)|Recientes (modificación)|Distancia");
+        order_input.set_value(0);
+
+        let operations = house_service
+            .get_operations()
+            .unwrap_or_else(|_| panic!("Can't connect to the DB"));
+        let statuses = house_service
+            .get_statuses()
+            .unwrap_or_else(|_| panic!("Can't connect to the DB"));
+
+        let mut operation_filter = Choice::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&order_input, WIDGET_WIDTH)
+            .with_label("Operación:");
+        operation_filter.add_choice("Todas");
+        for o in &operations {
+            operation_filter.add_choice(&o.operation);
+        }
+        operation_filter.set_value(0);
+
+        let mut status_filter = Choice::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&operation_filter, WIDGET_WIDTH)
+            .with_label("Estado:");
+        status_filter.add_choice("Todos");
+        for s in &statuses {
+            status_filter.add_choice(&s.status);
+        }
+        status_filter.set_value(0);
+
+        let mut search_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .right_of(&status_filter, WIDGET_WIDTH)
+            .with_label("Buscar:");
+        search_input.set_tooltip("Calle, número, piso, CP, ciudad, tipo o texto de las fotos");
+
+        let mut saved_searches_browser = HoldBrowser::default()
             .with_pos(
                 WIDGET_PADDING,
                 filter_input.y() + filter_input.height() + WIDGET_PADDING,
             )
+            .with_size(
+                WIDGET_WIDTH * 2 + WIDGET_PADDING,
+                WIDGET_HEIGHT * 11 - WIDGET_PADDING,
+            );
+        saved_searches_browser.set_tooltip("Búsquedas guardadas y cantidad de elementos");
+
+        let save_search_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&saved_searches_browser, WIDGET_PADDING)
+            .with_label("Guardar búsqueda");
+
+        let delete_search_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&save_search_button, WIDGET_PADDING)
+            .with_label("Quitar búsqueda");
+
+        let list_browser = MultiBrowser::default()
+            .with_pos(
+                saved_searches_browser.x() + saved_searches_browser.width() + WIDGET_PADDING,
+                saved_searches_browser.y(),
+            )
             .with_size(WIDGET_WIDTH * 5, WIDGET_HEIGHT * 12);
 
         let mut id_output = Output::default()
@@ -90,9 +745,34 @@ impl GUI {
             .below_of(&street_number_input, WIDGET_PADDING)
             .with_label("CP:");
 
-        let surface_input = Input::default()
+        let city_input = Input::default()
             .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
             .below_of(&postal_code_input, WIDGET_PADDING)
+            .with_label("Ciudad:");
+
+        let province_input = Input::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&city_input, WIDGET_PADDING)
+            .with_label("Provincia:");
+
+        let country_input = Input::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&province_input, WIDGET_PADDING)
+            .with_label("País:");
+
+        let latitude_input = Input::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&country_input, WIDGET_PADDING)
+            .with_label("Latitud:");
+
+        let longitude_input = Input::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&latitude_input, WIDGET_PADDING)
+            .with_label("Longitud:");
+
+        let surface_input = Input::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&longitude_input, WIDGET_PADDING)
             .with_label("Superfice:");
 
         let bathrooms_input = Input::default()
@@ -118,43 +798,312 @@ impl GUI {
             kind_input.add_choice(&k.kind);
         }
 
-        let mut street_floor_input = Input::default()
+        let mut operation_input = Choice::default()
             .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
-            .below_of(&kind_input, WIDGET_PADDING)
-            .with_label("Piso:");
-        street_floor_input.deactivate();
+            .with_pos(id_output.x() + WIDGET_WIDTH * 2, id_output.y())
+            .with_label("Operación:");
+        for o in &operations {
+            operation_input.add_choice(&o.operation);
+        }
 
-        let create_button = Button::default()
+        let mut status_input = Choice::default()
             .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
-            .with_pos(
-                WIDGET_PADDING,
-                street_floor_input.y() + street_floor_input.height() + WIDGET_PADDING,
-            )
-            .with_label("Crear");
+            .below_of(&operation_input, WIDGET_PADDING)
+            .with_label("Estado:");
+        for s in &statuses {
+            status_input.add_choice(&s.status);
+        }
+        status_input.set_value(AVAILABLE);
 
-        let update_button = Button::default()
+        let price_input = Input::default()
             .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
-            .right_of(&create_button, WIDGET_PADDING)
-            .with_label("Modificar");
+            .below_of(&status_input, WIDGET_PADDING)
+            .with_label("Precio:");
 
-        let delete_button = Button::default()
+        let mut currency_input = Choice::default()
             .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
-            .right_of(&update_button, WIDGET_PADDING)
-            .with_label("Borrar");
+            .below_of(&price_input, WIDGET_PADDING)
+            .with_label("Moneda:");
+        for c in utils::CURRENCIES {
+            currency_input.add_choice(c);
+        }
+        currency_input.set_value(0);
 
-        let save_button = Button::default()
+        let history_button = Button::default()
             .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
-            .right_of(&delete_button, WIDGET_PADDING)
-            .with_label("Guardar");
+            .below_of(&currency_input, WIDGET_PADDING)
+            .with_label("Historial");
 
-        let message_output = Output::default()
-            .with_size(420, WIDGET_HEIGHT)
-            .right_of(&save_button, WIDGET_PADDING * 3);
-        // .with_label("Id:");
-        // .with_pos(
-        //     list_browser.x() + list_browser.width() + WIDGET_PADDING + WIDGET_WIDTH,
-        //     list_browser.y(),
-        // );
+        let mut linked_contacts_browser = HoldBrowser::default()
+            .with_size(WIDGET_WIDTH * 2 - WIDGET_PADDING, WIDGET_HEIGHT * 4)
+            .below_of(&history_button, WIDGET_PADDING)
+            .with_label("Contactos:");
+        linked_contacts_browser.set_align(Align::Left | Align::Top);
+
+        let contact_input = Choice::default()
+            .with_size(WIDGET_WIDTH * 2 - WIDGET_PADDING, WIDGET_HEIGHT)
+            .below_of(&linked_contacts_browser, WIDGET_PADDING)
+            .with_label("Contacto:");
+
+        let mut role_input = Choice::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&contact_input, WIDGET_PADDING)
+            .with_label("Rol:");
+        let roles = house_service
+            .get_contact_roles()
+            .unwrap_or_else(|_| panic!("Can't connect to the DB"));
+        for r in roles {
+            role_input.add_choice(&r.role);
+        }
+        role_input.set_value(0);
+
+        let link_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&role_input, WIDGET_PADDING)
+            .with_label("Vincular");
+
+        let unlink_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&link_button, WIDGET_PADDING)
+            .with_label("Desvincular");
+
+        let mut tags_browser = HoldBrowser::default()
+            .with_size(WIDGET_WIDTH * 2 - WIDGET_PADDING, WIDGET_HEIGHT * 3)
+            .below_of(&unlink_button, WIDGET_PADDING)
+            .with_label("Etiquetas:");
+        tags_browser.set_align(Align::Left | Align::Top);
+
+        let mut tag_input = InputChoice::default()
+            .with_size(WIDGET_WIDTH * 2 - WIDGET_PADDING, WIDGET_HEIGHT)
+            .below_of(&tags_browser, WIDGET_PADDING)
+            .with_label("Etiqueta:");
+        tag_input.set_tooltip("Elija una etiqueta o escriba una nueva");
+
+        let tag_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&tag_input, WIDGET_PADDING)
+            .with_label("Etiquetar");
+
+        let untag_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&tag_button, WIDGET_PADDING)
+            .with_label("Quitar etiqueta");
+
+        let rename_tag_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&untag_button, WIDGET_PADDING)
+            .with_label("Renombrar");
+
+        let amenities = house_service
+            .get_amenities()
+            .unwrap_or_else(|_| panic!("Can't connect to the DB"));
+        let mut amenity_inputs: Vec<AmenityInput> = vec![];
+        for (row, amenity) in amenities.iter().enumerate() {
+            let check = CheckButton::default()
+                .with_size(WIDGET_WIDTH + WIDGET_WIDTH / 2, WIDGET_HEIGHT)
+                .with_pos(
+                    operation_input.x() + WIDGET_WIDTH * 2 + WIDGET_PADDING,
+                    id_output.y() + (WIDGET_HEIGHT + WIDGET_PADDING) * row as i32,
+                )
+                .with_label(&amenity.amenity);
+            let quantity = amenity.countable.then(|| {
+                let mut quantity = Input::default()
+                    .with_size(WIDGET_WIDTH / 2, WIDGET_HEIGHT)
+                    .right_of(&check, WIDGET_PADDING);
+                quantity.set_tooltip("Cantidad (opcional)");
+                quantity
+            });
+            amenity_inputs.push(AmenityInput {
+                amenity_id: amenity.id,
+                check,
+                quantity,
+            });
+        }
+
+        let mut custom_fields_scroll = Scroll::default()
+            .with_size(WIDGET_WIDTH * 2 + WIDGET_PADDING, 0)
+            .with_pos(
+                operation_input.x() + WIDGET_WIDTH * 2 + WIDGET_PADDING,
+                id_output.y()
+                    + (WIDGET_HEIGHT + WIDGET_PADDING) * amenities.len() as i32
+                    + WIDGET_HEIGHT,
+            )
+            .with_label("Campos adicionales:");
+        custom_fields_scroll.set_align(Align::Left | Align::Top);
+        custom_fields_scroll.set_type(ScrollType::Vertical);
+        let mut custom_fields_pack = Pack::default()
+            .with_size(custom_fields_scroll.width() - WIDGET_PADDING * 2, 0)
+            .with_pos(custom_fields_scroll.x(), custom_fields_scroll.y());
+        custom_fields_pack.set_spacing(WIDGET_PADDING);
+        custom_fields_pack.end();
+        custom_fields_scroll.end();
+
+        let mut street_floor_input = Input::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&kind_input, WIDGET_PADDING)
+            .with_label("Piso:");
+        street_floor_input.deactivate();
+
+        let mut street_unit_input = Input::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&street_floor_input, WIDGET_PADDING)
+            .with_label("Depto:");
+        street_unit_input.deactivate();
+
+        let mut created_output = Output::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&street_unit_input, WIDGET_PADDING)
+            .with_label("Alta:");
+        created_output.deactivate();
+
+        let mut updated_output = Output::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&created_output, WIDGET_PADDING)
+            .with_label("Modificado:");
+        updated_output.deactivate();
+
+        let description_input = MultilineInput::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT * 3)
+            .below_of(&updated_output, WIDGET_PADDING)
+            .with_label("Descripción:");
+
+        custom_fields_scroll.set_size(
+            custom_fields_scroll.width(),
+            (description_input.y() + description_input.height() - custom_fields_scroll.y())
+                .max(WIDGET_HEIGHT * 3),
+        );
+
+        let create_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .with_pos(
+                WIDGET_PADDING,
+                (description_input.y() + description_input.height())
+                    .max(custom_fields_scroll.y() + custom_fields_scroll.height())
+                    + WIDGET_PADDING,
+            )
+            .with_label("Crear");
+
+        let update_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&create_button, WIDGET_PADDING)
+            .with_label("Modificar");
+
+        let delete_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&update_button, WIDGET_PADDING)
+            .with_label("Borrar");
+
+        let save_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&delete_button, WIDGET_PADDING)
+            .with_label("Guardar");
+
+        let change_kind_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&create_button, WIDGET_PADDING)
+            .with_label("Cambiar tipo");
+
+        let mut bulk_field_input = Choice::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&change_kind_button, WIDGET_WIDTH)
+            .with_label("Campo:");
+        bulk_field_input.add_choice("Calle|Número|CP|Superficie|Baños|Habitaciones");
+        bulk_field_input.set_value(0);
+
+        let set_field_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&bulk_field_input, WIDGET_PADDING)
+            .with_label("Editar campo");
+
+        let duplicates_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&set_field_button, WIDGET_PADDING)
+            .with_label("Duplicados");
+
+        let contacts_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&duplicates_button, WIDGET_PADDING)
+            .with_label("Contactos");
+
+        let mut near_input = Input::default()
+            .with_size(WIDGET_WIDTH * 2, WIDGET_HEIGHT)
+            .right_of(&contacts_button, WIDGET_WIDTH)
+            .with_label("Cerca de:");
+        near_input.set_tooltip(
+            "Latitud, longitud y radio en km (ej: -34.60 -58.38 5), o sur, oeste, norte y este",
+        );
+
+        let print_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&near_input, WIDGET_PADDING)
+            .with_label("Imprimir ficha");
+
+        let mut attachments_scroll = Scroll::default()
+            .with_size(
+                list_browser.width(),
+                THUMBNAIL_SIZE + WIDGET_HEIGHT * 2 + WIDGET_PADDING,
+            )
+            .with_pos(
+                WIDGET_PADDING,
+                change_kind_button.y() + change_kind_button.height() + WIDGET_PADDING,
+            );
+        attachments_scroll.set_type(ScrollType::Horizontal);
+        let mut attachments_pack = Pack::default()
+            .with_size(0, THUMBNAIL_SIZE + WIDGET_HEIGHT)
+            .with_pos(attachments_scroll.x(), attachments_scroll.y());
+        attachments_pack.set_type(PackType::Horizontal);
+        attachments_pack.set_spacing(WIDGET_PADDING);
+        attachments_pack.end();
+        attachments_scroll.end();
+
+        let attach_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&attachments_scroll, WIDGET_PADDING)
+            .with_label("Adjuntar");
+
+        let move_back_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&attach_button, WIDGET_PADDING)
+            .with_label("Mover antes");
+
+        let move_forward_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&move_back_button, WIDGET_PADDING)
+            .with_label("Mover después");
+
+        let remove_attachment_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .below_of(&move_forward_button, WIDGET_PADDING)
+            .with_label("Quitar adjunto");
+
+        let message_output = Output::default()
+            .with_size(420, WIDGET_HEIGHT)
+            .right_of(&save_button, WIDGET_PADDING * 3);
+
+        let stats_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&message_output, WIDGET_PADDING)
+            .with_label("Estadísticas");
+
+        let charts_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&stats_button, WIDGET_PADDING)
+            .with_label("Gráficos");
+
+        let buildings_button = Button::default()
+            .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+            .right_of(&charts_button, WIDGET_PADDING)
+            .with_label("Edificios");
+        // .with_label("Id:");
+        // .with_pos(
+        //     list_browser.x() + list_browser.width() + WIDGET_PADDING + WIDGET_WIDTH,
+        //     list_browser.y(),
+        // );
+
+        wind.end();
+        let contacts_panel = ContactsPanel::new();
+        let buildings_panel = BuildingsPanel::new();
+        let chart_panel = ChartPanel::new();
 
         GUI {
             app: app,
@@ -163,21 +1112,83 @@ impl GUI {
             receiver: receiver,
             id_output: id_output,
             filter_input: filter_input,
+            order_input,
+            operation_filter,
+            status_filter,
+            search_input,
+            near_input,
+            saved_searches_browser,
+            save_search_button,
+            delete_search_button,
             list_browser: list_browser,
             service: house_service,
             street_input,
             street_number_input,
             street_floor_input,
+            street_unit_input,
             postal_code_input,
+            city_input,
+            province_input,
+            country_input,
+            latitude_input,
+            longitude_input,
             surface_input,
             bathrooms_input,
             rooms_input,
             kind_input,
+            operation_input,
+            status_input,
+            price_input,
+            currency_input,
+            history_button,
+            created_output,
+            updated_output,
+            description_input,
             create_button: create_button,
             update_button: update_button,
             delete_button: delete_button,
             save_button: save_button,
+            change_kind_button,
+            bulk_field_input,
+            set_field_button,
+            duplicates_button,
+            stats_button,
+            charts_button,
+            print_button,
+            contacts_button,
+            buildings_button,
+            linked_contacts_browser,
+            contact_input,
+            role_input,
+            link_button,
+            unlink_button,
+            tags_browser,
+            tag_input,
+            tag_button,
+            untag_button,
+            rename_tag_button,
+            attachments_scroll,
+            attachments_pack,
+            attach_button,
+            move_back_button,
+            move_forward_button,
+            remove_attachment_button,
             message_output: message_output,
+            contacts_panel,
+            buildings_panel,
+            chart_panel,
+            contacts: vec![],
+            buildings: vec![],
+            linked_contacts: vec![],
+            attachments: vec![],
+            selected_attachment: None,
+            selected_version: 0,
+            saved_searches: vec![],
+            house_tags: vec![],
+            amenity_inputs,
+            custom_fields_scroll,
+            custom_fields_pack,
+            custom_inputs: vec![],
         }
     }
 
@@ -185,8 +1196,27 @@ impl GUI {
         self.filter_input.set_trigger(CallbackTrigger::Changed);
         self.filter_input.emit(self.sender, Message::Filter);
 
+        self.order_input.emit(self.sender, Message::Filter);
+        self.operation_filter.emit(self.sender, Message::Filter);
+        self.status_filter.emit(self.sender, Message::Filter);
+        self.search_input.set_trigger(CallbackTrigger::Changed);
+        self.search_input.emit(self.sender, Message::Filter);
+        self.near_input.set_trigger(CallbackTrigger::EnterKeyAlways);
+        self.near_input.emit(self.sender, Message::Filter);
+
+        self.history_button.emit(self.sender, Message::History);
+        self.history_button.deactivate();
+
         self.list_browser.emit(self.sender, Message::Select);
 
+        self.saved_searches_browser
+            .emit(self.sender, Message::SavedSearchSelect);
+        self.save_search_button
+            .emit(self.sender, Message::SavedSearchSave);
+        self.delete_search_button
+            .emit(self.sender, Message::SavedSearchDelete);
+        self.delete_search_button.deactivate();
+
         self.create_button.emit(self.sender, Message::Create);
 
         self.update_button.emit(self.sender, Message::Update);
@@ -197,27 +1227,691 @@ impl GUI {
 
         self.save_button.emit(self.sender, Message::Save);
 
+        self.change_kind_button
+            .emit(self.sender, Message::ChangeKind);
+        self.change_kind_button.deactivate();
+
+        self.set_field_button.emit(self.sender, Message::SetField);
+        self.set_field_button.deactivate();
+
+        self.duplicates_button
+            .emit(self.sender, Message::Duplicates);
+
+        self.stats_button.emit(self.sender, Message::Stats);
+        self.charts_button.emit(self.sender, Message::ChartsOpen);
+        self.chart_panel.build(self.sender);
+
+        self.print_button.emit(self.sender, Message::PrintSheet);
+        self.print_button.deactivate();
+
+        self.contacts_button
+            .emit(self.sender, Message::ContactsOpen);
+        self.linked_contacts_browser
+            .emit(self.sender, Message::LinkedContactSelect);
+        self.link_button.emit(self.sender, Message::LinkContact);
+        self.link_button.deactivate();
+        self.unlink_button.emit(self.sender, Message::UnlinkContact);
+        self.unlink_button.deactivate();
+        self.attach_button.emit(self.sender, Message::AttachmentAdd);
+        self.move_back_button
+            .emit(self.sender, Message::AttachmentMove(-1));
+        self.move_forward_button
+            .emit(self.sender, Message::AttachmentMove(1));
+        self.remove_attachment_button
+            .emit(self.sender, Message::AttachmentRemove);
+        self.kind_input.emit(self.sender, Message::KindChange);
+        self.tags_browser.emit(self.sender, Message::TagSelect);
+        self.tag_button.emit(self.sender, Message::TagAdd);
+        self.untag_button.emit(self.sender, Message::TagRemove);
+        self.rename_tag_button.emit(self.sender, Message::TagRename);
+        self.refresh_tags(None);
+        self.refresh_attachments(None);
+        self.contacts_panel.build(self.sender);
+        self.buildings_button
+            .emit(self.sender, Message::BuildingsOpen);
+        self.buildings_panel.build(self.sender);
+        self.refresh_contacts();
+
+        let right = [
+            self.charts_button.x() + self.charts_button.width(),
+            self.print_button.x() + self.print_button.width(),
+            self.search_input.x() + self.search_input.width(),
+        ]
+        .into_iter()
+        .chain(
+            self.amenity_inputs
+                .iter()
+                .map(|input| match &input.quantity {
+                    Some(quantity) => quantity.x() + quantity.width(),
+                    None => input.check.x() + input.check.width(),
+                }),
+        )
+        .max()
+        .unwrap_or_default();
         self.wind.set_size(
-            self.street_input.x() + self.street_input.width() + WIDGET_PADDING,
-            self.update_button.y() + self.update_button.height() + WIDGET_PADDING,
+            right + WIDGET_PADDING,
+            self.attachments_scroll.y() + self.attachments_scroll.height() + WIDGET_PADDING,
         );
 
-        self.sender.send(Message::Filter);
+        self.sender.send(Message::Refresh);
     }
 
     fn clear_edit(&mut self) {
         self.street_input.set_value("");
         self.street_number_input.set_value("");
         self.street_floor_input.set_value("");
+        self.street_unit_input.set_value("");
         self.postal_code_input.set_value("");
+        self.city_input.set_value("");
+        self.province_input.set_value("");
+        self.country_input.set_value("");
+        self.latitude_input.set_value("");
+        self.longitude_input.set_value("");
         self.surface_input.set_value("");
         self.bathrooms_input.set_value("");
         self.rooms_input.set_value("");
         self.kind_input.set_value(-1);
+        self.operation_input.set_value(-1);
+        self.status_input.set_value(AVAILABLE);
+        self.price_input.set_value("");
+        self.currency_input.set_value(0);
         self.id_output.set_value("");
+        self.created_output.set_value("");
+        self.updated_output.set_value("");
+        self.description_input.set_value("");
+        for input in &mut self.amenity_inputs {
+            input.check.set_checked(false);
+            if let Some(quantity) = &mut input.quantity {
+                quantity.set_value("");
+            }
+        }
+        self.selected_version = 0;
+        self.refresh_custom_fields(&[]);
         // self.message_output.set_value("");
     }
 
+    fn fill_edit(&mut self, house: &HouseWithKind) {
+        self.id_output.set_value(&house.id.to_string());
+        self.street_input.set_value(&house.street.to_string());
+        self.street_number_input
+            .set_value(&house.street_number.to_string());
+        self.street_floor_input
+            .set_value(&house.street_floor.to_string());
+        self.street_unit_input
+            .set_value(&house.street_unit.to_string());
+        self.postal_code_input
+            .set_value(&house.postal_code.to_string());
+        self.city_input.set_value(&house.city.to_string());
+        self.province_input.set_value(&house.province.to_string());
+        self.country_input.set_value(&house.country.to_string());
+        self.latitude_input
+            .set_value(&house.latitude.map(|l| l.to_string()).unwrap_or_default());
+        self.longitude_input
+            .set_value(&house.longitude.map(|l| l.to_string()).unwrap_or_default());
+        self.surface_input
+            .set_value(&house.surface_square_meters.to_string());
+        self.bathrooms_input.set_value(&house.bathrooms.to_string());
+        self.rooms_input.set_value(&house.rooms.to_string());
+        self.kind_input.set_value(house.kind_id.get());
+        self.operation_input.set_value(house.operation_id);
+        self.status_input.set_value(house.status_id);
+        self.price_input
+            .set_value(&house.price.map(|p| p.to_string()).unwrap_or_default());
+        let currency = utils::CURRENCIES
+            .iter()
+            .position(|c| *c == house.currency)
+            .unwrap_or(0);
+        self.currency_input.set_value(currency as i32);
+        self.created_output
+            .set_value(&utils::format_timestamp(&house.created_at));
+        self.updated_output
+            .set_value(&utils::format_timestamp(&house.updated_at));
+        self.description_input.set_value(&house.description);
+        self.selected_version = house.version;
+        let amenities = self.service.get_house_amenities(house.id).unwrap();
+        for input in &mut self.amenity_inputs {
+            let amenity = amenities.iter().find(|a| a.amenity_id == input.amenity_id);
+            input.check.set_checked(amenity.is_some());
+            if let Some(quantity) = &mut input.quantity {
+                quantity.set_value(
+                    &amenity
+                        .and_then(|a| a.quantity)
+                        .map(|q| q.to_string())
+                        .unwrap_or_default(),
+                );
+            }
+        }
+        let values: Vec<(i32, String)> = self
+            .service
+            .get_custom_values(house.id)
+            .unwrap()
+            .into_iter()
+            .map(|v| (v.field_id, v.value))
+            .collect();
+        self.refresh_custom_fields(&values);
+    }
+
+    /// Shows the custom fields of the selected kind with the given values,
+    /// by field id.
+    fn refresh_custom_fields(&mut self, values: &[(i32, String)]) {
+        let fields = match self.kind_input.value() {
+            kind_id if kind_id >= 0 => self.service.get_kind_custom_fields(kind_id).unwrap(),
+            _ => vec![],
+        };
+        self.custom_fields_pack.clear();
+        self.custom_fields_pack.begin();
+        self.custom_inputs = fields
+            .into_iter()
+            .map(|field| {
+                let mut row = Pack::default().with_size(0, WIDGET_HEIGHT);
+                row.set_type(PackType::Horizontal);
+                row.set_spacing(WIDGET_PADDING);
+                let required = if field.required { " *" } else { "" };
+                let mut label = Frame::default()
+                    .with_size(WIDGET_WIDTH, WIDGET_HEIGHT)
+                    .with_label(&format!("{}{required}:", field.name.replace('@', "@@")));
+                label.set_align(Align::Right | Align::Inside | Align::Clip);
+                label.set_tooltip(&field.name);
+                let widget = match field.field_type {
+                    CustomFieldType::Bool => CustomWidget::Check(
+                        CheckButton::default().with_size(WIDGET_WIDTH, WIDGET_HEIGHT),
+                    ),
+                    CustomFieldType::Enum => {
+                        let mut choice = Choice::default().with_size(WIDGET_WIDTH, WIDGET_HEIGHT);
+                        if !field.required {
+                            choice.add_choice("-");
+                        }
+                        for option in field.options() {
+                            choice.add_choice(option);
+                        }
+                        CustomWidget::Choice(choice)
+                    }
+                    field_type => {
+                        let mut input = Input::default().with_size(WIDGET_WIDTH, WIDGET_HEIGHT);
+                        if field_type == CustomFieldType::Date {
+                            input.set_tooltip("dd/mm/aaaa");
+                        }
+                        CustomWidget::Text(input)
+                    }
+                };
+                row.end();
+                let mut input = CustomInput { field, widget };
+                let value = values
+                    .iter()
+                    .find(|(field_id, _)| *field_id == input.field.id)
+                    .map_or("", |(_, value)| value.as_str());
+                input.set_value(value);
+                input
+            })
+            .collect();
+        self.custom_fields_pack.end();
+        self.custom_fields_scroll.scroll_to(0, 0);
+        self.custom_fields_scroll.redraw();
+    }
+
+    /// Text typed for each custom field shown, by field id.
+    fn custom_values(&self) -> Vec<(i32, String)> {
+        self.custom_inputs
+            .iter()
+            .map(|input| (input.field.id, input.value()))
+            .collect()
+    }
+
+    /// Checked amenities with the quantities typed for them.
+    fn selected_amenities(&self) -> Result<Vec<(i32, Option<i32>)>, ServiceError> {
+        let mut amenities = vec![];
+        for input in self.amenity_inputs.iter().filter(|i| i.check.is_checked()) {
+            let quantity = match input.quantity.as_ref().map(|q| q.value()) {
+                Some(text) if !text.trim().is_empty() => Some(
+                    text.trim()
+                        .parse::<i32>()
+                        .map_err(|_| ServiceError::Validation("Cantidad inválida"))?,
+                ),
+                _ => None,
+            };
+            amenities.push((input.amenity_id, quantity));
+        }
+        Ok(amenities)
+    }
+
+    fn refresh_contacts(&mut self) {
+        self.contacts = self.service.get_contacts().unwrap();
+        self.contacts_panel.list_browser.clear();
+        self.contact_input.clear();
+        for c in &self.contacts {
+            self.contacts_panel.list_browser.add(&c.to_string());
+            self.contact_input
+                .add_choice(&menu_label(&format!("#{}: {}", c.id, c.name)));
+        }
+    }
+
+    fn refresh_buildings(&mut self) {
+        self.buildings = self.service.get_buildings().unwrap();
+        self.buildings_panel.list_browser.clear();
+        for b in &self.buildings {
+            self.buildings_panel
+                .list_browser
+                .add(&format!("#{}: {}", b.id, b));
+        }
+    }
+
+    fn refresh_linked_contacts(&mut self, house_id: Option<HouseId>) {
+        self.linked_contacts = match house_id {
+            Some(id) => self.service.get_house_contacts(id).unwrap(),
+            None => vec![],
+        };
+        self.linked_contacts_browser.clear();
+        for c in &self.linked_contacts {
+            self.linked_contacts_browser.add(&c.to_string());
+        }
+        if house_id.is_some() {
+            self.link_button.activate();
+        } else {
+            self.link_button.deactivate();
+        }
+        self.unlink_button.deactivate();
+    }
+
+    /// Lists the tags of the house and offers every existing tag to add.
+    fn refresh_tags(&mut self, house_id: Option<HouseId>) {
+        self.house_tags = match house_id {
+            Some(id) => self.service.get_house_tags(id).unwrap(),
+            None => vec![],
+        };
+        self.tags_browser.clear();
+        for t in &self.house_tags {
+            self.tags_browser.add(&t.to_string());
+        }
+        self.tag_input.clear();
+        for t in self.service.get_tags().unwrap() {
+            self.tag_input.add(&menu_label(&t.name));
+        }
+        self.tag_input.set_value("");
+        if house_id.is_some() {
+            self.tag_button.activate();
+        } else {
+            self.tag_button.deactivate();
+        }
+        self.untag_button.deactivate();
+        self.rename_tag_button.deactivate();
+    }
+
+    fn selected_tag(&self) -> Option<&Tag> {
+        let line = self.tags_browser.value();
+        if line > 0 {
+            self.house_tags.get(line as usize - 1)
+        } else {
+            None
+        }
+    }
+
+    fn refresh_attachments(&mut self, house_id: Option<HouseId>) {
+        self.attachments = match house_id {
+            Some(id) => self.service.get_attachments(id).unwrap(),
+            None => vec![],
+        };
+        if !self
+            .attachments
+            .iter()
+            .any(|a| Some(a.id) == self.selected_attachment)
+        {
+            self.selected_attachment = None;
+        }
+        self.attachments_pack.clear();
+        self.attachments_pack.begin();
+        for attachment in &self.attachments {
+            let mut thumbnail = Button::default().with_size(
+                THUMBNAIL_SIZE + WIDGET_PADDING * 2,
+                THUMBNAIL_SIZE + WIDGET_HEIGHT,
+            );
+            let caption = if attachment.caption.is_empty() {
+                &attachment.file_name
+            } else {
+                &attachment.caption
+            };
+            set_thumbnail(&mut thumbnail, attachment);
+            if attachment.mime_type == "application/pdf" {
+                thumbnail.set_label(&format!("PDF\n{}", caption.replace('@', "@@")));
+            } else {
+                thumbnail.set_label(&caption.replace('@', "@@"));
+            }
+            thumbnail.set_align(Align::Bottom | Align::Inside | Align::Clip);
+            thumbnail.set_tooltip(&format!(
+                "{}. {} ({})",
+                attachment.position + 1,
+                attachment.file_name,
+                utils::format_timestamp(&attachment.created_at)
+            ));
+            if Some(attachment.id) == self.selected_attachment {
+                thumbnail.set_frame(FrameType::DownBox);
+                thumbnail.set_color(Color::Selection);
+            }
+            thumbnail.emit(self.sender, Message::AttachmentSelect(attachment.id));
+        }
+        self.attachments_pack.end();
+        self.attachments_scroll.scroll_to(0, 0);
+        self.attachments_scroll.redraw();
+
+        if house_id.is_some() {
+            self.attach_button.activate();
+        } else {
+            self.attach_button.deactivate();
+        }
+        for button in [
+            &mut self.move_back_button,
+            &mut self.move_forward_button,
+            &mut self.remove_attachment_button,
+        ] {
+            if self.selected_attachment.is_some() {
+                button.activate();
+            } else {
+                button.deactivate();
+            }
+        }
+    }
+
+    fn selected_house_id(&self) -> Option<HouseId> {
+        self.id_output.value().parse::<HouseId>().ok()
+    }
+
+    fn selected_contact(&self) -> Option<&Contact> {
+        let line = self.contacts_panel.list_browser.value();
+        if line > 0 {
+            self.contacts.get(line as usize - 1)
+        } else {
+            None
+        }
+    }
+
+    fn selected_building(&self) -> Option<&Building> {
+        let line = self.buildings_panel.list_browser.value();
+        if line > 0 {
+            self.buildings.get(line as usize - 1)
+        } else {
+            None
+        }
+    }
+
+    fn selected_linked_contact(&self) -> Option<&LinkedContact> {
+        let line = self.linked_contacts_browser.value();
+        if line > 0 {
+            self.linked_contacts.get(line as usize - 1)
+        } else {
+            None
+        }
+    }
+
+    fn selected_order(&self) -> HouseOrder {
+        match self.order_input.value() {
+            1 => HouseOrder::RecentlyCreated,
+            2 => HouseOrder::RecentlyUpdated,
+            3 => HouseOrder::Distance,
+            _ => HouseOrder::Id,
+        }
+    }
+
+    fn selected_filter(&self) -> HouseFilter {
+        HouseFilter {
+            kind_id: None,
+            operation_id: match self.operation_filter.value() {
+                value if value > 0 => Some(value - 1),
+                _ => None,
+            },
+            status_id: match self.status_filter.value() {
+                value if value > 0 => Some(value - 1),
+                _ => None,
+            },
+            bounds: geo::parse_bounding_box(&self.near_input.value()),
+            near: geo::parse_radius(&self.near_input.value()),
+            query: self.selected_query().unwrap_or_default(),
+        }
+    }
+
+    /// Text of the filter box as a query, unless it is a plain id prefix.
+    fn selected_query(&self) -> Result<Query, ParseError> {
+        let text = self.filter_input.value();
+        if text.trim().chars().all(|c| c.is_ascii_digit()) {
+            Ok(Query::default())
+        } else {
+            Query::parse(&text)
+        }
+    }
+
+    fn visible_houses(&mut self) -> Vec<HouseWithKind> {
+        let prefix = self.filter_input.value().trim().to_string();
+        let filter_empty = prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_digit());
+        let order = self.selected_order();
+        let filter = self.selected_filter();
+        let search = self.search_input.value();
+        let houses = if search.trim().is_empty() {
+            self.service.get_houses_filtered(order, &filter).unwrap()
+        } else {
+            self.service
+                .search_houses(&search, &filter)
+                .unwrap_or_default()
+        };
+        houses
+            .into_iter()
+            .filter(|h| {
+                (h.id.to_string().contains(prefix.as_str()) && !filter_empty) || (filter_empty)
+            })
+            .collect()
+    }
+
+    fn refresh_list(&mut self) {
+        let houses = self.visible_houses();
+        self.list_browser.clear();
+        for h in &houses {
+            let item = h.to_string();
+            self.list_browser.add(&item);
+        }
+        if self.chart_panel.wind.shown() {
+            self.chart_panel.update(&houses);
+        }
+    }
+
+    /// Lists the saved searches with their current counts, keeping the
+    /// selected one.
+    fn refresh_saved_searches(&mut self) {
+        let selected = self.selected_saved_search().map(|s| s.id);
+        let searches = self.service.get_saved_search_counts().unwrap_or_default();
+        self.saved_searches_browser.clear();
+        for (search, count) in &searches {
+            self.saved_searches_browser
+                .add(&format!("{} ({})", search.name, count));
+        }
+        self.saved_searches = searches.into_iter().map(|(search, _)| search).collect();
+        match self
+            .saved_searches
+            .iter()
+            .position(|s| Some(s.id) == selected)
+        {
+            Some(index) => self.saved_searches_browser.select(index as i32 + 1),
+            None => self.delete_search_button.deactivate(),
+        }
+    }
+
+    fn selected_saved_search(&self) -> Option<&SavedSearch> {
+        let line = self.saved_searches_browser.value();
+        if line > 0 {
+            self.saved_searches.get(line as usize - 1)
+        } else {
+            None
+        }
+    }
+
+    /// Criteria in the filter boxes, to save under `name`.
+    fn current_search(&self, name: &str) -> NewSavedSearch {
+        let filter = self.selected_filter();
+        let text = self.filter_input.value().trim().to_string();
+        // A plain number in the filter box is an id.
+        let query = if !text.is_empty() && text.chars().all(|c| c.is_ascii_digit()) {
+            format!("id:{text}")
+        } else {
+            text
+        };
+        NewSavedSearch {
+            name: name.to_string(),
+            query,
+            search: self.search_input.value().trim().to_string(),
+            operation_id: filter.operation_id,
+            status_id: filter.status_id,
+            near: self.near_input.value().trim().to_string(),
+            sort_order: self.selected_order(),
+        }
+    }
+
+    /// Puts the criteria of `search` in the filter boxes.
+    fn apply_saved_search(&mut self, search: &SavedSearch) {
+        self.filter_input.set_value(&search.query);
+        self.search_input.set_value(&search.search);
+        self.operation_filter
+            .set_value(search.operation_id.map_or(0, |id| id + 1));
+        self.status_filter
+            .set_value(search.status_id.map_or(0, |id| id + 1));
+        self.near_input.set_value(&search.near);
+        self.order_input.set_value(match search.sort_order {
+            HouseOrder::Id => 0,
+            HouseOrder::RecentlyCreated => 1,
+            HouseOrder::RecentlyUpdated => 2,
+            HouseOrder::Distance => 3,
+        });
+    }
+
+    fn reload_house(&mut self, id: HouseId) {
+        self.refresh_list();
+        match self
+            .service
+            .get_houses()
+            .unwrap()
+            .into_iter()
+            .find(|h| h.id == id)
+        {
+            Some(house) => {
+                let item = house.to_string();
+                for line in 1..=self.list_browser.size() {
+                    if self.list_browser.text(line).as_deref() == Some(item.as_str()) {
+                        self.list_browser.select(line);
+                    }
+                }
+                self.fill_edit(&house);
+                self.show_message(&format!("Elemento #{} recargado", house.id));
+            }
+            None => {
+                self.clear_edit();
+                self.show_message(&format!("El elemento #{} ya no existe", id));
+                self.sender.send(Message::Refresh);
+            }
+        }
+    }
+
+    fn selected_houses(&mut self) -> Vec<HouseWithKind> {
+        let selected: Vec<String> = (1..=self.list_browser.size())
+            .filter(|line| self.list_browser.selected(*line))
+            .filter_map(|line| self.list_browser.text(line))
+            .collect();
+        self.service
+            .get_houses()
+            .unwrap()
+            .into_iter()
+            .filter(|h| {
+                selected
+                    .iter()
+                    .any(|text| h.to_string().eq_ignore_ascii_case(text))
+            })
+            .collect()
+    }
+
+    fn selected_field(&self) -> Option<HouseField> {
+        match self.bulk_field_input.value() {
+            0 => Some(HouseField::Street),
+            1 => Some(HouseField::StreetNumber),
+            2 => Some(HouseField::PostalCode),
+            3 => Some(HouseField::SurfaceSquareMeters),
+            4 => Some(HouseField::Bathrooms),
+            5 => Some(HouseField::Rooms),
+            _ => None,
+        }
+    }
+
+    fn confirm(&self, message: &str, action: &str) -> bool {
+        dialog::choice2_default(message, "Cancelar", action, "") == Some(1)
+    }
+
+    fn set_bulk_active(&mut self, active: bool) {
+        for button in [
+            &mut self.delete_button,
+            &mut self.change_kind_button,
+            &mut self.set_field_button,
+            &mut self.print_button,
+        ] {
+            if active {
+                button.activate();
+            } else {
+                button.deactivate();
+            }
+        }
+    }
+
+    /// Draft of the house in the form, with the amenities and custom values
+    /// to save with it.
+    fn form_draft(&self, amenities: &[(i32, Option<i32>)]) -> Result<HouseDraft, ServiceError> {
+        let text = self.form_text();
+        Ok(self
+            .house_form(&text)
+            .draft()?
+            .amenities(amenities)
+            .custom_values(&self.custom_values()))
+    }
+
+    /// Text of the house inputs, read by [`house_form`](Self::house_form).
+    fn form_text(&self) -> [String; 16] {
+        [
+            self.street_input.value(),
+            self.street_number_input.value(),
+            self.street_floor_input.value(),
+            self.street_unit_input.value(),
+            self.postal_code_input.value(),
+            self.city_input.value(),
+            self.province_input.value(),
+            self.country_input.value(),
+            self.latitude_input.value(),
+            self.longitude_input.value(),
+            self.surface_input.value(),
+            self.bathrooms_input.value(),
+            self.rooms_input.value(),
+            self.price_input.value(),
+            self.currency_input.choice().unwrap_or_default(),
+            self.description_input.value(),
+        ]
+    }
+
+    fn house_form<'t>(&self, text: &'t [String; 16]) -> HouseForm<'t> {
+        let [street, street_number, street_floor, street_unit, postal_code, city, province, country, latitude, longitude, surface_square_meters, bathrooms, rooms, price, currency, description] =
+            text;
+        HouseForm {
+            street,
+            street_number,
+            street_floor,
+            street_unit,
+            postal_code,
+            city,
+            province,
+            country,
+            latitude,
+            longitude,
+            surface_square_meters,
+            bathrooms,
+            rooms,
+            kind_id: self.kind_input.value(),
+            price,
+            currency,
+            operation_id: self.operation_input.value(),
+            status_id: self.status_input.value(),
+            description,
+        }
+    }
+
     pub fn show_message(&mut self, message: &str) {
         self.message_output.set_text_color(Color::Black);
         self.message_output.set_value(message);
@@ -230,9 +1924,12 @@ impl GUI {
         while self.app.wait() {
             if self.kind_input.value() == APARTMENT {
                 self.street_floor_input.activate();
+                self.street_unit_input.activate();
             } else {
                 self.street_floor_input.set_value("");
                 self.street_floor_input.deactivate();
+                self.street_unit_input.set_value("");
+                self.street_unit_input.deactivate();
             }
             match self.receiver.recv() {
                 Some(Message::Create) => {
@@ -241,169 +1938,875 @@ impl GUI {
                     self.save_button.activate();
                     self.sender.send(Message::Filter);
                 }
-                Some(Message::Update) => {
-                    if self.list_browser.value() > 0 {
-                        let text_selection =
-                            self.list_browser.text(self.list_browser.value()).unwrap();
-
-                        match self
-                            .service
-                            .get_houses()
-                            .unwrap()
-                            .iter()
-                            .filter(|h| h.to_string().eq_ignore_ascii_case(&text_selection))
-                            .next()
-                        {
-                            Some(_) => {
-                                let updated_house = self.service.update_house(
-                                    &self.id_output.value(),
-                                    &self.street_input.value(),
-                                    &self.street_number_input.value(),
-                                    &self.street_floor_input.value(),
-                                    &self.postal_code_input.value(),
-                                    &self.surface_input.value(),
-                                    &self.bathrooms_input.value(),
-                                    &self.rooms_input.value(),
-                                    self.kind_input.value(),
-                                );
-                                if updated_house.is_err() {
-                                    self.show_message(&format!(
-                                        "Error actualizando el elemento #{}",
-                                        self.id_output.value()
-                                    ));
-                                } else {
-                                    self.show_message(&format!(
-                                        "Elemento #{} actualizado",
-                                        self.id_output.value()
-                                    ));
-                                }
-                                self.clear_edit();
-                                self.sender.send(Message::Filter);
-                                self.sender.send(Message::Select);
+                Some(Message::Update) => match self.selected_houses().as_slice() {
+                    [house] => {
+                        let id = house.id;
+                        let amenities = match self.selected_amenities() {
+                            Ok(amenities) => amenities,
+                            Err(e) => {
+                                self.show_message(&e.to_string());
+                                continue;
+                            }
+                        };
+                        let draft = match self.form_draft(&amenities) {
+                            Ok(draft) => draft,
+                            Err(e) => {
+                                self.show_message(&e.to_string());
+                                continue;
                             }
-                            _ => {
-                                println!("ELEMENTO NO ENCONTRADO!!!");
+                        };
+                        let mut updated_house =
+                            self.service
+                                .update_from_draft(id, self.selected_version, &draft);
+                        if updated_house == Err(ServiceError::Conflict) {
+                            let choice = dialog::choice2_default(
+                                &format!("El elemento #{} fue modificado por otro usuario", id),
+                                "Recargar",
+                                "Sobrescribir",
+                                "",
+                            );
+                            if choice == Some(1) {
+                                if let Ok(current) = self.service.get_house(id) {
+                                    self.selected_version = current.version;
+                                }
+                                updated_house = self.service.update_from_draft(
+                                    id,
+                                    self.selected_version,
+                                    &draft,
+                                );
+                            } else {
+                                self.reload_house(id);
+                                continue;
                             }
                         }
-                    } else {
+                        if let Err(e) = updated_house {
+                            self.show_message(&format!(
+                                "Error actualizando el elemento #{}: {}",
+                                self.id_output.value(),
+                                e
+                            ));
+                        } else {
+                            self.show_message(&format!(
+                                "Elemento #{} actualizado",
+                                self.id_output.value()
+                            ));
+                        }
+                        self.clear_edit();
+                        self.sender.send(Message::Refresh);
+                        self.sender.send(Message::Select);
+                    }
+                    _ => {
                         println!("NO HAY ELEMENTO PARA MODIFICAR!!!");
                     }
-                }
+                },
                 Some(Message::Delete) => {
                     self.save_button.deactivate();
-                    if self.list_browser.value() > 0 {
-                        let text_selection =
-                            self.list_browser.text(self.list_browser.value()).unwrap();
-                        match self
-                            .service
-                            .get_houses()
-                            .unwrap()
-                            .iter()
-                            .filter(|h| h.to_string().eq_ignore_ascii_case(&text_selection))
-                            .next()
-                        {
-                            Some(house) => {
-                                if self.service.delete_house(house.id).is_ok() {
-                                    self.show_message(&format!(
-                                        "Elemento #{} eliminado",
-                                        self.id_output.value()
-                                    ));
-                                    self.clear_edit();
-                                    self.sender.send(Message::Filter);
-                                    self.sender.send(Message::Select);
-                                } else {
-                                    self.show_message(&format!(
-                                        "Error eliminando el elemento {}",
-                                        self.id_output.value()
-                                    ));
+                    match self.selected_houses().as_slice() {
+                        [] => {
+                            println!("NO HAY ELEMENTO PARA ELIMINAR!!!");
+                        }
+                        [house] => {
+                            if self.service.delete_house(house.id).is_ok() {
+                                self.show_message(&format!(
+                                    "Elemento #{} eliminado",
+                                    self.id_output.value()
+                                ));
+                                self.clear_edit();
+                                self.sender.send(Message::Refresh);
+                                self.sender.send(Message::Select);
+                            } else {
+                                self.show_message(&format!(
+                                    "Error eliminando el elemento {}",
+                                    self.id_output.value()
+                                ));
+                            }
+                        }
+                        houses => {
+                            let ids: Vec<HouseId> = houses.iter().map(|h| h.id).collect();
+                            if self
+                                .confirm(&format!("¿Eliminar {} elementos?", ids.len()), "Eliminar")
+                            {
+                                match self.service.delete_houses(&ids) {
+                                    Ok(deleted) => {
+                                        self.show_message(&format!(
+                                            "{} elementos eliminados",
+                                            deleted
+                                        ));
+                                        self.clear_edit();
+                                        self.sender.send(Message::Refresh);
+                                    }
+                                    Err(e) => {
+                                        self.show_message(&format!(
+                                            "Error eliminando los elementos: {}",
+                                            e
+                                        ));
+                                    }
                                 }
                             }
-                            _ => {
-                                println!("ELEMENTO NO ENCONTRADO!!!");
+                        }
+                    }
+                }
+                Some(Message::KindChange) => {
+                    let values = self.custom_values();
+                    self.refresh_custom_fields(&values);
+                }
+                Some(Message::ChangeKind) => {
+                    let ids: Vec<HouseId> = self.selected_houses().iter().map(|h| h.id).collect();
+                    let kind_id = self.kind_input.value();
+                    if ids.is_empty() || kind_id < 0 {
+                        self.show_message("Seleccione los elementos y el nuevo tipo");
+                        continue;
+                    }
+                    let kind = self.kind_input.choice().unwrap_or_default();
+                    if self.confirm(
+                        &format!(
+                            "¿Cambiar el tipo de {} elemento/s a \"{}\"?",
+                            ids.len(),
+                            kind
+                        ),
+                        "Cambiar",
+                    ) {
+                        match self.service.change_kind(&ids, kind_id) {
+                            Ok(updated) => {
+                                self.show_message(&format!("{} elemento/s actualizado/s", updated));
+                                self.clear_edit();
+                                self.sender.send(Message::Refresh);
+                            }
+                            Err(e) => {
+                                self.show_message(&format!(
+                                    "Error actualizando los elementos: {}",
+                                    e
+                                ));
+                            }
+                        }
+                    }
+                }
+                Some(Message::SetField) => {
+                    let ids: Vec<HouseId> = self.selected_houses().iter().map(|h| h.id).collect();
+                    let field = self.selected_field();
+                    if ids.is_empty() || field.is_none() {
+                        self.show_message("Seleccione los elementos y el campo a editar");
+                        continue;
+                    }
+                    let label = self.bulk_field_input.choice().unwrap_or_default();
+                    let value = match dialog::input_default(
+                        &format!(
+                            "Nuevo valor de \"{}\" para {} elemento/s:",
+                            label,
+                            ids.len()
+                        ),
+                        "",
+                    ) {
+                        Some(value) => value,
+                        None => continue,
+                    };
+                    if self.confirm(
+                        &format!(
+                            "¿Asignar \"{}\" a \"{}\" en {} elemento/s?",
+                            value,
+                            label,
+                            ids.len()
+                        ),
+                        "Asignar",
+                    ) {
+                        match self.service.set_field(&ids, field.unwrap(), &value) {
+                            Ok(updated) => {
+                                self.show_message(&format!("{} elemento/s actualizado/s", updated));
+                                self.clear_edit();
+                                self.sender.send(Message::Refresh);
+                            }
+                            Err(e) => {
+                                self.show_message(&format!(
+                                    "Error actualizando los elementos: {}",
+                                    e
+                                ));
                             }
                         }
-                    } else {
-                        println!("NO HAY ELEMENTO PARA ELIMINAR!!!");
                     }
                 }
                 Some(Message::Save) => {
-                    let new_house = self.service.create_house(
-                        &self.street_input.value(),
-                        &self.street_number_input.value(),
-                        &self.street_floor_input.value(),
-                        &self.postal_code_input.value(),
-                        &self.surface_input.value(),
-                        &self.bathrooms_input.value(),
-                        &self.rooms_input.value(),
-                        self.kind_input.value(),
-                    );
-                    if new_house.is_err() {
-                        self.show_message(&format!("Error guardando el nuevo elemento"));
+                    if self.service.duplicate_policy == DuplicatePolicy::Warn {
+                        if let Ok(street_number) = self.street_number_input.value().parse::<i32>() {
+                            let address = Address {
+                                street: self.street_input.value(),
+                                street_number,
+                                floor: self.street_floor_input.value(),
+                                unit: self.street_unit_input.value(),
+                                postal_code: self.postal_code_input.value(),
+                                ..Address::default()
+                            };
+                            if let Ok(Some(duplicate)) = self.service.find_duplicate(&address) {
+                                if !self.confirm(
+                                    &format!(
+                                        "Ya existe el elemento #{} en la misma dirección. ¿Guardar de todos modos?",
+                                        duplicate.id
+                                    ),
+                                    "Guardar",
+                                ) {
+                                    continue;
+                                }
+                            }
+                        }
+                    }
+                    let amenities = match self.selected_amenities() {
+                        Ok(amenities) => amenities,
+                        Err(e) => {
+                            self.show_message(&e.to_string());
+                            continue;
+                        }
+                    };
+                    let new_house = self
+                        .form_draft(&amenities)
+                        .and_then(|draft| self.service.create_from_draft(&draft));
+                    if let Err(e @ ServiceError::Duplicate(_)) = new_house {
+                        self.show_message(&e.to_string());
+                    } else if let Err(e) = new_house {
+                        self.show_message(&format!("Error guardando el nuevo elemento: {}", e));
                     } else {
                         self.show_message(&format!("Elemento nuevo guardado"));
                         self.clear_edit();
-                        self.sender.send(Message::Filter);
+                        self.sender.send(Message::Refresh);
                         self.sender.send(Message::Select);
                         self.save_button.deactivate();
                     }
                 }
+                Some(Message::Duplicates) => {
+                    let groups = match self.service.find_duplicates() {
+                        Ok(groups) => groups,
+                        Err(e) => {
+                            self.show_message(&format!("Error buscando duplicados: {}", e));
+                            continue;
+                        }
+                    };
+                    if groups.is_empty() {
+                        self.show_message("No se encontraron duplicados");
+                        continue;
+                    }
+                    let mut merged = 0;
+                    for group in &groups {
+                        let ids: Vec<String> = group.iter().map(|h| format!("#{}", h.id)).collect();
+                        let choice = dialog::choice2_default(
+                            &format!(
+                                "{}\n\nElementos en la misma dirección: {}.\n¿Unificar en #{}?",
+                                group[0],
+                                ids.join(", "),
+                                group[0].id
+                            ),
+                            "Omitir",
+                            "Unificar",
+                            "Cancelar",
+                        );
+                        match choice {
+                            Some(1) => {
+                                let duplicate_ids: Vec<HouseId> =
+                                    group[1..].iter().map(|h| h.id).collect();
+                                match self.service.merge_houses(group[0].id, &duplicate_ids) {
+                                    Ok(_) => merged += 1,
+                                    Err(e) => {
+                                        self.show_message(&format!(
+                                            "Error unificando el elemento #{}: {}",
+                                            group[0].id, e
+                                        ));
+                                    }
+                                }
+                            }
+                            Some(2) | None => break,
+                            _ => {}
+                        }
+                    }
+                    self.show_message(&format!(
+                        "{} grupo/s de duplicados, {} unificado/s",
+                        groups.len(),
+                        merged
+                    ));
+                    self.clear_edit();
+                    self.sender.send(Message::Refresh);
+                }
+                Some(Message::History) => {
+                    if let [house] = self.selected_houses().as_slice() {
+                        match self.service.get_status_history(house.id) {
+                            Ok(history) => {
+                                let lines: Vec<String> = history
+                                    .iter()
+                                    .map(|change| {
+                                        format!(
+                                            "{}: {}",
+                                            utils::format_timestamp(&change.changed_at),
+                                            change.status
+                                        )
+                                    })
+                                    .collect();
+                                dialog::message_default(&format!(
+                                    "Historial del elemento #{}\n\n{}",
+                                    house.id,
+                                    lines.join("\n")
+                                ));
+                            }
+                            Err(_) => {
+                                self.show_message(&format!(
+                                    "Error leyendo el historial del elemento #{}",
+                                    house.id
+                                ));
+                            }
+                        }
+                    }
+                }
                 Some(Message::Select) => {
                     self.save_button.activate();
-                    if self.list_browser.value() == 0 {
-                        self.update_button.deactivate();
-                        self.delete_button.deactivate();
-                    } else {
-                        let text_selection =
-                            self.list_browser.text(self.list_browser.value()).unwrap();
+                    match self.selected_houses().as_slice() {
+                        [] => {
+                            self.update_button.deactivate();
+                            self.history_button.deactivate();
+                            self.set_bulk_active(false);
+                            self.refresh_linked_contacts(None);
+                            self.refresh_tags(None);
+                            self.refresh_attachments(None);
+                        }
+                        [house] => {
+                            self.fill_edit(house);
+                            self.refresh_linked_contacts(Some(house.id));
+                            self.refresh_tags(Some(house.id));
+                            self.refresh_attachments(Some(house.id));
+                            self.update_button.activate();
+                            self.history_button.activate();
+                            self.set_bulk_active(true);
 
-                        match self
-                            .service
-                            .get_houses()
-                            .unwrap()
-                            .iter()
-                            .filter(|h| h.to_string().eq_ignore_ascii_case(&text_selection))
-                            .next()
-                        {
-                            Some(house) => {
-                                self.id_output.set_value(&house.id.to_string());
-                                self.street_input.set_value(&house.street.to_string());
-                                self.street_number_input
-                                    .set_value(&house.street_number.to_string());
-                                self.street_floor_input
-                                    .set_value(&house.street_floor.to_string());
-                                self.postal_code_input
-                                    .set_value(&house.postal_code.to_string());
-                                self.surface_input
-                                    .set_value(&house.surface_square_meters.to_string());
-                                self.bathrooms_input.set_value(&house.bathrooms.to_string());
-                                self.rooms_input.set_value(&house.rooms.to_string());
-                                self.kind_input.set_value(house.kind_id);
-                                self.update_button.activate();
-                                self.delete_button.activate();
+                            self.show_message(&format!(
+                                "Elemento #{} seleccionado",
+                                self.id_output.value()
+                            ));
+                        }
+                        houses => {
+                            let count = houses.len();
+                            self.clear_edit();
+                            self.update_button.deactivate();
+                            self.history_button.deactivate();
+                            self.set_bulk_active(true);
+                            self.refresh_linked_contacts(None);
+                            self.refresh_tags(None);
+                            self.refresh_attachments(None);
 
+                            self.show_message(&format!("{} elementos seleccionados", count));
+                        }
+                    }
+                }
+                Some(Message::ContactsOpen) => {
+                    self.refresh_contacts();
+                    self.contacts_panel.clear_edit();
+                    self.contacts_panel.wind.show();
+                }
+                Some(Message::ContactSelect) => match self.selected_contact().cloned() {
+                    Some(contact) => {
+                        self.contacts_panel.fill_edit(&contact);
+                        self.contacts_panel
+                            .show_message(&format!("Contacto #{} seleccionado", contact.id));
+                    }
+                    None => self.contacts_panel.clear_edit(),
+                },
+                Some(Message::ContactNew) => {
+                    self.contacts_panel.list_browser.select(0);
+                    self.contacts_panel.clear_edit();
+                    self.contacts_panel.show_message("Complete los campos");
+                }
+                Some(Message::ContactSave) => {
+                    let panel = &self.contacts_panel;
+                    let (name, phone, email, notes) = (
+                        panel.name_input.value(),
+                        panel.phone_input.value(),
+                        panel.email_input.value(),
+                        panel.notes_input.value(),
+                    );
+                    let saved = match panel.id_output.value().parse::<i32>() {
+                        Ok(id) => self
+                            .service
+                            .update_contact(id, &name, &phone, &email, &notes),
+                        Err(_) => self.service.create_contact(&name, &phone, &email, &notes),
+                    };
+                    match saved {
+                        Ok(_) => {
+                            self.refresh_contacts();
+                            self.contacts_panel.clear_edit();
+                            self.contacts_panel.show_message("Contacto guardado");
+                            let house_id = self.selected_house_id();
+                            self.refresh_linked_contacts(house_id);
+                        }
+                        Err(e) => self
+                            .contacts_panel
+                            .show_message(&format!("Error guardando el contacto: {}", e)),
+                    }
+                }
+                Some(Message::ContactDelete) => {
+                    if let Some(contact) = self.selected_contact().cloned() {
+                        if !self.confirm(
+                            &format!("¿Eliminar el contacto \"{}\" y sus vínculos?", contact.name),
+                            "Eliminar",
+                        ) {
+                            continue;
+                        }
+                        match self.service.delete_contact(contact.id) {
+                            Ok(_) => {
+                                self.refresh_contacts();
+                                self.contacts_panel.clear_edit();
+                                self.contacts_panel
+                                    .show_message(&format!("Contacto #{} eliminado", contact.id));
+                                let house_id = self.selected_house_id();
+                                self.refresh_linked_contacts(house_id);
+                            }
+                            Err(e) => self.contacts_panel.show_message(&format!(
+                                "Error eliminando el contacto #{}: {}",
+                                contact.id, e
+                            )),
+                        }
+                    }
+                }
+                Some(Message::BuildingsOpen) => {
+                    self.refresh_buildings();
+                    self.buildings_panel.clear_edit();
+                    self.buildings_panel.wind.show();
+                }
+                Some(Message::BuildingSelect) => match self.selected_building().cloned() {
+                    Some(building) => match self.service.get_building_units(building.id) {
+                        Ok(units) => {
+                            self.buildings_panel.fill_edit(&building, &units);
+                            self.buildings_panel
+                                .show_message(&format!("Edificio #{} seleccionado", building.id));
+                        }
+                        Err(e) => self
+                            .buildings_panel
+                            .show_message(&format!("Error leyendo las unidades: {}", e)),
+                    },
+                    None => self.buildings_panel.clear_edit(),
+                },
+                Some(Message::BuildingNew) => {
+                    self.buildings_panel.list_browser.select(0);
+                    self.buildings_panel.clear_edit();
+                    self.buildings_panel.show_message("Complete los campos");
+                }
+                Some(Message::BuildingSave) => {
+                    let panel = &self.buildings_panel;
+                    let address = match panel.address() {
+                        Some(address) => address,
+                        None => {
+                            self.buildings_panel
+                                .show_message("Error convritiendo el número de la calle");
+                            continue;
+                        }
+                    };
+                    let (year_built, elevator) = (
+                        panel.year_built_input.value(),
+                        panel.elevator_check.is_checked(),
+                    );
+                    let saved = match panel.id_output.value().parse::<i32>() {
+                        Ok(id) => self
+                            .service
+                            .update_building(id, &address, &year_built, elevator),
+                        Err(_) => self
+                            .service
+                            .create_building(&address, &year_built, elevator)
+                            .map(|_| 1),
+                    };
+                    match saved {
+                        Ok(_) => {
+                            self.refresh_buildings();
+                            self.buildings_panel.clear_edit();
+                            self.buildings_panel.show_message("Edificio guardado");
+                            self.refresh_list();
+                        }
+                        Err(e) => self
+                            .buildings_panel
+                            .show_message(&format!("Error guardando el edificio: {}", e)),
+                    }
+                }
+                Some(Message::BuildingDelete) => {
+                    if let Some(building) = self.selected_building().cloned() {
+                        if !self.confirm(
+                            &format!("¿Eliminar el edificio \"{}\"?", building),
+                            "Eliminar",
+                        ) {
+                            continue;
+                        }
+                        match self.service.delete_building(building.id) {
+                            Ok(_) => {
+                                self.refresh_buildings();
+                                self.buildings_panel.clear_edit();
+                                self.buildings_panel
+                                    .show_message(&format!("Edificio #{} eliminado", building.id));
+                            }
+                            Err(e) => self.buildings_panel.show_message(&format!(
+                                "Error eliminando el edificio #{}: {}",
+                                building.id, e
+                            )),
+                        }
+                    }
+                }
+                Some(Message::BuildingUnits) => {
+                    if let Some(building) = self.selected_building() {
+                        self.filter_input
+                            .set_value(&format!("building:{}", building.id));
+                        self.sender.send(Message::Filter);
+                    }
+                }
+                Some(Message::LinkedContactSelect) => {
+                    if self.selected_linked_contact().is_some() {
+                        self.unlink_button.activate();
+                    } else {
+                        self.unlink_button.deactivate();
+                    }
+                }
+                Some(Message::LinkContact) => {
+                    let contact = usize::try_from(self.contact_input.value())
+                        .ok()
+                        .and_then(|index| self.contacts.get(index))
+                        .map(|c| c.id);
+                    match (self.selected_house_id(), contact) {
+                        (Some(house_id), Some(contact_id)) => {
+                            match self.service.link_contact(
+                                house_id,
+                                contact_id,
+                                self.role_input.value(),
+                            ) {
+                                Ok(_) => {
+                                    self.refresh_linked_contacts(Some(house_id));
+                                    self.show_message(&format!(
+                                        "Contacto #{} vinculado al elemento #{}",
+                                        contact_id, house_id
+                                    ));
+                                }
+                                Err(e) => self
+                                    .show_message(&format!("Error vinculando el contacto: {}", e)),
+                            }
+                        }
+                        _ => self.show_message("Seleccione un elemento y un contacto"),
+                    }
+                }
+                Some(Message::UnlinkContact) => {
+                    let link = self
+                        .selected_linked_contact()
+                        .map(|c| (c.contact_id, c.role_id));
+                    if let (Some(house_id), Some((contact_id, role_id))) =
+                        (self.selected_house_id(), link)
+                    {
+                        match self.service.unlink_contact(house_id, contact_id, role_id) {
+                            Ok(_) => {
+                                self.refresh_linked_contacts(Some(house_id));
                                 self.show_message(&format!(
-                                    "Elemento #{} seleccionado",
-                                    self.id_output.value()
+                                    "Contacto #{} desvinculado del elemento #{}",
+                                    contact_id, house_id
+                                ));
+                            }
+                            Err(e) => self
+                                .show_message(&format!("Error desvinculando el contacto: {}", e)),
+                        }
+                    }
+                }
+                Some(Message::TagSelect) => {
+                    if self.selected_tag().is_some() {
+                        self.untag_button.activate();
+                        self.rename_tag_button.activate();
+                    } else {
+                        self.untag_button.deactivate();
+                        self.rename_tag_button.deactivate();
+                    }
+                }
+                Some(Message::TagAdd) => {
+                    let Some(house_id) = self.selected_house_id() else {
+                        continue;
+                    };
+                    let name = self.tag_input.value().unwrap_or_default();
+                    match self.service.tag_house(house_id, &name) {
+                        Ok(_) => {
+                            self.refresh_tags(Some(house_id));
+                            self.show_message(&format!(
+                                "Elemento #{} etiquetado \"{}\"",
+                                house_id,
+                                name.trim()
+                            ));
+                        }
+                        Err(e) => self.show_message(&format!("Error etiquetando: {}", e)),
+                    }
+                }
+                Some(Message::TagRemove) => {
+                    let tag = self.selected_tag().cloned();
+                    if let (Some(house_id), Some(tag)) = (self.selected_house_id(), tag) {
+                        match self.service.untag_house(house_id, tag.id) {
+                            Ok(_) => {
+                                self.refresh_tags(Some(house_id));
+                                self.show_message(&format!(
+                                    "Etiqueta \"{}\" quitada del elemento #{}",
+                                    tag.name, house_id
                                 ));
                             }
-                            _ => {
-                                println!("ELEMENTO NO ENCONTRADO!!!");
+                            Err(e) => {
+                                self.show_message(&format!("Error quitando la etiqueta: {}", e))
                             }
                         }
                     }
                 }
-                Some(Message::Filter) => {
-                    self.save_button.deactivate();
-                    let prefix = self.filter_input.value().to_lowercase();
-                    let filter_empty = prefix.trim().eq_ignore_ascii_case("");
-                    self.list_browser.clear();
-                    for (_, h) in self.service.get_houses().unwrap().iter().enumerate() {
-                        if (h.id.to_string().contains(prefix.as_str()) && !filter_empty)
-                            || (filter_empty)
+                Some(Message::TagRename) => {
+                    let Some(tag) = self.selected_tag().cloned() else {
+                        continue;
+                    };
+                    let Some(name) = dialog::input_default(
+                        &format!("Nuevo nombre de \"{}\" en todos los elementos:", tag.name),
+                        &tag.name,
+                    ) else {
+                        continue;
+                    };
+                    match self.service.rename_tag(tag.id, &name) {
+                        Ok(_) => {
+                            self.refresh_tags(self.selected_house_id());
+                            self.show_message(&format!("Etiqueta \"{}\" renombrada", tag.name));
+                        }
+                        Err(e) => {
+                            self.show_message(&format!("Error renombrando la etiqueta: {}", e))
+                        }
+                    }
+                }
+                Some(Message::AttachmentAdd) => {
+                    let house_id = match self.selected_house_id() {
+                        Some(id) => id,
+                        None => continue,
+                    };
+                    let path = match dialog::file_chooser(
+                        "Adjuntar archivo",
+                        "*.{png,jpg,jpeg,gif,bmp,pdf}",
+                        ".",
+                        false,
+                    ) {
+                        Some(path) => path,
+                        None => continue,
+                    };
+                    let data = match fs::read(&path) {
+                        Ok(data) => data,
+                        Err(e) => {
+                            self.show_message(&format!("Error leyendo el archivo: {}", e));
+                            continue;
+                        }
+                    };
+                    let file_name = Path::new(&path)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or(path.clone());
+                    let caption = dialog::input_default("Descripción:", "").unwrap_or_default();
+                    match self
+                        .service
+                        .add_attachment(house_id, &file_name, data, &caption)
+                    {
+                        Ok(_) => {
+                            self.refresh_attachments(Some(house_id));
+                            self.refresh_saved_searches();
+                            self.show_message(&format!(
+                                "Archivo \"{}\" adjuntado al elemento #{}",
+                                file_name, house_id
+                            ));
+                        }
+                        Err(e) => self.show_message(&format!("Error adjuntando el archivo: {}", e)),
+                    }
+                }
+                Some(Message::AttachmentSelect(attachment_id)) => {
+                    self.selected_attachment = Some(attachment_id);
+                    let house_id = self.selected_house_id();
+                    self.refresh_attachments(house_id);
+                }
+                Some(Message::AttachmentMove(offset)) => {
+                    if let (Some(house_id), Some(attachment_id)) =
+                        (self.selected_house_id(), self.selected_attachment)
+                    {
+                        if let Err(e) =
+                            self.service
+                                .move_attachment(house_id, attachment_id, offset)
+                        {
+                            self.show_message(&format!("Error ordenando los adjuntos: {}", e));
+                        }
+                        self.refresh_attachments(Some(house_id));
+                    }
+                }
+                Some(Message::AttachmentRemove) => {
+                    let attachment = self
+                        .attachments
+                        .iter()
+                        .find(|a| Some(a.id) == self.selected_attachment)
+                        .map(|a| (a.id, a.house_id, a.file_name.clone()));
+                    if let Some((attachment_id, house_id, file_name)) = attachment {
+                        if !self
+                            .confirm(&format!("¿Quitar el adjunto \"{}\"?", file_name), "Quitar")
                         {
-                            let item = h.to_string();
-                            self.list_browser.add(&item);
+                            continue;
                         }
+                        match self.service.remove_attachment(attachment_id) {
+                            Ok(_) => {
+                                self.refresh_saved_searches();
+                                self.show_message(&format!(
+                                    "Adjunto \"{}\" quitado del elemento #{}",
+                                    file_name, house_id
+                                ))
+                            }
+                            Err(e) => {
+                                self.show_message(&format!("Error quitando el adjunto: {}", e))
+                            }
+                        }
+                        self.refresh_attachments(Some(house_id));
+                    }
+                }
+                Some(Message::Stats) => match self.service.get_report(&self.selected_filter()) {
+                    Ok(report) => {
+                        let mut stats_wind = Window::default()
+                            .with_size(WIDGET_WIDTH * 6, WIDGET_HEIGHT * 20)
+                            .with_label("Estadísticas");
+                        let mut buffer = TextBuffer::default();
+                        buffer.set_text(&report.to_string());
+                        let mut display = TextDisplay::default().size_of_parent();
+                        display.set_text_font(Font::Courier);
+                        display.set_buffer(buffer);
+                        stats_wind.end();
+                        stats_wind.make_resizable(true);
+                        stats_wind.show();
+                    }
+                    Err(e) => {
+                        self.show_message(&format!("Error generando las estadísticas: {}", e))
+                    }
+                },
+                Some(Message::ChartsOpen) | Some(Message::ChartRefresh) => {
+                    let houses = self.visible_houses();
+                    self.chart_panel.update(&houses);
+                    self.chart_panel.wind.show();
+                }
+                Some(Message::ChartExport) => {
+                    let mut chooser = dialog::NativeFileChooser::new(
+                        dialog::NativeFileChooserType::BrowseSaveFile,
+                    );
+                    chooser.set_title("Exportar gráfico");
+                    chooser.set_filter("*.png");
+                    chooser.set_option(dialog::NativeFileChooserOptions::SaveAsConfirm);
+                    chooser.show();
+                    let path = chooser.filename();
+                    if path.as_os_str().is_empty() {
+                        continue;
+                    }
+                    let path = path.with_extension("png").to_string_lossy().to_string();
+                    let canvas = &self.chart_panel.canvas;
+                    let exported = self.chart_panel.chart.borrow().export_png(
+                        &path,
+                        canvas.width(),
+                        canvas.height(),
+                    );
+                    match exported {
+                        Ok(()) => self.show_message(&format!("Gráfico exportado a {}", path)),
+                        Err(e) => self.show_message(&format!("Error exportando el gráfico: {}", e)),
+                    }
+                }
+                Some(Message::PrintSheet) => {
+                    let ids: Vec<HouseId> = self.selected_houses().iter().map(|h| h.id).collect();
+                    let mut chooser = dialog::NativeFileChooser::new(
+                        dialog::NativeFileChooserType::BrowseSaveFile,
+                    );
+                    chooser.set_title("Imprimir ficha");
+                    chooser.set_filter("PDF\t*.pdf\nHTML\t*.html");
+                    chooser.set_option(dialog::NativeFileChooserOptions::SaveAsConfirm);
+                    chooser.show();
+                    let mut path = chooser.filename();
+                    if path.as_os_str().is_empty() {
+                        continue;
+                    }
+                    if SheetFormat::from_path(&path.to_string_lossy()).is_none() {
+                        path.set_extension("pdf");
+                    }
+                    let path = path.to_string_lossy().to_string();
+                    let printed = self
+                        .service
+                        .get_listing_sheets(&ids)
+                        .map_err(|e| e.to_string())
+                        .and_then(|sheets| sheet::export(&sheets, &path));
+                    match printed {
+                        Ok(()) => self.show_message(&format!(
+                            "{} ficha/s guardada/s en {}",
+                            ids.len(),
+                            path
+                        )),
+                        Err(e) => self.show_message(&format!("Error generando la ficha: {}", e)),
+                    }
+                }
+                Some(Message::SavedSearchSelect) => {
+                    if let Some(search) = self.selected_saved_search().cloned() {
+                        self.apply_saved_search(&search);
+                        self.delete_search_button.activate();
+                        self.sender.send(Message::Filter);
+                    } else {
+                        self.delete_search_button.deactivate();
+                    }
+                }
+                Some(Message::SavedSearchSave) => {
+                    let default_name = self
+                        .selected_saved_search()
+                        .map(|s| s.name.clone())
+                        .unwrap_or_default();
+                    let Some(name) = dialog::input_default("Nombre de la búsqueda:", &default_name)
+                    else {
+                        continue;
+                    };
+                    let search = self.current_search(&name);
+                    match self.service.save_search(&search) {
+                        Ok(_) => {
+                            self.refresh_saved_searches();
+                            self.show_message(&format!("Búsqueda \"{}\" guardada", name.trim()));
+                        }
+                        Err(e) => self.show_message(&format!("Error guardando la búsqueda: {}", e)),
+                    }
+                }
+                Some(Message::SavedSearchDelete) => {
+                    if let Some(search) = self.selected_saved_search().cloned() {
+                        if !self.confirm(
+                            &format!("¿Quitar la búsqueda \"{}\"?", search.name),
+                            "Quitar",
+                        ) {
+                            continue;
+                        }
+                        match self.service.delete_saved_search(search.id) {
+                            Ok(_) => {
+                                self.refresh_saved_searches();
+                                self.show_message(&format!("Búsqueda \"{}\" quitada", search.name));
+                            }
+                            Err(e) => {
+                                self.show_message(&format!("Error quitando la búsqueda: {}", e))
+                            }
+                        }
+                    }
+                }
+                Some(Message::Refresh) => {
+                    self.refresh_saved_searches();
+                    self.sender.send(Message::Filter);
+                }
+                Some(Message::Filter) => {
+                    self.save_button.deactivate();
+                    self.refresh_list();
+                    let filter = self.selected_filter();
+                    if let Err(error) = self.selected_query() {
+                        self.show_message(&format!("Filtro: {}", error));
+                    } else if let Some(radius) = filter.near {
+                        let nearest = self
+                            .service
+                            .find_houses_within(radius.center, radius.km)
+                            .ok()
+                            .and_then(|houses| houses.into_iter().next());
+                        self.show_message(&match nearest {
+                            Some((house, distance)) => format!(
+                                "{} elemento/s a menos de {} km, el más cercano #{} a {:.2} km",
+                                self.list_browser.size(),
+                                radius.km,
+                                house.id,
+                                distance
+                            ),
+                            None => format!("Ningún elemento a menos de {} km", radius.km),
+                        });
+                    } else if filter.bounds.is_some() {
+                        self.show_message(&format!(
+                            "{} elemento/s dentro del área",
+                            self.list_browser.size()
+                        ));
+                    } else if !self.near_input.value().trim().is_empty() {
+                        self.show_message(
+                            "Cerca de: ingrese latitud, longitud y radio en km, o sur, oeste, norte y este",
+                        );
                     }
                     self.sender.send(Message::Select);
                 }
@@ -412,3 +2815,27 @@ impl GUI {
         }
     }
 }
+
+fn menu_label(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('/', "\\/")
+        .replace('&', "&&")
+        .replace('|', " ")
+}
+
+fn set_thumbnail(button: &mut Button, attachment: &Attachment) {
+    match attachment.mime_type.as_str() {
+        "image/png" => button.set_image(scaled(PngImage::from_data(&attachment.data))),
+        "image/jpeg" => button.set_image(scaled(JpegImage::from_data(&attachment.data))),
+        "image/gif" => button.set_image(scaled(GifImage::from_data(&attachment.data))),
+        "image/bmp" => button.set_image(scaled(BmpImage::from_data(&attachment.data))),
+        _ => {}
+    }
+}
+
+fn scaled<I: ImageExt>(image: Result<I, FltkError>) -> Option<I> {
+    image.ok().map(|mut image| {
+        image.scale(THUMBNAIL_SIZE, THUMBNAIL_SIZE, true, true);
+        image
+    })
+}
@@ -0,0 +1,451 @@
+use std::fmt;
+
+/// Field names accepted in a query, shown when an unknown one is used.
+pub const FIELDS: &str =
+    "id, number, surface, rooms, bathrooms, price, building, kind, street, floor, \
+unit, cp, city, province, country, currency, operation, status, tag, amenity, @campo";
+
+/// Conditions that must all hold, parsed from text such as
+/// `kind:departamento rooms>=3 surface<80 cp:1425 street~"rivadavia"`.
+///
+/// Each term is a field, an operator and a value, separated from the next
+/// term by spaces. Values with spaces go between double quotes.
+///
+/// - Numbers (`id`, `number`, `surface`, `rooms`, `bathrooms`, `price`,
+///   `building`) take `:` or `=`, `<`, `<=`, `>` and `>=`. `building` is the
+///   id of the building of an apartment.
+/// - Text (`kind`, `street`, `floor`, `unit`, `cp`, `city`, `province`,
+///   `country`, `currency`, `operation`, `status`) takes `:` or `=` for the
+///   whole value and `~` for part of it, ignoring case.
+/// - `tag` takes tag names separated by commas and matches houses with any of
+///   them; repeating it requires all: `tag:exclusivo,"a reciclar" tag:mascotas`.
+/// - `amenity` works the same way with amenity names, ignoring accents:
+///   `amenity:cochera amenity:pileta,jardin`.
+/// - `@` followed by the name of a custom field, between double quotes when
+///   it has spaces, compares its value according to the field type:
+///   `@expensas<=80000 @"apto credito":si`. Numbers and dates take `<`, `<=`,
+///   `>` and `>=`; any field takes `:` or `=` and `~`. Houses of kinds
+///   without the field, or without a value for it, don't match.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Query {
+    pub conditions: Vec<Condition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Condition {
+    Number(NumberField, Comparison, i64),
+    Text(TextField, TextMatch, String),
+    /// Houses with any of the tags.
+    Tags(TextMatch, Vec<String>),
+    /// Houses with any of the amenities.
+    Amenities(TextMatch, Vec<String>),
+    /// Houses whose custom field, named ignoring case and accents, has a
+    /// matching value.
+    Custom(String, CustomMatch, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberField {
+    Id,
+    StreetNumber,
+    SurfaceSquareMeters,
+    Rooms,
+    Bathrooms,
+    Price,
+    Building,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextField {
+    Kind,
+    Street,
+    Floor,
+    Unit,
+    PostalCode,
+    City,
+    Province,
+    Country,
+    Currency,
+    Operation,
+    Status,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextMatch {
+    Equal,
+    Contains,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CustomMatch {
+    Compare(Comparison),
+    Contains,
+}
+
+/// Why a query can't be parsed. `position` counts characters from 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub position: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (posición {})", self.message, self.position)
+    }
+}
+
+enum Field {
+    Number(NumberField),
+    Text(TextField),
+    Tag,
+    Amenity,
+    Custom(String),
+}
+
+impl Query {
+    pub fn parse(text: &str) -> Result<Query, ParseError> {
+        let mut parser = Parser {
+            chars: text.chars().collect(),
+            position: 0,
+        };
+        let mut conditions = vec![];
+        while parser.skip_spaces() {
+            conditions.push(parser.condition()?);
+        }
+        Ok(Query { conditions })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.conditions.is_empty()
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    /// Moves past spaces and tells whether there is anything left.
+    fn skip_spaces(&mut self) -> bool {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.position += 1;
+        }
+        self.peek().is_some()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn error<T>(&self, position: usize, message: String) -> Result<T, ParseError> {
+        Err(ParseError {
+            position: position + 1,
+            message,
+        })
+    }
+
+    fn condition(&mut self) -> Result<Condition, ParseError> {
+        let start = self.position;
+        let custom = self.peek() == Some('@');
+        if custom {
+            self.position += 1;
+        }
+        let name = if custom && self.peek() == Some('"') {
+            self.quoted()?
+        } else {
+            let name_start = self.position;
+            while self.peek().is_some_and(|c| c.is_alphabetic() || c == '_') {
+                self.position += 1;
+            }
+            self.chars[name_start..self.position].iter().collect()
+        };
+        if name.trim().is_empty() {
+            return self.error(start, "Se esperaba un campo".to_string());
+        }
+        let field = match name.to_lowercase().as_str() {
+            _ if custom => Field::Custom(name.trim().to_string()),
+            "id" => Field::Number(NumberField::Id),
+            "number" => Field::Number(NumberField::StreetNumber),
+            "surface" => Field::Number(NumberField::SurfaceSquareMeters),
+            "rooms" => Field::Number(NumberField::Rooms),
+            "bathrooms" => Field::Number(NumberField::Bathrooms),
+            "price" => Field::Number(NumberField::Price),
+            "building" => Field::Number(NumberField::Building),
+            "kind" => Field::Text(TextField::Kind),
+            "street" => Field::Text(TextField::Street),
+            "floor" => Field::Text(TextField::Floor),
+            "unit" => Field::Text(TextField::Unit),
+            "cp" => Field::Text(TextField::PostalCode),
+            "city" => Field::Text(TextField::City),
+            "province" => Field::Text(TextField::Province),
+            "country" => Field::Text(TextField::Country),
+            "currency" => Field::Text(TextField::Currency),
+            "operation" => Field::Text(TextField::Operation),
+            "status" => Field::Text(TextField::Status),
+            "tag" => Field::Tag,
+            "amenity" => Field::Amenity,
+            _ => {
+                return self.error(
+                    start,
+                    format!("Campo desconocido \"{name}\", use uno de: {FIELDS}"),
+                )
+            }
+        };
+
+        let operator_start = self.position;
+        let operator = match (self.peek(), self.chars.get(self.position + 1)) {
+            (Some('<' | '>'), Some('=')) => 2,
+            (Some(':' | '=' | '<' | '>' | '~'), _) => 1,
+            _ => {
+                return self.error(
+                    operator_start,
+                    format!("Se esperaba un operador (: = < <= > >= ~) después de \"{name}\""),
+                )
+            }
+        };
+        self.position += operator;
+        let operator: String = self.chars[operator_start..self.position].iter().collect();
+
+        let value_start = self.position;
+        let value = self.value()?;
+        match field {
+            Field::Custom(name) => {
+                let custom_match = match operator.as_str() {
+                    ":" | "=" => CustomMatch::Compare(Comparison::Equal),
+                    "<" => CustomMatch::Compare(Comparison::Less),
+                    "<=" => CustomMatch::Compare(Comparison::LessOrEqual),
+                    ">" => CustomMatch::Compare(Comparison::Greater),
+                    ">=" => CustomMatch::Compare(Comparison::GreaterOrEqual),
+                    _ => CustomMatch::Contains,
+                };
+                Ok(Condition::Custom(name, custom_match, value))
+            }
+            Field::Number(field) => {
+                let comparison = match operator.as_str() {
+                    ":" | "=" => Comparison::Equal,
+                    "<" => Comparison::Less,
+                    "<=" => Comparison::LessOrEqual,
+                    ">" => Comparison::Greater,
+                    ">=" => Comparison::GreaterOrEqual,
+                    _ => {
+                        return self.error(
+                            operator_start,
+                            format!("\"{name}\" es un número y no admite \"{operator}\""),
+                        )
+                    }
+                };
+                let number = value
+                    .parse::<i64>()
+                    .ok()
+                    .filter(|n| field == NumberField::Price || i32::try_from(*n).is_ok());
+                match number {
+                    Some(number) => Ok(Condition::Number(field, comparison, number)),
+                    None => self.error(value_start, format!("Número inválido \"{value}\"")),
+                }
+            }
+            Field::Text(_) | Field::Tag | Field::Amenity => {
+                let text_match = match operator.as_str() {
+                    ":" | "=" => TextMatch::Equal,
+                    "~" => TextMatch::Contains,
+                    _ => {
+                        return self.error(
+                            operator_start,
+                            format!("\"{name}\" es un texto y no admite \"{operator}\""),
+                        )
+                    }
+                };
+                let names = list_names(&value);
+                match field {
+                    Field::Text(field) => Ok(Condition::Text(field, text_match, value)),
+                    _ if names.is_empty() => self.error(value_start, "Falta el nombre".to_string()),
+                    Field::Tag => Ok(Condition::Tags(text_match, names)),
+                    _ => Ok(Condition::Amenities(text_match, names)),
+                }
+            }
+        }
+    }
+
+    /// Reads the text between the double quotes at the current position.
+    fn quoted(&mut self) -> Result<String, ParseError> {
+        let quote = self.position;
+        self.position += 1;
+        let start = self.position;
+        while self.peek().is_some_and(|c| c != '"') {
+            self.position += 1;
+        }
+        if self.peek().is_none() {
+            return self.error(quote, "Comillas sin cerrar".to_string());
+        }
+        let text = self.chars[start..self.position].iter().collect();
+        self.position += 1;
+        Ok(text)
+    }
+
+    /// Reads up to the next space outside double quotes, dropping the quotes.
+    fn value(&mut self) -> Result<String, ParseError> {
+        let start = self.position;
+        let mut value = String::new();
+        let mut open_quote = None;
+        while let Some(c) = self.peek() {
+            if c == '"' {
+                open_quote = match open_quote {
+                    Some(_) => None,
+                    None => Some(self.position),
+                };
+            } else if c.is_whitespace() && open_quote.is_none() {
+                break;
+            } else {
+                value.push(c);
+            }
+            self.position += 1;
+        }
+        if let Some(quote) = open_quote {
+            return self.error(quote, "Comillas sin cerrar".to_string());
+        }
+        if self.position == start {
+            return self.error(start, "Falta el valor".to_string());
+        }
+        Ok(value)
+    }
+}
+
+fn list_names(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error(text: &str) -> ParseError {
+        Query::parse(text).unwrap_err()
+    }
+
+    #[test]
+    fn parses_every_kind_of_condition() {
+        let query =
+            Query::parse(r#"  kind:departamento rooms>=3 surface<80 street~"santa fe"  price=100"#)
+                .unwrap();
+        assert_eq!(
+            query.conditions,
+            [
+                Condition::Text(TextField::Kind, TextMatch::Equal, "departamento".into()),
+                Condition::Number(NumberField::Rooms, Comparison::GreaterOrEqual, 3),
+                Condition::Number(NumberField::SurfaceSquareMeters, Comparison::Less, 80),
+                Condition::Text(TextField::Street, TextMatch::Contains, "santa fe".into()),
+                Condition::Number(NumberField::Price, Comparison::Equal, 100),
+            ]
+        );
+        assert!(Query::parse("   ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parses_quoted_custom_field_names() {
+        let query =
+            Query::parse(r#"@expensas<=80000 @"apto credito":si @Orientación~nor"#).unwrap();
+        assert_eq!(
+            query.conditions,
+            [
+                Condition::Custom(
+                    "expensas".into(),
+                    CustomMatch::Compare(Comparison::LessOrEqual),
+                    "80000".into()
+                ),
+                Condition::Custom(
+                    "apto credito".into(),
+                    CustomMatch::Compare(Comparison::Equal),
+                    "si".into()
+                ),
+                Condition::Custom("Orientación".into(), CustomMatch::Contains, "nor".into()),
+            ]
+        );
+        assert_eq!(parse_error("rooms:2 @ :3").position, 9);
+        assert_eq!(parse_error(r#"@"apto credito:si"#).position, 2);
+    }
+
+    #[test]
+    fn parses_tag_and_amenity_lists() {
+        let query =
+            Query::parse(r#"tag:exclusivo,"a reciclar" tag~mas amenity:cochera,,pileta"#).unwrap();
+        assert_eq!(
+            query.conditions,
+            [
+                Condition::Tags(
+                    TextMatch::Equal,
+                    vec!["exclusivo".into(), "a reciclar".into()]
+                ),
+                Condition::Tags(TextMatch::Contains, vec!["mas".into()]),
+                Condition::Amenities(TextMatch::Equal, vec!["cochera".into(), "pileta".into()]),
+            ]
+        );
+        let error = parse_error("rooms:2 tag:,");
+        assert_eq!(error.position, 13);
+        assert_eq!(error.message, "Falta el nombre");
+    }
+
+    #[test]
+    fn reports_an_unknown_field_at_its_start() {
+        let error = parse_error("rooms:2 color:rojo");
+        assert_eq!(error.position, 9);
+        assert!(error.message.starts_with("Campo desconocido \"color\""));
+        assert_eq!(parse_error(":3").position, 1);
+        assert_eq!(parse_error("rooms:2 :3").message, "Se esperaba un campo");
+    }
+
+    #[test]
+    fn reports_a_missing_or_wrong_operator_at_its_place() {
+        let missing = parse_error("rooms:2 surface 80");
+        assert_eq!(missing.position, 16);
+        assert!(missing.message.starts_with("Se esperaba un operador"));
+        assert_eq!(parse_error("rooms").position, 6);
+
+        let wrong = parse_error("city<Rosario");
+        assert_eq!(wrong.position, 5);
+        assert_eq!(wrong.message, "\"city\" es un texto y no admite \"<\"");
+        assert_eq!(parse_error("rooms~3").position, 6);
+    }
+
+    #[test]
+    fn reports_an_unclosed_quote_at_the_quote() {
+        let error = parse_error(r#"rooms:2 street:"santa fe"#);
+        assert_eq!(error.position, 16);
+        assert_eq!(error.message, "Comillas sin cerrar");
+    }
+
+    #[test]
+    fn reports_a_bad_number_at_the_value() {
+        let error = parse_error("rooms:2 surface>=8o");
+        assert_eq!(error.position, 18);
+        assert_eq!(error.message, "Número inválido \"8o\"");
+        assert_eq!(parse_error("id:3000000000").position, 4);
+        assert!(Query::parse("price<3000000000").is_ok());
+    }
+
+    #[test]
+    fn reports_an_empty_value_where_it_was_expected() {
+        let error = parse_error("rooms: surface>1");
+        assert_eq!(error.position, 7);
+        assert_eq!(error.message, "Falta el valor");
+        assert_eq!(parse_error("kind:departamento city~").position, 24);
+    }
+
+    #[test]
+    fn positions_count_characters() {
+        assert_eq!(parse_error(r#"street:"año" ñandú:1"#).position, 14);
+    }
+}
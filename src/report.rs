@@ -0,0 +1,326 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+use crate::models::HouseWithKind;
+
+/// Statistics of a set of houses.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub total: usize,
+    pub by_kind: Vec<Count>,
+    pub by_postal_code: Vec<Count>,
+    pub surface_by_kind: Vec<KindSummary>,
+    pub rooms_by_kind: Vec<KindSummary>,
+    pub bathrooms_distribution: Vec<Distribution>,
+    pub rooms_distribution: Vec<Distribution>,
+    pub price_per_square_meter: Vec<PriceSummary>,
+}
+
+/// Number of houses with a given label.
+#[derive(Debug, Serialize)]
+pub struct Count {
+    pub label: String,
+    pub count: usize,
+}
+
+/// Average, median and range of a set of values.
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub count: usize,
+    pub average: f64,
+    pub median: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// Summary of the houses of one kind.
+#[derive(Debug, Serialize)]
+pub struct KindSummary {
+    pub kind: String,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+/// Number of houses with a given value.
+#[derive(Debug, Serialize)]
+pub struct Distribution {
+    pub value: i32,
+    pub count: usize,
+}
+
+/// Summary of the price per m2 for an operation and currency.
+#[derive(Debug, Serialize)]
+pub struct PriceSummary {
+    pub operation: String,
+    pub currency: String,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+impl Report {
+    pub fn build(houses: &[HouseWithKind]) -> Report {
+        let mut by_kind: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut by_postal_code: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut surfaces: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+        let mut rooms: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+        let mut bathrooms_distribution: BTreeMap<i32, usize> = BTreeMap::new();
+        let mut rooms_distribution: BTreeMap<i32, usize> = BTreeMap::new();
+        let mut prices: BTreeMap<(&str, &str), Vec<f64>> = BTreeMap::new();
+
+        for house in houses {
+            *by_kind.entry(&house.kind).or_default() += 1;
+            *by_postal_code
+                .entry(house.postal_code.as_str())
+                .or_default() += 1;
+            surfaces
+                .entry(&house.kind)
+                .or_default()
+                .push(house.surface_square_meters.get() as f64);
+            rooms
+                .entry(&house.kind)
+                .or_default()
+                .push(house.rooms.get() as f64);
+            *bathrooms_distribution
+                .entry(house.bathrooms.get())
+                .or_default() += 1;
+            *rooms_distribution.entry(house.rooms.get()).or_default() += 1;
+            if let Some(price) = house.price {
+                prices
+                    .entry((&house.operation, &house.currency))
+                    .or_default()
+                    .push(price as f64 / house.surface_square_meters.get() as f64);
+            }
+        }
+
+        Report {
+            total: houses.len(),
+            by_kind: counts(by_kind),
+            by_postal_code: counts(by_postal_code),
+            surface_by_kind: kind_summaries(surfaces),
+            rooms_by_kind: kind_summaries(rooms),
+            bathrooms_distribution: distribution(bathrooms_distribution),
+            rooms_distribution: distribution(rooms_distribution),
+            price_per_square_meter: prices
+                .into_iter()
+                .filter_map(|((operation, currency), values)| {
+                    Some(PriceSummary {
+                        operation: operation.to_string(),
+                        currency: currency.to_string(),
+                        summary: Summary::of(values)?,
+                    })
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Groups surfaces into `bins` ranges of equal width, rounded up to a
+/// multiple of 10 m2 so the labels stay readable.
+pub fn surface_histogram(houses: &[HouseWithKind], bins: usize) -> Vec<Count> {
+    let surfaces: Vec<i32> = houses
+        .iter()
+        .map(|h| h.surface_square_meters.get())
+        .collect();
+    let (min, max) = match (surfaces.iter().min(), surfaces.iter().max()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return vec![],
+    };
+    let start = min.div_euclid(10) * 10;
+    let span = (max - start + 1) as usize;
+    let width = (span.div_ceil(bins.max(1)).div_ceil(10) * 10) as i32;
+    let mut counts = vec![0; span.div_ceil(width as usize)];
+    for surface in surfaces {
+        counts[((surface - start) / width) as usize] += 1;
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(bin, count)| {
+            let from = start + bin as i32 * width;
+            Count {
+                label: format!("{}-{}", from, from + width - 1),
+                count,
+            }
+        })
+        .collect()
+}
+
+impl Summary {
+    fn of(mut values: Vec<f64>) -> Option<Summary> {
+        if values.is_empty() {
+            return None;
+        }
+        values.sort_by(f64::total_cmp);
+        let count = values.len();
+        let median = if count % 2 == 1 {
+            values[count / 2]
+        } else {
+            (values[count / 2 - 1] + values[count / 2]) / 2.0
+        };
+        Some(Summary {
+            count,
+            average: values.iter().sum::<f64>() / count as f64,
+            median,
+            min: values[0],
+            max: values[count - 1],
+        })
+    }
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} elemento/s, promedio {}, mediana {}, mínimo {}, máximo {}",
+            self.count,
+            number(self.average),
+            number(self.median),
+            number(self.min),
+            number(self.max)
+        )
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Elementos: {}", self.total)?;
+
+        writeln!(f, "\nPor tipo")?;
+        for c in &self.by_kind {
+            writeln!(f, "  {}: {}", c.label, c.count)?;
+        }
+        writeln!(f, "\nPor código postal")?;
+        for c in &self.by_postal_code {
+            writeln!(f, "  {}: {}", c.label, c.count)?;
+        }
+        writeln!(f, "\nSuperficie por tipo (m2)")?;
+        for s in &self.surface_by_kind {
+            writeln!(f, "  {}: {}", s.kind, s.summary)?;
+        }
+        writeln!(f, "\nHabitaciones por tipo")?;
+        for s in &self.rooms_by_kind {
+            writeln!(f, "  {}: {}", s.kind, s.summary)?;
+        }
+        writeln!(f, "\nDistribución de baños")?;
+        for d in &self.bathrooms_distribution {
+            writeln!(f, "  {} baño/s: {}", d.value, d.count)?;
+        }
+        writeln!(f, "\nDistribución de habitaciones")?;
+        for d in &self.rooms_distribution {
+            writeln!(f, "  {} habitación/es: {}", d.value, d.count)?;
+        }
+        writeln!(f, "\nPrecio por m2")?;
+        if self.price_per_square_meter.is_empty() {
+            writeln!(f, "  Sin elementos con precio")?;
+        }
+        for p in &self.price_per_square_meter {
+            writeln!(f, "  {} en {}: {}", p.operation, p.currency, p.summary)?;
+        }
+        Ok(())
+    }
+}
+
+fn counts(map: BTreeMap<&str, usize>) -> Vec<Count> {
+    map.into_iter()
+        .map(|(label, count)| Count {
+            label: label.to_string(),
+            count,
+        })
+        .collect()
+}
+
+fn kind_summaries(map: BTreeMap<&str, Vec<f64>>) -> Vec<KindSummary> {
+    map.into_iter()
+        .filter_map(|(kind, values)| {
+            Some(KindSummary {
+                kind: kind.to_string(),
+                summary: Summary::of(values)?,
+            })
+        })
+        .collect()
+}
+
+fn distribution(map: BTreeMap<i32, usize>) -> Vec<Distribution> {
+    map.into_iter()
+        .map(|(value, count)| Distribution { value, count })
+        .collect()
+}
+
+fn number(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDateTime;
+
+    use super::*;
+    use crate::types::{Floor, HouseId, KindId, PostalCode, RoomCount, Surface};
+
+    fn house(kind: &str, surface_square_meters: i32, rooms: i32) -> HouseWithKind {
+        HouseWithKind {
+            id: HouseId::new(1).unwrap(),
+            street: "Corrientes".to_string(),
+            street_number: 100,
+            street_floor: Floor::new("").unwrap(),
+            postal_code: PostalCode::new("C1043").unwrap(),
+            surface_square_meters: Surface::new(surface_square_meters).unwrap(),
+            bathrooms: RoomCount::new(1).unwrap(),
+            rooms: RoomCount::new(rooms).unwrap(),
+            kind_id: KindId::new(0).unwrap(),
+            kind: kind.to_string(),
+            version: 0,
+            created_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+            street_unit: String::new(),
+            city: String::new(),
+            province: String::new(),
+            country: String::new(),
+            price: None,
+            currency: "ARS".to_string(),
+            operation_id: 0,
+            status_id: 0,
+            operation: "Venta".to_string(),
+            status: "Disponible".to_string(),
+            latitude: None,
+            longitude: None,
+            description: String::new(),
+            building_id: None,
+        }
+    }
+
+    #[test]
+    fn median_of_an_even_count_is_the_mean_of_the_middle_values() {
+        let summary = Summary::of(vec![90.0, 30.0, 60.0, 40.0]).unwrap();
+        assert_eq!(summary.median, 50.0);
+        assert_eq!(
+            (summary.min, summary.max, summary.average),
+            (30.0, 90.0, 55.0)
+        );
+        assert_eq!(Summary::of(vec![30.0, 60.0, 40.0]).unwrap().median, 40.0);
+    }
+
+    #[test]
+    fn kinds_without_houses_are_left_out() {
+        assert!(Summary::of(Vec::new()).is_none());
+
+        let report = Report::build(&[house("Casa", 100, 3), house("Casa", 60, 2)]);
+        assert_eq!(report.total, 2);
+        assert_eq!(report.by_kind.len(), 1);
+        assert_eq!(report.surface_by_kind.len(), 1);
+        assert_eq!(report.surface_by_kind[0].kind, "Casa");
+        assert_eq!(report.surface_by_kind[0].summary.median, 80.0);
+        assert!(report.price_per_square_meter.is_empty());
+
+        let report = Report::build(&[]);
+        assert_eq!(report.total, 0);
+        assert!(report.by_kind.is_empty() && report.rooms_by_kind.is_empty());
+        assert!(report.to_string().contains("Sin elementos con precio"));
+    }
+}
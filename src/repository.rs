@@ -1,25 +1,224 @@
+use chrono::Utc;
 use diesel::associations::HasTable;
+use diesel::connection::{AnsiTransactionManager, TransactionManager};
+use diesel::dsl::sql;
 use diesel::prelude::*;
 use diesel::result::Error;
+use diesel::sql_types::{Bool, Double, Text};
+use diesel::sqlite::Sqlite;
 use diesel::SqliteConnection;
 
-use crate::models::{House, HouseKind, HouseWithKind, NewHouse};
+use crate::address::Address;
+use crate::geo::Coordinates;
+use crate::models::{
+    Amenity, Attachment, Building, Contact, ContactRole, CustomField, CustomFieldKind,
+    CustomFieldType, CustomValue, House, HouseAmenity, HouseChange, HouseContact, HouseFilter,
+    HouseKind, HouseOperation, HouseOrder, HouseStatus, HouseStatusChange, HouseTag, HouseWithKind,
+    LinkedContact, NewAttachment, NewBuilding, NewContact, NewCustomField, NewHouse,
+    NewHouseStatusChange, NewSavedSearch, NewTag, SavedSearch, Tag,
+};
+use crate::query::{Comparison, Condition, CustomMatch, NumberField, TextField, TextMatch};
 use crate::schema::houses::dsl::*;
+use crate::schema::houses_attachments;
+use crate::schema::houses_contacts;
 use crate::schema::houses_kind::dsl::{houses_kind, id as houses_kind_id};
+use crate::schema::houses_operation::dsl::{houses_operation, id as houses_operation_id};
+use crate::schema::houses_status::dsl::{houses_status, id as houses_status_id};
+use crate::schema::houses_status_history;
+use crate::schema::{amenities, buildings, houses_amenities};
+use crate::schema::{contacts, contacts_role, houses_tags, saved_searches, tags};
+use crate::schema::{custom_fields, custom_fields_kind, houses_custom_values};
+use crate::types::HouseId;
+use crate::utils;
 
+diesel::sql_function!(fn last_insert_rowid() -> Integer);
+
+type FilteredHouses<'a> = diesel::dsl::IntoBoxed<
+    'a,
+    diesel::dsl::InnerJoin<
+        diesel::dsl::InnerJoin<
+            diesel::dsl::InnerJoin<crate::schema::houses::table, crate::schema::houses_kind::table>,
+            crate::schema::houses_operation::table,
+        >,
+        crate::schema::houses_status::table,
+    >,
+    Sqlite,
+>;
+
+/// Applies `comparison` between a column and a value to a boxed query.
+macro_rules! compare {
+    ($query:expr, $column:expr, $comparison:expr, $value:expr) => {
+        match $comparison {
+            Comparison::Equal => $query.filter($column.eq($value)),
+            Comparison::Less => $query.filter($column.lt($value)),
+            Comparison::LessOrEqual => $query.filter($column.le($value)),
+            Comparison::Greater => $query.filter($column.gt($value)),
+            Comparison::GreaterOrEqual => $query.filter($column.ge($value)),
+        }
+    };
+}
+
+/// `LIKE` pattern for `value`, escaping its wildcards with `\`. SQLite
+/// compares ASCII letters ignoring case.
+fn like_pattern(text_match: TextMatch, value: &str) -> String {
+    let value = value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    match text_match {
+        TextMatch::Equal => value,
+        TextMatch::Contains => format!("%{value}%"),
+    }
+}
+
+/// Ids of the amenities named like any of `names`, ignoring case and accents.
+fn matching_amenities(catalog: &[Amenity], text_match: TextMatch, names: &[String]) -> Vec<i32> {
+    let fold = |text: &str| utils::strip_accents(&utils::normalize_text(text));
+    let names: Vec<String> = names.iter().map(|name| fold(name)).collect();
+    catalog
+        .iter()
+        .filter(|a| {
+            let amenity = fold(&a.amenity);
+            names.iter().any(|name| match text_match {
+                TextMatch::Equal => amenity == *name,
+                TextMatch::Contains => amenity.contains(name.as_str()),
+            })
+        })
+        .map(|a| a.id)
+        .collect()
+}
+
+/// Ids of the houses with a value of `field` matching `text`. Numbers are
+/// compared as numbers and dates by their stored `aaaa-mm-dd` text.
+fn matching_values(
+    field: &CustomField,
+    custom_match: CustomMatch,
+    text: &str,
+) -> houses_custom_values::BoxedQuery<'static, Sqlite, diesel::sql_types::Integer> {
+    let query = houses_custom_values::table
+        .filter(houses_custom_values::field_id.eq(field.id))
+        .select(houses_custom_values::house_id)
+        .into_boxed();
+    let comparison = match custom_match {
+        CustomMatch::Contains => {
+            let pattern = like_pattern(TextMatch::Contains, text);
+            return query.filter(houses_custom_values::value.like(pattern).escape('\\'));
+        }
+        CustomMatch::Compare(comparison) => comparison,
+    };
+    if field.field_type == CustomFieldType::Text && comparison == Comparison::Equal {
+        let pattern = like_pattern(TextMatch::Equal, text);
+        return query.filter(houses_custom_values::value.like(pattern).escape('\\'));
+    }
+    let Ok(Some(stored)) = field.parse_value(text) else {
+        return query.filter(sql::<Bool>("0"));
+    };
+    match field.field_type {
+        CustomFieldType::Integer | CustomFieldType::Decimal => {
+            let operator = match comparison {
+                Comparison::Equal => "=",
+                Comparison::Less => "<",
+                Comparison::LessOrEqual => "<=",
+                Comparison::Greater => ">",
+                Comparison::GreaterOrEqual => ">=",
+            };
+            let number: f64 = stored.parse().unwrap_or_default();
+            query.filter(
+                sql::<Bool>(&format!(
+                    "CAST(houses_custom_values.value AS REAL) {operator} "
+                ))
+                .bind::<Double, _>(number),
+            )
+        }
+        _ => compare!(query, houses_custom_values::value, comparison, stored),
+    }
+}
+
+#[derive(QueryableByName)]
+struct UserVersion {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    user_version: i32,
+}
+
+#[derive(QueryableByName)]
+struct SearchResult {
+    #[diesel(sql_type = diesel::sql_types::Integer)]
+    house_id: HouseId,
+}
+
+/// Database access. It performs no validation; use
+/// [`HouseService`](crate::service::HouseService) instead.
 pub struct HouseRepository {
     pub conn: SqliteConnection,
 }
 
 impl HouseRepository {
-    pub fn find_all(&mut self) -> Result<Vec<HouseWithKind>, Error> {
-        let houses_and_kinds: Vec<(House, HouseKind)> = houses::table()
-            .inner_join(houses_kind::table())
-            .select((House::as_select(), HouseKind::as_select()))
-            .load::<(House, HouseKind)>(&mut self.conn)?;
+    /// Opens the SQLite database at `database_url`.
+    pub fn establish(database_url: &str) -> ConnectionResult<HouseRepository> {
+        Ok(HouseRepository {
+            conn: SqliteConnection::establish(database_url)?,
+        })
+    }
+
+    /// Starts a transaction, or a savepoint inside one. The outermost one
+    /// takes the write lock right away, so what it reads can't change until
+    /// it commits.
+    pub fn begin(&mut self) -> Result<(), Error> {
+        let status = AnsiTransactionManager::transaction_manager_status_mut(&mut self.conn);
+        if status.transaction_depth()?.is_none() {
+            AnsiTransactionManager::begin_transaction_sql(&mut self.conn, "BEGIN IMMEDIATE")
+        } else {
+            AnsiTransactionManager::begin_transaction(&mut self.conn)
+        }
+    }
+
+    pub fn commit(&mut self) -> Result<(), Error> {
+        AnsiTransactionManager::commit_transaction(&mut self.conn)
+    }
+
+    pub fn rollback(&mut self) -> Result<(), Error> {
+        AnsiTransactionManager::rollback_transaction(&mut self.conn)
+    }
+
+    /// Version of the data fixes applied to the database, kept in SQLite's
+    /// `user_version`.
+    pub fn data_version(&mut self) -> Result<i32, Error> {
+        Ok(diesel::sql_query("PRAGMA user_version")
+            .get_result::<UserVersion>(&mut self.conn)?
+            .user_version)
+    }
+
+    pub fn set_data_version(&mut self, data_version: i32) -> Result<(), Error> {
+        diesel::sql_query(format!("PRAGMA user_version = {data_version}"))
+            .execute(&mut self.conn)?;
+        Ok(())
+    }
+
+    pub fn find_all(&mut self, order: HouseOrder) -> Result<Vec<HouseWithKind>, Error> {
+        self.find_filtered(order, &HouseFilter::default())
+    }
+
+    pub fn find_filtered(
+        &mut self,
+        order: HouseOrder,
+        filter: &HouseFilter,
+    ) -> Result<Vec<HouseWithKind>, Error> {
+        let mut query = self.filtered_houses(filter)?.select((
+            House::as_select(),
+            HouseKind::as_select(),
+            HouseOperation::as_select(),
+            HouseStatus::as_select(),
+        ));
+        query = match order {
+            HouseOrder::Id | HouseOrder::Distance => query.order(id),
+            HouseOrder::RecentlyCreated => query.order((created_at.desc(), id.desc())),
+            HouseOrder::RecentlyUpdated => query.order((updated_at.desc(), id.desc())),
+        };
+        let houses_and_kinds: Vec<(House, HouseKind, HouseOperation, HouseStatus)> =
+            query.load(&mut self.conn)?;
         let mut houses_with_kind: Vec<HouseWithKind> = vec![];
 
-        for (house, kind) in houses_and_kinds {
+        for (house, kind, operation, status) in houses_and_kinds {
             houses_with_kind.push(HouseWithKind {
                 id: house.id,
                 street: house.street,
@@ -31,39 +230,961 @@ impl HouseRepository {
                 rooms: house.rooms,
                 kind_id: kind.id,
                 kind: kind.kind,
+                version: house.version,
+                created_at: house.created_at,
+                updated_at: house.updated_at,
+                street_unit: house.street_unit,
+                city: house.city,
+                province: house.province,
+                country: house.country,
+                price: house.price,
+                currency: house.currency,
+                operation_id: operation.id,
+                status_id: status.id,
+                operation: operation.operation,
+                status: status.status,
+                latitude: house.latitude,
+                longitude: house.longitude,
+                description: house.description,
+                building_id: house.building_id,
             })
         }
         Ok(houses_with_kind)
     }
 
+    /// Number of houses matching `filter` and, unless it is empty, the
+    /// full-text query `search`. Only the coordinates are loaded to check a
+    /// radius; otherwise the database counts them.
+    pub fn count_filtered(&mut self, filter: &HouseFilter, search: &str) -> Result<usize, Error> {
+        let mut query = self.filtered_houses(filter)?;
+        if !search.is_empty() {
+            query = query.filter(
+                sql::<Bool>(
+                    "houses.id IN (SELECT rowid FROM houses_search WHERE houses_search MATCH ",
+                )
+                .bind::<Text, _>(search.to_string())
+                .sql(")"),
+            );
+        }
+        match filter.near {
+            Some(near) => Ok(query
+                .select((latitude, longitude))
+                .load::<(Option<f64>, Option<f64>)>(&mut self.conn)?
+                .into_iter()
+                .filter_map(|(lat, lon)| Coordinates::new(lat?, lon?))
+                .filter(|c| near.contains(c))
+                .count()),
+            None => Ok(query.count().get_result::<i64>(&mut self.conn)? as usize),
+        }
+    }
+
+    /// Houses joined with their lookups and narrowed by `filter`, except for
+    /// the exact distance of `filter.near`, which callers check.
+    fn filtered_houses(&mut self, filter: &HouseFilter) -> Result<FilteredHouses<'static>, Error> {
+        let mut query = houses::table()
+            .inner_join(houses_kind::table())
+            .inner_join(houses_operation::table())
+            .inner_join(houses_status::table())
+            .into_boxed();
+        if let Some(kind) = filter.kind_id {
+            query = query.filter(kind_id.eq(kind));
+        }
+        if let Some(operation) = filter.operation_id {
+            query = query.filter(operation_id.eq(operation));
+        }
+        if let Some(status) = filter.status_id {
+            query = query.filter(status_id.eq(status));
+        }
+        for bounds in [filter.bounds, filter.near.map(|near| near.bounding_box())]
+            .into_iter()
+            .flatten()
+        {
+            query = query.filter(latitude.between(bounds.south, bounds.north));
+            query = if bounds.crosses_antimeridian() {
+                query.filter(longitude.ge(bounds.west).or(longitude.le(bounds.east)))
+            } else {
+                query.filter(longitude.between(bounds.west, bounds.east))
+            };
+        }
+        let catalog = if filter
+            .query
+            .conditions
+            .iter()
+            .any(|c| matches!(c, Condition::Amenities(..)))
+        {
+            self.find_all_amenities()?
+        } else {
+            vec![]
+        };
+        let (fields, field_kinds) = if filter
+            .query
+            .conditions
+            .iter()
+            .any(|c| matches!(c, Condition::Custom(..)))
+        {
+            (self.find_custom_fields()?, self.find_custom_field_kinds()?)
+        } else {
+            (vec![], vec![])
+        };
+        for condition in &filter.query.conditions {
+            query = match condition {
+                Condition::Custom(name, custom_match, value) => {
+                    let fold = |text: &str| utils::strip_accents(&utils::normalize_text(text));
+                    match fields.iter().find(|f| fold(&f.name) == fold(name)) {
+                        Some(field) => {
+                            let kinds: Vec<i32> = field_kinds
+                                .iter()
+                                .filter(|k| k.field_id == field.id)
+                                .map(|k| k.kind_id)
+                                .collect();
+                            query
+                                .filter(kind_id.eq_any(kinds))
+                                .filter(id.eq_any(matching_values(field, *custom_match, value)))
+                        }
+                        None => query.filter(sql::<Bool>("0")),
+                    }
+                }
+                Condition::Amenities(text_match, names) => {
+                    let amenity_ids = matching_amenities(&catalog, *text_match, names);
+                    query.filter(
+                        id.eq_any(
+                            houses_amenities::table
+                                .filter(houses_amenities::amenity_id.eq_any(amenity_ids))
+                                .select(houses_amenities::house_id),
+                        ),
+                    )
+                }
+                Condition::Number(field, comparison, value) => {
+                    let value = *value;
+                    match field {
+                        NumberField::Id => compare!(query, id, comparison, value as i32),
+                        NumberField::StreetNumber => {
+                            compare!(query, street_number, comparison, value as i32)
+                        }
+                        NumberField::SurfaceSquareMeters => {
+                            compare!(query, surface_square_meters, comparison, value as i32)
+                        }
+                        NumberField::Rooms => compare!(query, rooms, comparison, value as i32),
+                        NumberField::Bathrooms => {
+                            compare!(query, bathrooms, comparison, value as i32)
+                        }
+                        NumberField::Price => compare!(query, price, comparison, value),
+                        NumberField::Building => {
+                            compare!(query, building_id, comparison, value as i32)
+                        }
+                    }
+                }
+                Condition::Tags(text_match, names) => {
+                    let mut tagged = houses_tags::table
+                        .inner_join(tags::table)
+                        .select(houses_tags::house_id)
+                        .into_boxed();
+                    for name in names {
+                        let pattern = like_pattern(*text_match, name);
+                        tagged = tagged.or_filter(tags::name.like(pattern).escape('\\'));
+                    }
+                    query.filter(id.eq_any(tagged))
+                }
+                Condition::Text(field, text_match, value) => {
+                    let pattern = like_pattern(*text_match, value);
+                    match field {
+                        TextField::Kind => query
+                            .filter(crate::schema::houses_kind::kind.like(pattern).escape('\\')),
+                        TextField::Street => query.filter(street.like(pattern).escape('\\')),
+                        TextField::Floor => query.filter(street_floor.like(pattern).escape('\\')),
+                        TextField::Unit => query.filter(street_unit.like(pattern).escape('\\')),
+                        TextField::PostalCode => {
+                            query.filter(postal_code.like(pattern).escape('\\'))
+                        }
+                        TextField::City => query.filter(city.like(pattern).escape('\\')),
+                        TextField::Province => query.filter(province.like(pattern).escape('\\')),
+                        TextField::Country => query.filter(country.like(pattern).escape('\\')),
+                        TextField::Currency => query.filter(currency.like(pattern).escape('\\')),
+                        TextField::Operation => query.filter(
+                            crate::schema::houses_operation::operation
+                                .like(pattern)
+                                .escape('\\'),
+                        ),
+                        TextField::Status => query.filter(
+                            crate::schema::houses_status::status
+                                .like(pattern)
+                                .escape('\\'),
+                        ),
+                    }
+                }
+            };
+        }
+        Ok(query)
+    }
+
+    pub fn find(&mut self, uniq_id: HouseId) -> Result<House, Error> {
+        houses.find(uniq_id).first::<House>(&mut self.conn)
+    }
+
+    /// Ids of the houses matching the FTS5 `query`, best ranked first.
+    pub fn search(&mut self, query: &str) -> Result<Vec<HouseId>, Error> {
+        let results = diesel::sql_query(
+            "SELECT rowid AS house_id FROM houses_search WHERE houses_search MATCH ? ORDER BY rank",
+        )
+        .bind::<diesel::sql_types::Text, _>(query)
+        .load::<SearchResult>(&mut self.conn)?;
+        Ok(results.into_iter().map(|result| result.house_id).collect())
+    }
+
+    pub fn find_by_street_number(&mut self, number: i32) -> Result<Vec<House>, Error> {
+        houses
+            .filter(street_number.eq(number))
+            .order(id)
+            .load::<House>(&mut self.conn)
+    }
+
     pub fn find_all_kinds(&mut self) -> Result<Vec<HouseKind>, Error> {
         houses_kind
             .order(houses_kind_id)
             .load::<HouseKind>(&mut self.conn)
     }
 
-    pub fn create(&mut self, new_house: &NewHouse) -> Result<usize, Error> {
-        diesel::insert_into(houses)
-            .values(new_house)
-            .execute(&mut self.conn)
+    pub fn find_all_operations(&mut self) -> Result<Vec<HouseOperation>, Error> {
+        houses_operation
+            .order(houses_operation_id)
+            .load::<HouseOperation>(&mut self.conn)
+    }
+
+    pub fn find_all_statuses(&mut self) -> Result<Vec<HouseStatus>, Error> {
+        houses_status
+            .order(houses_status_id)
+            .load::<HouseStatus>(&mut self.conn)
+    }
+
+    pub fn find_status_history(
+        &mut self,
+        uniq_id: HouseId,
+    ) -> Result<Vec<HouseStatusChange>, Error> {
+        houses_status_history::table
+            .inner_join(houses_status)
+            .filter(houses_status_history::house_id.eq(uniq_id))
+            .select((
+                crate::schema::houses_status::status,
+                houses_status_history::changed_at,
+            ))
+            .order((houses_status_history::changed_at, houses_status_history::id))
+            .load::<HouseStatusChange>(&mut self.conn)
+    }
+
+    pub fn create(&mut self, new_house: &NewHouse) -> Result<HouseId, Error> {
+        let now = Utc::now().naive_utc();
+        self.conn.transaction(|conn| {
+            diesel::insert_into(houses)
+                .values((new_house, created_at.eq(now), updated_at.eq(now)))
+                .execute(conn)?;
+            let house_id = diesel::select(last_insert_rowid()).get_result::<HouseId>(conn)?;
+            insert_status_change(conn, house_id, new_house.status_id)?;
+            Ok(house_id)
+        })
     }
 
     pub fn update(&mut self, house: House) -> Result<usize, Error> {
-        diesel::update(houses.find(house.id))
+        self.conn.transaction(|conn| {
+            let previous_status = houses.find(house.id).select(status_id).first::<i32>(conn)?;
+            let updated = diesel::update(houses.find(house.id).filter(version.eq(house.version)))
+                .set((
+                    id.eq(&house.id),
+                    street.eq(&house.street),
+                    street_number.eq(&house.street_number),
+                    street_floor.eq(&house.street_floor),
+                    street_unit.eq(&house.street_unit),
+                    postal_code.eq(&house.postal_code),
+                    city.eq(&house.city),
+                    province.eq(&house.province),
+                    country.eq(&house.country),
+                    price.eq(&house.price),
+                    currency.eq(&house.currency),
+                    operation_id.eq(&house.operation_id),
+                    status_id.eq(&house.status_id),
+                    latitude.eq(&house.latitude),
+                    longitude.eq(&house.longitude),
+                    surface_square_meters.eq(&house.surface_square_meters),
+                    bathrooms.eq(&house.bathrooms),
+                    rooms.eq(&house.rooms),
+                    kind_id.eq(&house.kind_id),
+                    description.eq(&house.description),
+                    building_id.eq(&house.building_id),
+                    version.eq(house.version + 1),
+                    updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+            if updated > 0 && previous_status != house.status_id {
+                insert_status_change(conn, house.id, house.status_id)?;
+            }
+            Ok(updated)
+        })
+    }
+    /// Replaces the address of the house with `address`, already normalized.
+    /// The contents stay the same, so its version doesn't change.
+    pub fn set_house_address(
+        &mut self,
+        uniq_id: HouseId,
+        address: &Address,
+    ) -> Result<usize, Error> {
+        diesel::update(houses.find(uniq_id))
+            .set((
+                street.eq(&address.street),
+                street_number.eq(address.street_number),
+                street_floor.eq(&address.floor),
+                street_unit.eq(&address.unit),
+                postal_code.eq(&address.postal_code),
+                city.eq(&address.city),
+                province.eq(&address.province),
+                country.eq(&address.country),
+            ))
+            .execute(&mut self.conn)
+    }
+
+    pub fn delete(&mut self, uniq_id: HouseId) -> Result<usize, Error> {
+        self.conn
+            .transaction(|conn| delete_houses(conn, &[uniq_id]))
+    }
+
+    pub fn delete_many(&mut self, ids: &[HouseId]) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            let deleted = delete_houses(conn, ids)?;
+            if deleted != ids.len() {
+                return Err(Error::NotFound);
+            }
+            Ok(deleted)
+        })
+    }
+
+    pub fn update_many(&mut self, ids: &[HouseId], change: &HouseChange) -> Result<usize, Error> {
+        let now = Utc::now().naive_utc();
+        self.conn.transaction(|conn| {
+            let target = houses.filter(id.eq_any(ids));
+            let bump = (version.eq(version + 1), updated_at.eq(now));
+            let updated = match change {
+                HouseChange::Street(value) => diesel::update(target)
+                    .set((street.eq(value), bump))
+                    .execute(conn)?,
+                HouseChange::StreetNumber(value) => diesel::update(target)
+                    .set((street_number.eq(value), bump))
+                    .execute(conn)?,
+                HouseChange::PostalCode(value) => diesel::update(target)
+                    .set((postal_code.eq(value), bump))
+                    .execute(conn)?,
+                HouseChange::SurfaceSquareMeters(value) => diesel::update(target)
+                    .set((surface_square_meters.eq(value), bump))
+                    .execute(conn)?,
+                HouseChange::Bathrooms(value) => diesel::update(target)
+                    .set((bathrooms.eq(value), bump))
+                    .execute(conn)?,
+                HouseChange::Rooms(value) => diesel::update(target)
+                    .set((rooms.eq(value), bump))
+                    .execute(conn)?,
+                HouseChange::Kind(value) if utils::requires_floor(value.get()) => {
+                    diesel::update(target)
+                        .set((kind_id.eq(value), bump))
+                        .execute(conn)?
+                }
+                HouseChange::Kind(value) => diesel::update(target)
+                    .set((
+                        kind_id.eq(value),
+                        street_floor.eq(""),
+                        street_unit.eq(""),
+                        bump,
+                    ))
+                    .execute(conn)?,
+            };
+            if updated != ids.len() {
+                return Err(Error::NotFound);
+            }
+            Ok(updated)
+        })
+    }
+
+    /// Points the house to `building`, or to none. It's derived from the
+    /// address, so the version of the house stays the same.
+    pub fn set_house_building(
+        &mut self,
+        uniq_id: HouseId,
+        building: Option<i32>,
+    ) -> Result<usize, Error> {
+        diesel::update(houses.find(uniq_id))
+            .set(building_id.eq(building))
+            .execute(&mut self.conn)
+    }
+
+    pub fn find_buildings(&mut self) -> Result<Vec<Building>, Error> {
+        buildings::table
+            .order((buildings::street, buildings::street_number, buildings::id))
+            .load::<Building>(&mut self.conn)
+    }
+
+    pub fn find_building(&mut self, building: i32) -> Result<Building, Error> {
+        buildings::table
+            .find(building)
+            .first::<Building>(&mut self.conn)
+    }
+
+    /// Building with the same address as `new_building`.
+    pub fn find_building_by_address(
+        &mut self,
+        new_building: &NewBuilding,
+    ) -> Result<Building, Error> {
+        buildings::table
+            .filter(buildings::street.eq(&new_building.street))
+            .filter(buildings::street_number.eq(new_building.street_number))
+            .filter(buildings::postal_code.eq(&new_building.postal_code))
+            .filter(buildings::city.eq(&new_building.city))
+            .filter(buildings::province.eq(&new_building.province))
+            .filter(buildings::country.eq(&new_building.country))
+            .first::<Building>(&mut self.conn)
+    }
+
+    pub fn create_building(&mut self, new_building: &NewBuilding) -> Result<i32, Error> {
+        self.conn.transaction(|conn| {
+            diesel::insert_into(buildings::table)
+                .values(new_building)
+                .execute(conn)?;
+            diesel::select(last_insert_rowid()).get_result::<i32>(conn)
+        })
+    }
+
+    /// Replaces the values of the building and copies its address to its units.
+    pub fn update_building(&mut self, building: i32, values: &NewBuilding) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            let updated = diesel::update(buildings::table.find(building))
+                .set(values)
+                .execute(conn)?;
+            diesel::update(houses.filter(building_id.eq(building)))
+                .set((
+                    street.eq(&values.street),
+                    street_number.eq(values.street_number),
+                    postal_code.eq(&values.postal_code),
+                    city.eq(&values.city),
+                    province.eq(&values.province),
+                    country.eq(&values.country),
+                    version.eq(version + 1),
+                    updated_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)?;
+            Ok(updated)
+        })
+    }
+
+    pub fn delete_building(&mut self, building: i32) -> Result<usize, Error> {
+        diesel::delete(buildings::table.find(building)).execute(&mut self.conn)
+    }
+
+    /// Deletes the buildings among `ids` that have no units.
+    pub fn delete_empty_buildings(&mut self, ids: &[i32]) -> Result<usize, Error> {
+        delete_empty_buildings(&mut self.conn, ids)
+    }
+
+    pub fn find_all_contacts(&mut self) -> Result<Vec<Contact>, Error> {
+        contacts::table
+            .order((contacts::name, contacts::id))
+            .load::<Contact>(&mut self.conn)
+    }
+
+    pub fn find_contact(&mut self, contact_id: i32) -> Result<Contact, Error> {
+        contacts::table
+            .find(contact_id)
+            .first::<Contact>(&mut self.conn)
+    }
+
+    pub fn create_contact(&mut self, new_contact: &NewContact) -> Result<usize, Error> {
+        diesel::insert_into(contacts::table)
+            .values(new_contact)
+            .execute(&mut self.conn)
+    }
+
+    pub fn update_contact(&mut self, contact: &Contact) -> Result<usize, Error> {
+        diesel::update(contacts::table.find(contact.id))
             .set((
-                id.eq(&house.id),
-                street.eq(&house.street),
-                street_number.eq(&house.street_number),
-                street_floor.eq(&house.street_floor),
-                postal_code.eq(&house.postal_code),
-                surface_square_meters.eq(&house.surface_square_meters),
-                bathrooms.eq(&house.bathrooms),
-                rooms.eq(&house.rooms),
-                kind_id.eq(&house.kind_id),
+                contacts::name.eq(&contact.name),
+                contacts::phone.eq(&contact.phone),
+                contacts::email.eq(&contact.email),
+                contacts::notes.eq(&contact.notes),
+            ))
+            .execute(&mut self.conn)
+    }
+
+    pub fn delete_contact(&mut self, contact_id: i32) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            diesel::delete(
+                houses_contacts::table.filter(houses_contacts::contact_id.eq(contact_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(contacts::table.find(contact_id)).execute(conn)
+        })
+    }
+
+    pub fn find_saved_searches(&mut self) -> Result<Vec<SavedSearch>, Error> {
+        saved_searches::table
+            .order((saved_searches::name, saved_searches::id))
+            .load::<SavedSearch>(&mut self.conn)
+    }
+
+    pub fn find_saved_search(&mut self, search_name: &str) -> Result<SavedSearch, Error> {
+        saved_searches::table
+            .filter(saved_searches::name.eq(search_name))
+            .first::<SavedSearch>(&mut self.conn)
+    }
+
+    /// Inserts `search`, or replaces the saved search with the same name.
+    pub fn save_search(&mut self, search: &NewSavedSearch) -> Result<usize, Error> {
+        diesel::insert_into(saved_searches::table)
+            .values(search)
+            .on_conflict(saved_searches::name)
+            .do_update()
+            .set(search)
+            .execute(&mut self.conn)
+    }
+
+    pub fn delete_saved_search(&mut self, search_id: i32) -> Result<usize, Error> {
+        diesel::delete(saved_searches::table.find(search_id)).execute(&mut self.conn)
+    }
+
+    pub fn find_all_roles(&mut self) -> Result<Vec<ContactRole>, Error> {
+        contacts_role::table
+            .order(contacts_role::id)
+            .load::<ContactRole>(&mut self.conn)
+    }
+
+    pub fn find_house_contacts(&mut self, uniq_id: HouseId) -> Result<Vec<LinkedContact>, Error> {
+        houses_contacts::table
+            .inner_join(contacts::table)
+            .inner_join(contacts_role::table)
+            .filter(houses_contacts::house_id.eq(uniq_id))
+            .select((
+                contacts::id,
+                contacts::name,
+                contacts::phone,
+                contacts::email,
+                contacts_role::id,
+                contacts_role::role,
             ))
+            .order((contacts_role::id, contacts::name))
+            .load::<LinkedContact>(&mut self.conn)
+    }
+
+    pub fn link_contact(&mut self, link: &HouseContact) -> Result<usize, Error> {
+        diesel::insert_or_ignore_into(houses_contacts::table)
+            .values(link)
             .execute(&mut self.conn)
     }
-    pub fn delete(&mut self, uniq_id: i32) -> Result<usize, Error> {
-        diesel::delete(houses.find(uniq_id)).execute(&mut self.conn)
+
+    pub fn unlink_contact(&mut self, link: &HouseContact) -> Result<usize, Error> {
+        diesel::delete(
+            houses_contacts::table
+                .filter(houses_contacts::house_id.eq(link.house_id))
+                .filter(houses_contacts::contact_id.eq(link.contact_id))
+                .filter(houses_contacts::role_id.eq(link.role_id)),
+        )
+        .execute(&mut self.conn)
+    }
+
+    pub fn move_contacts(&mut self, from_ids: &[HouseId], to_id: HouseId) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            let links = houses_contacts::table
+                .filter(houses_contacts::house_id.eq_any(from_ids))
+                .load::<HouseContact>(conn)?;
+            let moved: Vec<HouseContact> = links
+                .into_iter()
+                .map(|link| HouseContact {
+                    house_id: to_id,
+                    ..link
+                })
+                .collect();
+            let inserted = diesel::insert_or_ignore_into(houses_contacts::table)
+                .values(&moved)
+                .execute(conn)?;
+            diesel::delete(
+                houses_contacts::table.filter(houses_contacts::house_id.eq_any(from_ids)),
+            )
+            .execute(conn)?;
+            Ok(inserted)
+        })
+    }
+
+    pub fn find_all_tags(&mut self) -> Result<Vec<Tag>, Error> {
+        tags::table
+            .order((tags::name, tags::id))
+            .load::<Tag>(&mut self.conn)
+    }
+
+    /// Tag with `tag_name`, ignoring case.
+    pub fn find_tag_by_name(&mut self, tag_name: &str) -> Result<Tag, Error> {
+        tags::table
+            .filter(tags::name.eq(tag_name))
+            .first::<Tag>(&mut self.conn)
+    }
+
+    pub fn find_house_tags(&mut self, uniq_id: HouseId) -> Result<Vec<Tag>, Error> {
+        houses_tags::table
+            .inner_join(tags::table)
+            .filter(houses_tags::house_id.eq(uniq_id))
+            .order((tags::name, tags::id))
+            .select(Tag::as_select())
+            .load::<Tag>(&mut self.conn)
+    }
+
+    pub fn create_tag(&mut self, new_tag: &NewTag) -> Result<i32, Error> {
+        self.conn.transaction(|conn| {
+            diesel::insert_into(tags::table)
+                .values(new_tag)
+                .execute(conn)?;
+            diesel::select(last_insert_rowid()).get_result::<i32>(conn)
+        })
+    }
+
+    pub fn rename_tag(&mut self, tag_id: i32, tag_name: &str) -> Result<usize, Error> {
+        diesel::update(tags::table.find(tag_id))
+            .set(tags::name.eq(tag_name))
+            .execute(&mut self.conn)
+    }
+
+    pub fn tag_house(&mut self, link: &HouseTag) -> Result<usize, Error> {
+        diesel::insert_or_ignore_into(houses_tags::table)
+            .values(link)
+            .execute(&mut self.conn)
+    }
+
+    pub fn untag_house(&mut self, link: &HouseTag) -> Result<usize, Error> {
+        diesel::delete(
+            houses_tags::table
+                .filter(houses_tags::house_id.eq(link.house_id))
+                .filter(houses_tags::tag_id.eq(link.tag_id)),
+        )
+        .execute(&mut self.conn)
+    }
+
+    pub fn move_tags(&mut self, from_ids: &[HouseId], to_id: HouseId) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            let links = houses_tags::table
+                .filter(houses_tags::house_id.eq_any(from_ids))
+                .load::<HouseTag>(conn)?;
+            let moved: Vec<HouseTag> = links
+                .into_iter()
+                .map(|link| HouseTag {
+                    house_id: to_id,
+                    ..link
+                })
+                .collect();
+            let inserted = diesel::insert_or_ignore_into(houses_tags::table)
+                .values(&moved)
+                .execute(conn)?;
+            diesel::delete(houses_tags::table.filter(houses_tags::house_id.eq_any(from_ids)))
+                .execute(conn)?;
+            Ok(inserted)
+        })
+    }
+
+    pub fn find_all_amenities(&mut self) -> Result<Vec<Amenity>, Error> {
+        amenities::table
+            .order(amenities::id)
+            .load::<Amenity>(&mut self.conn)
+    }
+
+    pub fn find_house_amenities(&mut self, uniq_id: HouseId) -> Result<Vec<HouseAmenity>, Error> {
+        houses_amenities::table
+            .filter(houses_amenities::house_id.eq(uniq_id))
+            .order(houses_amenities::amenity_id)
+            .load::<HouseAmenity>(&mut self.conn)
+    }
+
+    /// Replaces the amenities of the house with `house_amenities`.
+    pub fn set_house_amenities(
+        &mut self,
+        uniq_id: HouseId,
+        house_amenities: &[HouseAmenity],
+    ) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            diesel::delete(houses_amenities::table.filter(houses_amenities::house_id.eq(uniq_id)))
+                .execute(conn)?;
+            diesel::insert_into(houses_amenities::table)
+                .values(house_amenities)
+                .execute(conn)
+        })
+    }
+
+    /// Moves the amenities the house `to_id` lacks from the `from_ids` ones.
+    pub fn move_amenities(&mut self, from_ids: &[HouseId], to_id: HouseId) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            let moved: Vec<HouseAmenity> = houses_amenities::table
+                .filter(houses_amenities::house_id.eq_any(from_ids))
+                .load::<HouseAmenity>(conn)?
+                .into_iter()
+                .map(|amenity| HouseAmenity {
+                    house_id: to_id,
+                    ..amenity
+                })
+                .collect();
+            let inserted = diesel::insert_or_ignore_into(houses_amenities::table)
+                .values(&moved)
+                .execute(conn)?;
+            diesel::delete(
+                houses_amenities::table.filter(houses_amenities::house_id.eq_any(from_ids)),
+            )
+            .execute(conn)?;
+            Ok(inserted)
+        })
+    }
+
+    pub fn find_custom_fields(&mut self) -> Result<Vec<CustomField>, Error> {
+        custom_fields::table
+            .order(custom_fields::id)
+            .load::<CustomField>(&mut self.conn)
+    }
+
+    pub fn find_custom_field_kinds(&mut self) -> Result<Vec<CustomFieldKind>, Error> {
+        custom_fields_kind::table
+            .order((custom_fields_kind::field_id, custom_fields_kind::kind_id))
+            .load::<CustomFieldKind>(&mut self.conn)
+    }
+
+    /// Custom fields that apply to the kind of property `kind`.
+    pub fn find_kind_custom_fields(&mut self, kind: i32) -> Result<Vec<CustomField>, Error> {
+        custom_fields::table
+            .inner_join(custom_fields_kind::table)
+            .filter(custom_fields_kind::kind_id.eq(kind))
+            .order(custom_fields::id)
+            .select(CustomField::as_select())
+            .load::<CustomField>(&mut self.conn)
+    }
+
+    /// Inserts the field and the kinds it applies to. Returns the new id.
+    pub fn create_custom_field(
+        &mut self,
+        new_field: &NewCustomField,
+        kind_ids: &[i32],
+    ) -> Result<i32, Error> {
+        self.conn.transaction(|conn| {
+            diesel::insert_into(custom_fields::table)
+                .values(new_field)
+                .execute(conn)?;
+            let field_id = diesel::select(last_insert_rowid()).get_result::<i32>(conn)?;
+            let kinds: Vec<CustomFieldKind> = kind_ids
+                .iter()
+                .map(|&kind| CustomFieldKind {
+                    field_id,
+                    kind_id: kind,
+                })
+                .collect();
+            diesel::insert_into(custom_fields_kind::table)
+                .values(&kinds)
+                .execute(conn)?;
+            Ok(field_id)
+        })
+    }
+
+    /// Deletes the field with its values on every house.
+    pub fn delete_custom_field(&mut self, field_id: i32) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            diesel::delete(
+                houses_custom_values::table.filter(houses_custom_values::field_id.eq(field_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(
+                custom_fields_kind::table.filter(custom_fields_kind::field_id.eq(field_id)),
+            )
+            .execute(conn)?;
+            diesel::delete(custom_fields::table.find(field_id)).execute(conn)
+        })
+    }
+
+    pub fn find_custom_values(&mut self, uniq_id: HouseId) -> Result<Vec<CustomValue>, Error> {
+        houses_custom_values::table
+            .filter(houses_custom_values::house_id.eq(uniq_id))
+            .order(houses_custom_values::field_id)
+            .load::<CustomValue>(&mut self.conn)
+    }
+
+    /// Replaces the custom values of the house with `values`.
+    pub fn set_custom_values(
+        &mut self,
+        uniq_id: HouseId,
+        values: &[CustomValue],
+    ) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            diesel::delete(
+                houses_custom_values::table.filter(houses_custom_values::house_id.eq(uniq_id)),
+            )
+            .execute(conn)?;
+            diesel::insert_into(houses_custom_values::table)
+                .values(values)
+                .execute(conn)
+        })
     }
+
+    /// Moves the custom values the house `to_id` lacks from the `from_ids` ones.
+    pub fn move_custom_values(
+        &mut self,
+        from_ids: &[HouseId],
+        to_id: HouseId,
+    ) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            let moved: Vec<CustomValue> = houses_custom_values::table
+                .filter(houses_custom_values::house_id.eq_any(from_ids))
+                .load::<CustomValue>(conn)?
+                .into_iter()
+                .map(|value| CustomValue {
+                    house_id: to_id,
+                    ..value
+                })
+                .collect();
+            let inserted = diesel::insert_or_ignore_into(houses_custom_values::table)
+                .values(&moved)
+                .execute(conn)?;
+            diesel::delete(
+                houses_custom_values::table.filter(houses_custom_values::house_id.eq_any(from_ids)),
+            )
+            .execute(conn)?;
+            Ok(inserted)
+        })
+    }
+
+    pub fn find_attachments(&mut self, uniq_id: HouseId) -> Result<Vec<Attachment>, Error> {
+        houses_attachments::table
+            .filter(houses_attachments::house_id.eq(uniq_id))
+            .order((houses_attachments::position, houses_attachments::id))
+            .load::<Attachment>(&mut self.conn)
+    }
+
+    pub fn add_attachment(&mut self, new_attachment: &NewAttachment) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            let position = next_attachment_position(conn, new_attachment.house_id)?;
+            diesel::insert_into(houses_attachments::table)
+                .values((
+                    new_attachment,
+                    houses_attachments::position.eq(position),
+                    houses_attachments::created_at.eq(Utc::now().naive_utc()),
+                ))
+                .execute(conn)
+        })
+    }
+
+    pub fn reorder_attachments(
+        &mut self,
+        uniq_id: HouseId,
+        attachment_ids: &[i32],
+    ) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            let mut updated = 0;
+            for (position, attachment_id) in attachment_ids.iter().enumerate() {
+                updated += diesel::update(
+                    houses_attachments::table
+                        .find(attachment_id)
+                        .filter(houses_attachments::house_id.eq(uniq_id)),
+                )
+                .set(houses_attachments::position.eq(position as i32))
+                .execute(conn)?;
+            }
+            if updated != attachment_ids.len() {
+                return Err(Error::NotFound);
+            }
+            Ok(updated)
+        })
+    }
+
+    pub fn delete_attachment(&mut self, attachment_id: i32) -> Result<usize, Error> {
+        diesel::delete(houses_attachments::table.find(attachment_id)).execute(&mut self.conn)
+    }
+
+    pub fn move_attachments(
+        &mut self,
+        from_ids: &[HouseId],
+        to_id: HouseId,
+    ) -> Result<usize, Error> {
+        self.conn.transaction(|conn| {
+            let moved = houses_attachments::table
+                .filter(houses_attachments::house_id.eq_any(from_ids))
+                .order((
+                    houses_attachments::house_id,
+                    houses_attachments::position,
+                    houses_attachments::id,
+                ))
+                .select(houses_attachments::id)
+                .load::<i32>(conn)?;
+            let first_position = next_attachment_position(conn, to_id)?;
+            for (offset, attachment_id) in moved.iter().enumerate() {
+                diesel::update(houses_attachments::table.find(attachment_id))
+                    .set((
+                        houses_attachments::house_id.eq(to_id),
+                        houses_attachments::position.eq(first_position + offset as i32),
+                    ))
+                    .execute(conn)?;
+            }
+            Ok(moved.len())
+        })
+    }
+}
+
+/// Deletes the houses `ids` with their rows in other tables and the
+/// buildings they leave without units.
+fn delete_houses(conn: &mut SqliteConnection, ids: &[HouseId]) -> Result<usize, Error> {
+    let left: Vec<i32> = houses
+        .filter(id.eq_any(ids))
+        .select(building_id)
+        .load::<Option<i32>>(conn)?
+        .into_iter()
+        .flatten()
+        .collect();
+    delete_house_rows(conn, ids)?;
+    let deleted = diesel::delete(houses.filter(id.eq_any(ids))).execute(conn)?;
+    delete_empty_buildings(conn, &left)?;
+    Ok(deleted)
+}
+
+/// Deletes the rows of the houses `ids` in every table that refers to them,
+/// before the houses themselves.
+fn delete_house_rows(conn: &mut SqliteConnection, ids: &[HouseId]) -> Result<(), Error> {
+    diesel::delete(houses_contacts::table.filter(houses_contacts::house_id.eq_any(ids)))
+        .execute(conn)?;
+    diesel::delete(houses_tags::table.filter(houses_tags::house_id.eq_any(ids))).execute(conn)?;
+    diesel::delete(houses_amenities::table.filter(houses_amenities::house_id.eq_any(ids)))
+        .execute(conn)?;
+    diesel::delete(houses_custom_values::table.filter(houses_custom_values::house_id.eq_any(ids)))
+        .execute(conn)?;
+    diesel::delete(houses_attachments::table.filter(houses_attachments::house_id.eq_any(ids)))
+        .execute(conn)?;
+    diesel::delete(
+        houses_status_history::table.filter(houses_status_history::house_id.eq_any(ids)),
+    )
+    .execute(conn)?;
+    Ok(())
+}
+
+fn delete_empty_buildings(conn: &mut SqliteConnection, ids: &[i32]) -> Result<usize, Error> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let units = houses.filter(building_id.is_not_null()).select(building_id);
+    diesel::delete(
+        buildings::table
+            .filter(buildings::id.eq_any(ids))
+            .filter(buildings::id.nullable().ne_all(units)),
+    )
+    .execute(conn)
+}
+
+fn insert_status_change(
+    conn: &mut SqliteConnection,
+    changed_house_id: HouseId,
+    new_status_id: i32,
+) -> Result<usize, Error> {
+    diesel::insert_into(houses_status_history::table)
+        .values(&NewHouseStatusChange {
+            house_id: changed_house_id,
+            status_id: new_status_id,
+            changed_at: Utc::now().naive_utc(),
+        })
+        .execute(conn)
+}
+
+fn next_attachment_position(conn: &mut SqliteConnection, uniq_id: HouseId) -> Result<i32, Error> {
+    let last = houses_attachments::table
+        .filter(houses_attachments::house_id.eq(uniq_id))
+        .select(diesel::dsl::max(houses_attachments::position))
+        .first::<Option<i32>>(conn)?;
+    Ok(last.map_or(0, |position| position + 1))
 }
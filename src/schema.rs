@@ -1,5 +1,61 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    amenities (id) {
+        id -> Integer,
+        amenity -> Text,
+        countable -> Bool,
+    }
+}
+
+diesel::table! {
+    buildings (id) {
+        id -> Integer,
+        street -> Text,
+        street_number -> Integer,
+        postal_code -> Text,
+        city -> Text,
+        province -> Text,
+        country -> Text,
+        year_built -> Nullable<Integer>,
+        elevator -> Bool,
+    }
+}
+
+diesel::table! {
+    contacts (id) {
+        id -> Integer,
+        name -> Text,
+        phone -> Text,
+        email -> Text,
+        notes -> Text,
+    }
+}
+
+diesel::table! {
+    contacts_role (id) {
+        id -> Integer,
+        role -> Text,
+    }
+}
+
+diesel::table! {
+    custom_fields (id) {
+        id -> Integer,
+        name -> Text,
+        field_type -> Text,
+        required -> Bool,
+        options -> Text,
+    }
+}
+
+diesel::table! {
+    custom_fields_kind (field_id, kind_id) {
+        field_id -> Integer,
+        kind_id -> Integer,
+    }
+}
+
 diesel::table! {
     houses (id) {
         id -> Integer,
@@ -11,6 +67,58 @@ diesel::table! {
         bathrooms -> Integer,
         rooms -> Integer,
         kind_id -> Integer,
+        version -> Integer,
+        created_at -> Timestamp,
+        updated_at -> Timestamp,
+        street_unit -> Text,
+        city -> Text,
+        province -> Text,
+        country -> Text,
+        price -> Nullable<BigInt>,
+        currency -> Text,
+        operation_id -> Integer,
+        status_id -> Integer,
+        latitude -> Nullable<Double>,
+        longitude -> Nullable<Double>,
+        description -> Text,
+        building_id -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    houses_amenities (house_id, amenity_id) {
+        house_id -> Integer,
+        amenity_id -> Integer,
+        quantity -> Nullable<Integer>,
+    }
+}
+
+diesel::table! {
+    houses_attachments (id) {
+        id -> Integer,
+        house_id -> Integer,
+        file_name -> Text,
+        mime_type -> Text,
+        caption -> Text,
+        position -> Integer,
+        data -> Binary,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    houses_contacts (house_id, contact_id, role_id) {
+        house_id -> Integer,
+        contact_id -> Integer,
+        role_id -> Integer,
+    }
+}
+
+diesel::table! {
+    houses_custom_values (house_id, field_id) {
+        house_id -> Integer,
+        field_id -> Integer,
+        value -> Text,
     }
 }
 
@@ -21,6 +129,94 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    houses_operation (id) {
+        id -> Integer,
+        operation -> Text,
+    }
+}
+
+diesel::table! {
+    houses_status (id) {
+        id -> Integer,
+        status -> Text,
+    }
+}
+
+diesel::table! {
+    houses_status_history (id) {
+        id -> Integer,
+        house_id -> Integer,
+        status_id -> Integer,
+        changed_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    houses_tags (house_id, tag_id) {
+        house_id -> Integer,
+        tag_id -> Integer,
+    }
+}
+
+diesel::table! {
+    saved_searches (id) {
+        id -> Integer,
+        name -> Text,
+        query -> Text,
+        search -> Text,
+        operation_id -> Nullable<Integer>,
+        status_id -> Nullable<Integer>,
+        near -> Text,
+        sort_order -> Text,
+    }
+}
+
+diesel::table! {
+    tags (id) {
+        id -> Integer,
+        name -> Text,
+    }
+}
+
+diesel::joinable!(custom_fields_kind -> custom_fields (field_id));
+diesel::joinable!(custom_fields_kind -> houses_kind (kind_id));
+diesel::joinable!(houses -> buildings (building_id));
 diesel::joinable!(houses -> houses_kind (kind_id));
+diesel::joinable!(houses -> houses_operation (operation_id));
+diesel::joinable!(houses -> houses_status (status_id));
+diesel::joinable!(houses_amenities -> amenities (amenity_id));
+diesel::joinable!(houses_amenities -> houses (house_id));
+diesel::joinable!(houses_attachments -> houses (house_id));
+diesel::joinable!(houses_contacts -> contacts (contact_id));
+diesel::joinable!(houses_contacts -> contacts_role (role_id));
+diesel::joinable!(houses_contacts -> houses (house_id));
+diesel::joinable!(houses_custom_values -> custom_fields (field_id));
+diesel::joinable!(houses_custom_values -> houses (house_id));
+diesel::joinable!(houses_status_history -> houses (house_id));
+diesel::joinable!(houses_status_history -> houses_status (status_id));
+diesel::joinable!(houses_tags -> houses (house_id));
+diesel::joinable!(houses_tags -> tags (tag_id));
+diesel::joinable!(saved_searches -> houses_operation (operation_id));
+diesel::joinable!(saved_searches -> houses_status (status_id));
 
-diesel::allow_tables_to_appear_in_same_query!(houses, houses_kind,);
+diesel::allow_tables_to_appear_in_same_query!(
+    amenities,
+    buildings,
+    contacts,
+    contacts_role,
+    custom_fields,
+    custom_fields_kind,
+    houses,
+    houses_amenities,
+    houses_attachments,
+    houses_contacts,
+    houses_custom_values,
+    houses_kind,
+    houses_operation,
+    houses_status,
+    houses_status_history,
+    houses_tags,
+    saved_searches,
+    tags,
+);
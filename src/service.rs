@@ -1,117 +1,1512 @@
-use crate::models::{House, HouseKind, HouseWithKind, NewHouse};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use chrono::{Datelike, Utc};
+use diesel::result::Error;
+
+use crate::address::Address;
+use crate::draft::HouseDraft;
+use crate::geo::{self, Coordinates, Radius};
+use crate::models::{
+    Amenity, Attachment, Building, Contact, ContactRole, CustomField, CustomFieldKind,
+    CustomFieldType, CustomValue, House, HouseAmenity, HouseChange, HouseContact, HouseField,
+    HouseFilter, HouseKind, HouseOperation, HouseOrder, HouseStatus, HouseStatusChange, HouseTag,
+    HouseWithKind, LinkedContact, NewAttachment, NewBuilding, NewContact, NewCustomField, NewHouse,
+    NewSavedSearch, NewTag, SavedSearch, Tag,
+};
+use crate::query::{Comparison, Condition, NumberField, ParseError, Query};
+use crate::report::Report;
 use crate::repository::HouseRepository;
-use crate::utils::{self, APARTMENT};
+use crate::sheet::ListingSheet;
+use crate::types::{HouseId, KindId, PostalCode};
+use crate::utils;
+
+/// Why a service operation failed. `Display` gives the message shown to users.
+#[derive(Debug, PartialEq)]
+pub enum ServiceError {
+    Validation(&'static str),
+    NotFound,
+    Conflict,
+    Duplicate(HouseId),
+    Query(ParseError),
+    /// What has more characters than the given maximum.
+    TooLong(&'static str, usize),
+    /// Invalid value for the custom field with the given name.
+    CustomField(String, &'static str),
+    Database,
+}
+
+/// What to do when a new house has the same address as an existing one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicatePolicy {
+    Warn,
+    Refuse,
+}
+
+/// Text of each field of the house form, as typed. Lookups are chosen from
+/// lists, so they're already ids.
+#[derive(Debug, Clone, Copy)]
+pub struct HouseForm<'a> {
+    pub street: &'a str,
+    pub street_number: &'a str,
+    pub street_floor: &'a str,
+    pub street_unit: &'a str,
+    pub postal_code: &'a str,
+    pub city: &'a str,
+    pub province: &'a str,
+    pub country: &'a str,
+    pub latitude: &'a str,
+    pub longitude: &'a str,
+    pub surface_square_meters: &'a str,
+    pub bathrooms: &'a str,
+    pub rooms: &'a str,
+    pub kind_id: i32,
+    pub price: &'a str,
+    pub currency: &'a str,
+    pub operation_id: i32,
+    pub status_id: i32,
+    pub description: &'a str,
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ServiceError::Validation(message) => write!(f, "{message}"),
+            ServiceError::NotFound => write!(f, "Elemento no encontrado"),
+            ServiceError::Conflict => write!(f, "El elemento fue modificado por otro usuario"),
+            ServiceError::Duplicate(id) => {
+                write!(f, "Ya existe el elemento #{id} en la misma dirección")
+            }
+            ServiceError::Query(error) => write!(f, "Filtro inválido: {error}"),
+            ServiceError::TooLong(what, max) => {
+                write!(f, "{what} no puede tener más de {max} caracteres")
+            }
+            ServiceError::CustomField(name, message) => write!(f, "{name}: {message}"),
+            ServiceError::Database => write!(f, "Error guardando en la DB"),
+        }
+    }
+}
+
+impl From<Error> for ServiceError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::NotFound => ServiceError::NotFound,
+            _ => ServiceError::Database,
+        }
+    }
+}
+
+/// Version of the fixes [`HouseService::upgrade_data`] applies to rows saved
+/// under older rules.
+const DATA_VERSION: i32 = 2;
+
+/// Rolls back the transaction opened by [`HouseService::transaction`] unless
+/// it was committed, also when the operation panics or the commit fails.
+struct TransactionGuard<'a> {
+    service: &'a mut HouseService,
+    committed: bool,
+}
 
+impl Drop for TransactionGuard<'_> {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = self.service.repository.rollback();
+        }
+    }
+}
+
+/// Validates input and applies the business rules before reaching the
+/// repository. Every front end goes through it.
 pub struct HouseService {
     pub repository: HouseRepository,
+    pub duplicate_policy: DuplicatePolicy,
 }
 
 impl HouseService {
     pub fn new(house_repository: HouseRepository) -> Self {
         HouseService {
             repository: house_repository,
+            duplicate_policy: DuplicatePolicy::Warn,
         }
     }
-    pub fn create_house(
-        &mut self,
-        street: &str,
-        street_number: &str,
-        street_floor: &str,
-        postal_code: &str,
-        surface_square_meters: &str,
-        bathrooms: &str,
-        rooms: &str,
-        kind_id: i32,
-    ) -> Result<usize, &str> {
-        let street_number = street_number
-            .parse::<i32>()
-            .map_err(|_| "Error convritiendo el número de la calle")?;
-        let surface_square_meters = surface_square_meters
-            .parse::<i32>()
-            .map_err(|_| "Error convritiendo la superficie")?;
-        let bathrooms = bathrooms
-            .parse::<i32>()
-            .map_err(|_| "Error convritiendo los baños")?;
-        let rooms = rooms
-            .parse::<i32>()
-            .map_err(|_| "Error convritiendo las habitaciones")?;
-        let street_floor = if kind_id == APARTMENT {
-            street_floor
-        } else {
-            ""
+    /// Runs `operation` in a database transaction, rolled back when it fails
+    /// or panics.
+    pub fn transaction<T, F>(&mut self, operation: F) -> Result<T, ServiceError>
+    where
+        F: FnOnce(&mut HouseService) -> Result<T, ServiceError>,
+    {
+        self.repository.begin()?;
+        let mut guard = TransactionGuard {
+            service: self,
+            committed: false,
         };
-        if kind_id < 0 || kind_id > 2 {
-            return Err("Error convirtiendo el tipo de casa");
+        let value = operation(guard.service)?;
+        guard.service.repository.commit()?;
+        guard.committed = true;
+        Ok(value)
+    }
+
+    /// Brings the rows saved before the current rules up to date, once per
+    /// database: normalizes the addresses stored before they were
+    /// normalized on save, then groups the apartments again by those
+    /// addresses.
+    pub fn upgrade_data(&mut self) -> Result<(), ServiceError> {
+        let data_version = self.repository.data_version()?;
+        if data_version >= DATA_VERSION {
+            return Ok(());
         }
-        let new_house = NewHouse {
-            street: street.to_string(),
-            street_number,
-            street_floor: street_floor.to_string(),
-            postal_code: postal_code.to_string(),
-            surface_square_meters,
-            bathrooms,
-            rooms,
-            kind_id,
-        };
-        self.repository
-            .create(&new_house)
-            .map_err(|_| "Error guardando en la DB")
+        self.transaction(|service| {
+            if data_version < 1 {
+                service.normalize_addresses()?;
+            }
+            if data_version < 2 {
+                service.regroup_buildings()?;
+            }
+            service.repository.set_data_version(DATA_VERSION)?;
+            Ok(())
+        })
+    }
+
+    /// Validates `draft` and inserts it. Returns the id of the new house.
+    pub fn create_from_draft(&mut self, draft: &HouseDraft) -> Result<HouseId, ServiceError> {
+        let new_house = draft.build()?;
+        self.validate_kind(new_house.kind_id.get())?;
+        self.validate_listing(Some(new_house.operation_id), Some(new_house.status_id))?;
+        self.transaction(|service| {
+            if service.duplicate_policy == DuplicatePolicy::Refuse {
+                if let Some(duplicate) = service.find_duplicate(&new_house.address())? {
+                    return Err(ServiceError::Duplicate(duplicate.id));
+                }
+            }
+            let new_house = NewHouse {
+                building_id: service.building_for(new_house.kind_id, &new_house.address())?,
+                ..new_house
+            };
+            let id = service
+                .repository
+                .create(&new_house)
+                .map_err(|_| ServiceError::Database)?;
+            if let Some(amenities) = &draft.amenities {
+                service.write_amenities(id, amenities)?;
+            }
+            let values = draft.custom_values.clone().unwrap_or_default();
+            service.write_custom_values(id, new_house.kind_id.get(), &values)?;
+            Ok(id)
+        })
+    }
+
+    /// Creates a house from the text of each field, as typed in a form.
+    /// See [`create_from_draft`](HouseService::create_from_draft).
+    pub fn create_house(&mut self, form: &HouseForm) -> Result<HouseId, ServiceError> {
+        self.create_from_draft(&form.draft()?)
+    }
+
+    pub fn find_duplicate(&mut self, address: &Address) -> Result<Option<House>, ServiceError> {
+        let key = address.key();
+        Ok(self
+            .repository
+            .find_by_street_number(address.street_number)?
+            .into_iter()
+            .find(|h| h.address().key() == key))
+    }
+
+    pub fn find_duplicates(&mut self) -> Result<Vec<Vec<HouseWithKind>>, ServiceError> {
+        let mut groups: BTreeMap<String, Vec<HouseWithKind>> = BTreeMap::new();
+        for house in self.repository.find_all(HouseOrder::Id)? {
+            let key = house.address().key();
+            groups.entry(key).or_default().push(house);
+        }
+        Ok(groups.into_values().filter(|g| g.len() > 1).collect())
+    }
+
+    pub fn merge_houses(
+        &mut self,
+        keep_id: HouseId,
+        duplicate_ids: &[HouseId],
+    ) -> Result<usize, ServiceError> {
+        let duplicate_ids: Vec<HouseId> = unique_ids(duplicate_ids)
+            .into_iter()
+            .filter(|id| *id != keep_id)
+            .collect();
+        self.transaction(|service| {
+            service.repository.find(keep_id)?;
+            service.repository.move_contacts(&duplicate_ids, keep_id)?;
+            service.repository.move_tags(&duplicate_ids, keep_id)?;
+            service.repository.move_amenities(&duplicate_ids, keep_id)?;
+            service
+                .repository
+                .move_custom_values(&duplicate_ids, keep_id)?;
+            service
+                .repository
+                .move_attachments(&duplicate_ids, keep_id)?;
+            service.delete_houses(&duplicate_ids)
+        })
     }
 
     pub fn get_houses(&mut self) -> Result<Vec<HouseWithKind>, diesel::result::Error> {
-        self.repository.find_all()
+        self.repository.find_all(HouseOrder::Id)
+    }
+    /// Houses matching `filter`, in the given order. The radius in
+    /// [`HouseFilter::near`] is measured exactly, not only by its bounding box.
+    pub fn get_houses_filtered(
+        &mut self,
+        order: HouseOrder,
+        filter: &HouseFilter,
+    ) -> Result<Vec<HouseWithKind>, diesel::result::Error> {
+        let mut houses = self.repository.find_filtered(order, filter)?;
+        if let Some(near) = filter.near {
+            houses.retain(|h| h.coordinates().is_some_and(|c| near.contains(&c)));
+        }
+        if order == HouseOrder::Distance {
+            let origin = filter
+                .near
+                .map(|near| near.center)
+                .or(filter.bounds.map(|bounds| bounds.center()));
+            if let Some(origin) = origin {
+                sort_by_distance(&mut houses, &origin);
+            }
+        }
+        Ok(houses)
+    }
+
+    /// Houses matching `filter` with every word of `text` at the start of a
+    /// word of their address, kind, photo captions or description, best
+    /// matches first.
+    /// Accents and case are ignored.
+    pub fn search_houses(
+        &mut self,
+        text: &str,
+        filter: &HouseFilter,
+    ) -> Result<Vec<HouseWithKind>, ServiceError> {
+        let query = search_query(text);
+        if query.is_empty() {
+            return Ok(self.get_houses_filtered(HouseOrder::Id, filter)?);
+        }
+        let ranks: HashMap<HouseId, usize> = self
+            .repository
+            .search(&query)?
+            .into_iter()
+            .enumerate()
+            .map(|(rank, id)| (id, rank))
+            .collect();
+        let mut houses = self.get_houses_filtered(HouseOrder::Id, filter)?;
+        houses.retain(|h| ranks.contains_key(&h.id));
+        houses.sort_by_key(|h| ranks[&h.id]);
+        Ok(houses)
+    }
+
+    pub fn find_houses_within(
+        &mut self,
+        center: Coordinates,
+        km: f64,
+    ) -> Result<Vec<(HouseWithKind, f64)>, ServiceError> {
+        if km.is_nan() || km <= 0.0 {
+            return Err(ServiceError::Validation("El radio debe ser mayor a cero"));
+        }
+        let filter = HouseFilter {
+            near: Some(Radius { center, km }),
+            ..HouseFilter::default()
+        };
+        Ok(self
+            .get_houses_filtered(HouseOrder::Distance, &filter)?
+            .into_iter()
+            .filter_map(|h| {
+                let distance = center.distance_km(&h.coordinates()?);
+                Some((h, distance))
+            })
+            .collect())
+    }
+
+    pub fn get_report(&mut self, filter: &HouseFilter) -> Result<Report, ServiceError> {
+        let houses = self.get_houses_filtered(HouseOrder::Id, filter)?;
+        Ok(Report::build(&houses))
+    }
+
+    pub fn get_saved_searches(&mut self) -> Result<Vec<SavedSearch>, diesel::result::Error> {
+        self.repository.find_saved_searches()
+    }
+
+    /// Saved searches with the number of houses each one finds now, counted
+    /// without loading the houses.
+    pub fn get_saved_search_counts(&mut self) -> Result<Vec<(SavedSearch, usize)>, ServiceError> {
+        self.get_saved_searches()?
+            .into_iter()
+            .map(|search| {
+                let filter = saved_search_filter(
+                    search.operation_id,
+                    search.status_id,
+                    &search.near,
+                    &search.query,
+                )?;
+                let count = self.count_houses(&search.search, &filter)?;
+                Ok((search, count))
+            })
+            .collect()
+    }
+
+    /// Number of houses [`search_houses`](HouseService::search_houses) finds.
+    pub fn count_houses(
+        &mut self,
+        text: &str,
+        filter: &HouseFilter,
+    ) -> Result<usize, ServiceError> {
+        Ok(self
+            .repository
+            .count_filtered(filter, &search_query(text))?)
+    }
+
+    pub fn find_saved_search(&mut self, name: &str) -> Result<SavedSearch, ServiceError> {
+        Ok(self.repository.find_saved_search(name.trim())?)
+    }
+
+    /// Saves `search`, replacing the saved search with the same name.
+    pub fn save_search(&mut self, search: &NewSavedSearch) -> Result<usize, ServiceError> {
+        let name = search.name.trim();
+        if name.is_empty() {
+            return Err(ServiceError::Validation("La búsqueda debe tener un nombre"));
+        }
+        saved_search_filter(
+            search.operation_id,
+            search.status_id,
+            &search.near,
+            &search.query,
+        )?;
+        self.validate_listing(search.operation_id, search.status_id)?;
+        Ok(self.repository.save_search(&NewSavedSearch {
+            name: name.to_string(),
+            ..search.clone()
+        })?)
+    }
+
+    pub fn delete_saved_search(&mut self, id: i32) -> Result<usize, ServiceError> {
+        Ok(self.repository.delete_saved_search(id)?)
+    }
+
+    /// Houses found by `search`, best matches first when it has full-text
+    /// words and otherwise in its saved order.
+    pub fn run_saved_search(
+        &mut self,
+        search: &SavedSearch,
+    ) -> Result<Vec<HouseWithKind>, ServiceError> {
+        let filter = saved_search_filter(
+            search.operation_id,
+            search.status_id,
+            &search.near,
+            &search.query,
+        )?;
+        if search.search.trim().is_empty() {
+            Ok(self.get_houses_filtered(search.sort_order, &filter)?)
+        } else {
+            self.search_houses(&search.search, &filter)
+        }
+    }
+    /// Gathers what's printed on the listing sheet of each house, in the
+    /// given order.
+    pub fn get_listing_sheets(
+        &mut self,
+        ids: &[HouseId],
+    ) -> Result<Vec<ListingSheet>, ServiceError> {
+        let houses = self.repository.find_all(HouseOrder::Id)?;
+        ids.iter()
+            .map(|id| {
+                let house = houses
+                    .iter()
+                    .find(|h| h.id == *id)
+                    .cloned()
+                    .ok_or(ServiceError::NotFound)?;
+                Ok(ListingSheet {
+                    attachments: self.repository.find_attachments(house.id)?,
+                    contacts: self.repository.find_house_contacts(house.id)?,
+                    custom_fields: self.get_custom_field_values(&house)?,
+                    building: match house.building_id {
+                        Some(building_id) => Some(self.repository.find_building(building_id)?),
+                        None => None,
+                    },
+                    house,
+                })
+            })
+            .collect()
+    }
+    pub fn get_house(&mut self, id: HouseId) -> Result<House, ServiceError> {
+        Ok(self.repository.find(id)?)
+    }
+    pub fn get_house_with_kind(&mut self, id: HouseId) -> Result<HouseWithKind, ServiceError> {
+        self.repository
+            .find_all(HouseOrder::Id)?
+            .into_iter()
+            .find(|h| h.id == id)
+            .ok_or(ServiceError::NotFound)
     }
     pub fn get_houses_kind(&mut self) -> Result<Vec<HouseKind>, diesel::result::Error> {
         self.repository.find_all_kinds()
     }
-    pub fn update_house(
+    pub fn get_operations(&mut self) -> Result<Vec<HouseOperation>, diesel::result::Error> {
+        self.repository.find_all_operations()
+    }
+    pub fn get_statuses(&mut self) -> Result<Vec<HouseStatus>, diesel::result::Error> {
+        self.repository.find_all_statuses()
+    }
+    pub fn get_status_history(
         &mut self,
-        id: &str,
-        street: &str,
-        street_number: &str,
-        street_floor: &str,
-        postal_code: &str,
-        surface_square_meters: &str,
-        bathrooms: &str,
-        rooms: &str,
-        kind_id: i32,
-    ) -> Result<usize, &str> {
-        let id = id.parse::<i32>().map_err(|_| "Error convritiendo el id")?;
-        let street_number = street_number
-            .parse::<i32>()
-            .map_err(|_| "Error convritiendo el número de la calle")?;
-        let surface_square_meters = surface_square_meters
-            .parse::<i32>()
-            .map_err(|_| "Error convritiendo la superficie")?;
-        let bathrooms = bathrooms
-            .parse::<i32>()
-            .map_err(|_| "Error convritiendo los baños")?;
-        let rooms = rooms
-            .parse::<i32>()
-            .map_err(|_| "Error convritiendo las habitaciones")?;
-        let street_floor = if utils::requires_floor(kind_id) {
-            street_floor
-        } else {
-            ""
-        };
+        id: HouseId,
+    ) -> Result<Vec<HouseStatusChange>, diesel::result::Error> {
+        self.repository.find_status_history(id)
+    }
+    /// Replaces the values of house `id` with `draft`. Fails with
+    /// [`ServiceError::Conflict`] when `version` is no longer the current one.
+    pub fn update_from_draft(
+        &mut self,
+        id: HouseId,
+        version: i32,
+        draft: &HouseDraft,
+    ) -> Result<usize, ServiceError> {
+        let NewHouse {
+            street,
+            street_number,
+            street_floor,
+            postal_code,
+            surface_square_meters,
+            bathrooms,
+            rooms,
+            kind_id,
+            street_unit,
+            city,
+            province,
+            country,
+            price,
+            currency,
+            operation_id,
+            status_id,
+            latitude,
+            longitude,
+            description,
+            building_id: _,
+        } = draft.build()?;
+        self.validate_kind(kind_id.get())?;
+        self.validate_listing(Some(operation_id), Some(status_id))?;
 
-        let house = House {
+        let current = self.repository.find(id)?;
+        let previous_building = current.building_id;
+        let mut house = House {
             id,
-            street: street.to_string(),
+            street,
             street_number,
-            street_floor: street_floor.to_string(),
-            postal_code: postal_code.to_string(),
+            street_floor,
+            postal_code,
             surface_square_meters,
             bathrooms,
             rooms,
             kind_id,
+            version,
+            street_unit,
+            city,
+            province,
+            country,
+            price,
+            currency,
+            operation_id,
+            status_id,
+            latitude,
+            longitude,
+            description,
+            ..current
         };
-        self.repository
-            .update(house)
-            .map_err(|_| "Error guardando en la DB")
+        self.transaction(|service| {
+            house.building_id = service.building_for(house.kind_id, &house.address())?;
+            let updated = match service.repository.update(house) {
+                Ok(0) => return Err(ServiceError::Conflict),
+                Ok(updated) => updated,
+                Err(_) => return Err(ServiceError::Database),
+            };
+            let left: Vec<i32> = previous_building.into_iter().collect();
+            service.repository.delete_empty_buildings(&left)?;
+            if let Some(amenities) = &draft.amenities {
+                service.write_amenities(id, amenities)?;
+            }
+            let values = match &draft.custom_values {
+                Some(values) => values.clone(),
+                None => service.stored_custom_values(id)?,
+            };
+            service.write_custom_values(id, kind_id.get(), &values)?;
+            Ok(updated)
+        })
     }
 
-    pub fn delete_house(&mut self, id: i32) -> Result<usize, diesel::result::Error> {
+    /// Updates a house from the text of each field.
+    /// See [`update_from_draft`](HouseService::update_from_draft).
+    pub fn update_house(
+        &mut self,
+        id: &str,
+        form: &HouseForm,
+        version: i32,
+    ) -> Result<usize, ServiceError> {
+        let id = id
+            .parse::<HouseId>()
+            .map_err(|_| ServiceError::Validation("Error convritiendo el id"))?;
+        self.update_from_draft(id, version, &form.draft()?)
+    }
+
+    pub fn delete_house(&mut self, id: HouseId) -> Result<usize, diesel::result::Error> {
         self.repository.delete(id)
     }
+
+    pub fn delete_houses(&mut self, ids: &[HouseId]) -> Result<usize, ServiceError> {
+        let ids = unique_ids(ids);
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        Ok(self.repository.delete_many(&ids)?)
+    }
+
+    pub fn change_kind(&mut self, ids: &[HouseId], kind_id: i32) -> Result<usize, ServiceError> {
+        let kind_id = self.validate_kind(kind_id)?;
+        self.update_many(ids, HouseChange::Kind(kind_id))
+    }
+
+    pub fn set_field(
+        &mut self,
+        ids: &[HouseId],
+        field: HouseField,
+        value: &str,
+    ) -> Result<usize, ServiceError> {
+        let change = match field {
+            HouseField::Street => HouseChange::Street(
+                Address {
+                    street: value.to_string(),
+                    ..Address::default()
+                }
+                .normalized()
+                .street,
+            ),
+            HouseField::StreetNumber => HouseChange::StreetNumber(parse_number(
+                value,
+                "Error convritiendo el número de la calle",
+            )?),
+            HouseField::PostalCode => {
+                HouseChange::PostalCode(PostalCode::new(value).map_err(ServiceError::Validation)?)
+            }
+            HouseField::SurfaceSquareMeters => {
+                HouseChange::SurfaceSquareMeters(parse(value, "Error convritiendo la superficie")?)
+            }
+            HouseField::Bathrooms => {
+                HouseChange::Bathrooms(parse(value, "Error convritiendo los baños")?)
+            }
+            HouseField::Rooms => {
+                HouseChange::Rooms(parse(value, "Error convritiendo las habitaciones")?)
+            }
+        };
+        self.update_many(ids, change)
+    }
+
+    pub fn get_buildings(&mut self) -> Result<Vec<Building>, diesel::result::Error> {
+        self.repository.find_buildings()
+    }
+
+    pub fn get_building(&mut self, id: i32) -> Result<Building, ServiceError> {
+        Ok(self.repository.find_building(id)?)
+    }
+
+    /// Apartments of the building, by floor and unit.
+    pub fn get_building_units(&mut self, id: i32) -> Result<Vec<HouseWithKind>, ServiceError> {
+        let filter = HouseFilter {
+            query: Query {
+                conditions: vec![Condition::Number(
+                    NumberField::Building,
+                    Comparison::Equal,
+                    id.into(),
+                )],
+            },
+            ..HouseFilter::default()
+        };
+        let mut units = self.get_houses_filtered(HouseOrder::Id, &filter)?;
+        units.sort_by(|a, b| {
+            (a.street_floor.to_string(), &a.street_unit)
+                .cmp(&(b.street_floor.to_string(), &b.street_unit))
+        });
+        Ok(units)
+    }
+
+    /// Creates a building at `address`, ignoring floor and unit. Its units
+    /// are the apartments with the same address.
+    pub fn create_building(
+        &mut self,
+        address: &Address,
+        year_built: &str,
+        elevator: bool,
+    ) -> Result<i32, ServiceError> {
+        let new_building = parse_building(address, year_built, elevator)?;
+        match self.repository.find_building_by_address(&new_building) {
+            Ok(_) => {
+                return Err(ServiceError::Validation(
+                    "Ya existe un edificio en esa dirección",
+                ))
+            }
+            Err(Error::NotFound) => {}
+            Err(error) => return Err(error.into()),
+        }
+        Ok(self.repository.create_building(&new_building)?)
+    }
+
+    /// Updates the building; a new address is copied to all of its units.
+    pub fn update_building(
+        &mut self,
+        id: i32,
+        address: &Address,
+        year_built: &str,
+        elevator: bool,
+    ) -> Result<usize, ServiceError> {
+        let values = parse_building(address, year_built, elevator)?;
+        match self.repository.find_building_by_address(&values) {
+            Ok(building) if building.id != id => {
+                return Err(ServiceError::Validation(
+                    "Ya existe un edificio en esa dirección",
+                ))
+            }
+            Ok(_) | Err(Error::NotFound) => {}
+            Err(error) => return Err(error.into()),
+        }
+        match self.repository.update_building(id, &values)? {
+            0 => Err(ServiceError::NotFound),
+            updated => Ok(updated),
+        }
+    }
+
+    /// Deletes a building without units.
+    pub fn delete_building(&mut self, id: i32) -> Result<usize, ServiceError> {
+        if !self.get_building_units(id)?.is_empty() {
+            return Err(ServiceError::Validation(
+                "No se puede borrar un edificio con unidades",
+            ));
+        }
+        match self.repository.delete_building(id)? {
+            0 => Err(ServiceError::NotFound),
+            deleted => Ok(deleted),
+        }
+    }
+
+    pub fn get_contacts(&mut self) -> Result<Vec<Contact>, diesel::result::Error> {
+        self.repository.find_all_contacts()
+    }
+    pub fn get_contact_roles(&mut self) -> Result<Vec<ContactRole>, diesel::result::Error> {
+        self.repository.find_all_roles()
+    }
+    pub fn get_house_contacts(
+        &mut self,
+        house_id: HouseId,
+    ) -> Result<Vec<LinkedContact>, diesel::result::Error> {
+        self.repository.find_house_contacts(house_id)
+    }
+
+    pub fn create_contact(
+        &mut self,
+        name: &str,
+        phone: &str,
+        email: &str,
+        notes: &str,
+    ) -> Result<usize, ServiceError> {
+        let new_contact = validate_contact(name, phone, email, notes)?;
+        Ok(self.repository.create_contact(&new_contact)?)
+    }
+
+    pub fn update_contact(
+        &mut self,
+        id: i32,
+        name: &str,
+        phone: &str,
+        email: &str,
+        notes: &str,
+    ) -> Result<usize, ServiceError> {
+        let NewContact {
+            name,
+            phone,
+            email,
+            notes,
+        } = validate_contact(name, phone, email, notes)?;
+        let contact = Contact {
+            id,
+            name,
+            phone,
+            email,
+            notes,
+        };
+        match self.repository.update_contact(&contact)? {
+            0 => Err(ServiceError::NotFound),
+            updated => Ok(updated),
+        }
+    }
+
+    pub fn delete_contact(&mut self, id: i32) -> Result<usize, ServiceError> {
+        Ok(self.repository.delete_contact(id)?)
+    }
+
+    pub fn link_contact(
+        &mut self,
+        house_id: HouseId,
+        contact_id: i32,
+        role_id: i32,
+    ) -> Result<usize, ServiceError> {
+        self.repository.find(house_id)?;
+        self.repository.find_contact(contact_id)?;
+        if !self
+            .repository
+            .find_all_roles()?
+            .iter()
+            .any(|r| r.id == role_id)
+        {
+            return Err(ServiceError::Validation("Error convirtiendo el rol"));
+        }
+        Ok(self.repository.link_contact(&HouseContact {
+            house_id,
+            contact_id,
+            role_id,
+        })?)
+    }
+
+    pub fn unlink_contact(
+        &mut self,
+        house_id: HouseId,
+        contact_id: i32,
+        role_id: i32,
+    ) -> Result<usize, ServiceError> {
+        Ok(self.repository.unlink_contact(&HouseContact {
+            house_id,
+            contact_id,
+            role_id,
+        })?)
+    }
+
+    pub fn get_tags(&mut self) -> Result<Vec<Tag>, diesel::result::Error> {
+        self.repository.find_all_tags()
+    }
+
+    pub fn get_house_tags(&mut self, house_id: HouseId) -> Result<Vec<Tag>, diesel::result::Error> {
+        self.repository.find_house_tags(house_id)
+    }
+
+    /// Tags the house with `name`, creating the tag when no other one has
+    /// that name in any case.
+    pub fn tag_house(&mut self, house_id: HouseId, name: &str) -> Result<usize, ServiceError> {
+        let name = validate_tag(name)?;
+        self.transaction(|service| {
+            service.repository.find(house_id)?;
+            let tag_id = match service.repository.find_tag_by_name(&name) {
+                Ok(tag) => tag.id,
+                Err(Error::NotFound) => service.repository.create_tag(&NewTag { name })?,
+                Err(error) => return Err(error.into()),
+            };
+            Ok(service
+                .repository
+                .tag_house(&HouseTag { house_id, tag_id })?)
+        })
+    }
+
+    pub fn untag_house(&mut self, house_id: HouseId, tag_id: i32) -> Result<usize, ServiceError> {
+        Ok(self
+            .repository
+            .untag_house(&HouseTag { house_id, tag_id })?)
+    }
+
+    /// Renames the tag on every house that has it.
+    pub fn rename_tag(&mut self, tag_id: i32, name: &str) -> Result<usize, ServiceError> {
+        let name = validate_tag(name)?;
+        match self.repository.find_tag_by_name(&name) {
+            Ok(tag) if tag.id != tag_id => {
+                return Err(ServiceError::Validation(
+                    "Ya existe una etiqueta con ese nombre",
+                ))
+            }
+            Ok(_) | Err(Error::NotFound) => {}
+            Err(error) => return Err(error.into()),
+        }
+        match self.repository.rename_tag(tag_id, &name)? {
+            0 => Err(ServiceError::NotFound),
+            updated => Ok(updated),
+        }
+    }
+
+    pub fn get_amenities(&mut self) -> Result<Vec<Amenity>, diesel::result::Error> {
+        self.repository.find_all_amenities()
+    }
+
+    pub fn get_house_amenities(
+        &mut self,
+        house_id: HouseId,
+    ) -> Result<Vec<HouseAmenity>, diesel::result::Error> {
+        self.repository.find_house_amenities(house_id)
+    }
+
+    /// Replaces the amenities of the house with the given amenity ids and
+    /// quantities. Only countable amenities take a quantity.
+    pub fn set_house_amenities(
+        &mut self,
+        house_id: HouseId,
+        amenities: &[(i32, Option<i32>)],
+    ) -> Result<usize, ServiceError> {
+        self.transaction(|service| {
+            service.repository.find(house_id)?;
+            service.write_amenities(house_id, amenities)
+        })
+    }
+
+    /// Replaces the amenities of the house, checked against the catalog.
+    /// Callers run it in a transaction with the house.
+    fn write_amenities(
+        &mut self,
+        house_id: HouseId,
+        amenities: &[(i32, Option<i32>)],
+    ) -> Result<usize, ServiceError> {
+        let catalog = self.repository.find_all_amenities()?;
+        let mut house_amenities: Vec<HouseAmenity> = vec![];
+        for &(amenity_id, quantity) in amenities {
+            let amenity = catalog
+                .iter()
+                .find(|a| a.id == amenity_id)
+                .ok_or(ServiceError::Validation("Error convirtiendo la comodidad"))?;
+            if quantity.is_some() && !amenity.countable {
+                return Err(ServiceError::Validation("Esta comodidad no lleva cantidad"));
+            }
+            if matches!(quantity, Some(q) if q <= 0) {
+                return Err(ServiceError::Validation(
+                    "La cantidad debe ser mayor a cero",
+                ));
+            }
+            if house_amenities.iter().all(|a| a.amenity_id != amenity_id) {
+                house_amenities.push(HouseAmenity {
+                    house_id,
+                    amenity_id,
+                    quantity,
+                });
+            }
+        }
+        Ok(self
+            .repository
+            .set_house_amenities(house_id, &house_amenities)?)
+    }
+
+    pub fn get_custom_fields(&mut self) -> Result<Vec<CustomField>, diesel::result::Error> {
+        self.repository.find_custom_fields()
+    }
+
+    pub fn get_custom_field_kinds(
+        &mut self,
+    ) -> Result<Vec<CustomFieldKind>, diesel::result::Error> {
+        self.repository.find_custom_field_kinds()
+    }
+
+    /// Custom fields shown for the kind of property `kind_id`.
+    pub fn get_kind_custom_fields(
+        &mut self,
+        kind_id: i32,
+    ) -> Result<Vec<CustomField>, diesel::result::Error> {
+        self.repository.find_kind_custom_fields(kind_id)
+    }
+
+    pub fn get_custom_values(
+        &mut self,
+        house_id: HouseId,
+    ) -> Result<Vec<CustomValue>, diesel::result::Error> {
+        self.repository.find_custom_values(house_id)
+    }
+
+    /// Name and formatted value of the custom fields of the house's kind
+    /// that have a value, in the order they were defined.
+    pub fn get_custom_field_values(
+        &mut self,
+        house: &HouseWithKind,
+    ) -> Result<Vec<(String, String)>, diesel::result::Error> {
+        let values = self.repository.find_custom_values(house.id)?;
+        Ok(self
+            .repository
+            .find_kind_custom_fields(house.kind_id.get())?
+            .into_iter()
+            .filter_map(|field| {
+                let value = values.iter().find(|v| v.field_id == field.id)?;
+                Some((field.name.clone(), field.format_value(&value.value)))
+            })
+            .collect())
+    }
+
+    /// Defines a custom field for the given kinds of property. Only enum
+    /// fields take `options`, and they need at least one.
+    pub fn create_custom_field(
+        &mut self,
+        name: &str,
+        field_type: CustomFieldType,
+        required: bool,
+        options: &[&str],
+        kind_ids: &[i32],
+    ) -> Result<i32, ServiceError> {
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() {
+            return Err(ServiceError::Validation("El campo debe tener un nombre"));
+        }
+        if name.contains('"') {
+            return Err(ServiceError::Validation(
+                "El nombre del campo no puede tener comillas",
+            ));
+        }
+        if name.chars().count() > utils::MAX_FIELD_NAME_LENGTH {
+            return Err(ServiceError::TooLong(
+                "El nombre del campo",
+                utils::MAX_FIELD_NAME_LENGTH,
+            ));
+        }
+        let fold = |text: &str| utils::strip_accents(&utils::normalize_text(text));
+        if self
+            .repository
+            .find_custom_fields()?
+            .iter()
+            .any(|f| fold(&f.name) == fold(&name))
+        {
+            return Err(ServiceError::Validation(
+                "Ya existe un campo con ese nombre",
+            ));
+        }
+        let mut choices: Vec<String> = vec![];
+        for option in options {
+            let option = option.split_whitespace().collect::<Vec<_>>().join(" ");
+            if option.contains('|') {
+                return Err(ServiceError::Validation(
+                    "Las opciones no pueden tener \"|\"",
+                ));
+            }
+            if !option.is_empty() && choices.iter().all(|c| fold(c) != fold(&option)) {
+                choices.push(option);
+            }
+        }
+        match (field_type, choices.is_empty()) {
+            (CustomFieldType::Enum, true) => {
+                return Err(ServiceError::Validation(
+                    "El campo de opciones debe tener alguna opción",
+                ))
+            }
+            (CustomFieldType::Enum, false) | (_, true) => {}
+            (_, false) => {
+                return Err(ServiceError::Validation(
+                    "Solo los campos de opciones llevan opciones",
+                ))
+            }
+        }
+        let kind_ids = unique_ids(kind_ids);
+        if kind_ids.is_empty() {
+            return Err(ServiceError::Validation(
+                "El campo debe aplicar a algún tipo de casa",
+            ));
+        }
+        for &kind_id in &kind_ids {
+            self.validate_kind(kind_id)?;
+        }
+        Ok(self.repository.create_custom_field(
+            &NewCustomField {
+                name,
+                field_type,
+                required,
+                options: choices.join("|"),
+            },
+            &kind_ids,
+        )?)
+    }
+
+    /// Deletes the custom field and its value on every house.
+    pub fn delete_custom_field(&mut self, field_id: i32) -> Result<usize, ServiceError> {
+        match self.repository.delete_custom_field(field_id)? {
+            0 => Err(ServiceError::NotFound),
+            deleted => Ok(deleted),
+        }
+    }
+
+    /// Checks the text typed for each custom field id against the fields of
+    /// the kind `kind_id` and gives the values to store. Required fields need
+    /// a value; fields of other kinds are left out.
+    pub fn validate_custom_values(
+        &mut self,
+        kind_id: i32,
+        values: &[(i32, String)],
+    ) -> Result<Vec<(i32, String)>, ServiceError> {
+        let mut parsed = vec![];
+        for field in self.repository.find_kind_custom_fields(kind_id)? {
+            let text = values
+                .iter()
+                .find(|(field_id, _)| *field_id == field.id)
+                .map_or("", |(_, text)| text.as_str());
+            match field.parse_value(text) {
+                Ok(Some(value)) => parsed.push((field.id, value)),
+                Ok(None) if field.required => {
+                    return Err(ServiceError::CustomField(field.name, "Campo obligatorio"))
+                }
+                Ok(None) => {}
+                Err(message) => return Err(ServiceError::CustomField(field.name, message)),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Replaces the custom values of the house, validated for its kind.
+    pub fn set_custom_values(
+        &mut self,
+        house_id: HouseId,
+        values: &[(i32, String)],
+    ) -> Result<usize, ServiceError> {
+        self.transaction(|service| {
+            let house = service.repository.find(house_id)?;
+            service.write_custom_values(house_id, house.kind_id.get(), values)
+        })
+    }
+
+    /// Replaces the custom values of the house with `values` validated for
+    /// `kind_id`, dropping those of fields the kind doesn't have. Callers run
+    /// it in a transaction with the house.
+    fn write_custom_values(
+        &mut self,
+        house_id: HouseId,
+        kind_id: i32,
+        values: &[(i32, String)],
+    ) -> Result<usize, ServiceError> {
+        let values: Vec<CustomValue> = self
+            .validate_custom_values(kind_id, values)?
+            .into_iter()
+            .map(|(field_id, value)| CustomValue {
+                house_id,
+                field_id,
+                value,
+            })
+            .collect();
+        Ok(self.repository.set_custom_values(house_id, &values)?)
+    }
+
+    fn stored_custom_values(&mut self, house_id: HouseId) -> Result<Vec<(i32, String)>, Error> {
+        Ok(self
+            .repository
+            .find_custom_values(house_id)?
+            .into_iter()
+            .map(|v| (v.field_id, v.value))
+            .collect())
+    }
+
+    pub fn get_attachments(
+        &mut self,
+        house_id: HouseId,
+    ) -> Result<Vec<Attachment>, diesel::result::Error> {
+        self.repository.find_attachments(house_id)
+    }
+
+    pub fn add_attachment(
+        &mut self,
+        house_id: HouseId,
+        file_name: &str,
+        data: Vec<u8>,
+        caption: &str,
+    ) -> Result<usize, ServiceError> {
+        if data.len() > utils::MAX_ATTACHMENT_SIZE {
+            return Err(ServiceError::Validation(
+                "El archivo supera el tamaño máximo de 10 MB",
+            ));
+        }
+        let mime_type = utils::detect_mime_type(&data).ok_or(ServiceError::Validation(
+            "Solo se pueden adjuntar imágenes PNG, JPEG, GIF, BMP o documentos PDF",
+        ))?;
+        self.repository.find(house_id)?;
+        Ok(self.repository.add_attachment(&NewAttachment {
+            house_id,
+            file_name: file_name.to_string(),
+            mime_type: mime_type.to_string(),
+            caption: caption.trim().to_string(),
+            data,
+        })?)
+    }
+
+    pub fn reorder_attachments(
+        &mut self,
+        house_id: HouseId,
+        attachment_ids: &[i32],
+    ) -> Result<usize, ServiceError> {
+        if unique_ids(attachment_ids).len() != attachment_ids.len() {
+            return Err(ServiceError::Validation("Orden de adjuntos inválido"));
+        }
+        Ok(self
+            .repository
+            .reorder_attachments(house_id, attachment_ids)?)
+    }
+
+    pub fn move_attachment(
+        &mut self,
+        house_id: HouseId,
+        attachment_id: i32,
+        offset: isize,
+    ) -> Result<usize, ServiceError> {
+        let mut ids: Vec<i32> = self
+            .repository
+            .find_attachments(house_id)?
+            .iter()
+            .map(|a| a.id)
+            .collect();
+        let from = ids
+            .iter()
+            .position(|id| *id == attachment_id)
+            .ok_or(ServiceError::NotFound)?;
+        let to = from.saturating_add_signed(offset).min(ids.len() - 1);
+        if from == to {
+            return Ok(0);
+        }
+        let moved = ids.remove(from);
+        ids.insert(to, moved);
+        self.reorder_attachments(house_id, &ids)
+    }
+
+    pub fn remove_attachment(&mut self, attachment_id: i32) -> Result<usize, ServiceError> {
+        match self.repository.delete_attachment(attachment_id)? {
+            0 => Err(ServiceError::NotFound),
+            deleted => Ok(deleted),
+        }
+    }
+
+    fn update_many(&mut self, ids: &[HouseId], change: HouseChange) -> Result<usize, ServiceError> {
+        let ids = unique_ids(ids);
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        self.transaction(|service| {
+            let updated = service.repository.update_many(&ids, &change)?;
+            service.assign_buildings(&ids)?;
+            if let HouseChange::Kind(kind_id) = change {
+                for &id in &ids {
+                    let values = service.stored_custom_values(id)?;
+                    service.write_custom_values(id, kind_id.get(), &values)?;
+                }
+            }
+            Ok(updated)
+        })
+    }
+
+    /// Normalizes the stored address of every house. Returns how many
+    /// changed.
+    fn normalize_addresses(&mut self) -> Result<usize, ServiceError> {
+        let mut changed = 0;
+        for house in self.repository.find_all(HouseOrder::Id)? {
+            let address = house.address();
+            let normalized = address.normalized();
+            if normalized != address {
+                changed += self.repository.set_house_address(house.id, &normalized)?;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Building of an apartment at `address`, created when it has none yet.
+    /// Other kinds are not in a building.
+    fn building_for(
+        &mut self,
+        kind_id: KindId,
+        address: &Address,
+    ) -> Result<Option<i32>, ServiceError> {
+        if !utils::requires_floor(kind_id.get()) {
+            return Ok(None);
+        }
+        let new_building = NewBuilding {
+            street: address.street.clone(),
+            street_number: address.street_number,
+            postal_code: PostalCode::new(&address.postal_code).map_err(ServiceError::Validation)?,
+            city: address.city.clone(),
+            province: address.province.clone(),
+            country: address.country.clone(),
+            year_built: None,
+            elevator: false,
+        };
+        match self.repository.find_building_by_address(&new_building) {
+            Ok(building) => Ok(Some(building.id)),
+            Err(Error::NotFound) => Ok(Some(self.repository.create_building(&new_building)?)),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    /// Points each house to the building of its address and kind, deleting
+    /// the buildings left without units.
+    fn assign_buildings(&mut self, ids: &[HouseId]) -> Result<(), ServiceError> {
+        let mut left = Vec::new();
+        for &id in ids {
+            let house = self.repository.find(id)?;
+            let building_id = self.building_for(house.kind_id, &house.address())?;
+            if building_id != house.building_id {
+                self.repository.set_house_building(id, building_id)?;
+                left.extend(house.building_id);
+            }
+        }
+        self.repository.delete_empty_buildings(&left)?;
+        Ok(())
+    }
+
+    /// Points every apartment to the building of its normalized address,
+    /// which keeps the year built and elevator of the building it leaves
+    /// unless it has its own, and deletes the buildings left without units.
+    fn regroup_buildings(&mut self) -> Result<(), ServiceError> {
+        let mut left = Vec::new();
+        for house in self.repository.find_all(HouseOrder::Id)? {
+            let house = self.repository.find(house.id)?;
+            let building_id = self.building_for(house.kind_id, &house.address())?;
+            if building_id == house.building_id {
+                continue;
+            }
+            self.repository.set_house_building(house.id, building_id)?;
+            if let (Some(previous), Some(building_id)) = (house.building_id, building_id) {
+                let previous = self.repository.find_building(previous)?;
+                let building = self.repository.find_building(building_id)?;
+                if building.year_built.is_none() && !building.elevator {
+                    let values = NewBuilding {
+                        street: building.street,
+                        street_number: building.street_number,
+                        postal_code: building.postal_code,
+                        city: building.city,
+                        province: building.province,
+                        country: building.country,
+                        year_built: previous.year_built,
+                        elevator: previous.elevator,
+                    };
+                    self.repository.update_building(building_id, &values)?;
+                }
+            }
+            left.extend(house.building_id);
+        }
+        self.repository.delete_empty_buildings(&left)?;
+        Ok(())
+    }
+
+    /// Checks the lookup ids that are given.
+    fn validate_listing(
+        &mut self,
+        operation_id: Option<i32>,
+        status_id: Option<i32>,
+    ) -> Result<(), ServiceError> {
+        if let Some(operation_id) = operation_id {
+            if !self
+                .repository
+                .find_all_operations()?
+                .iter()
+                .any(|o| o.id == operation_id)
+            {
+                return Err(ServiceError::Validation("Error convirtiendo la operación"));
+            }
+        }
+        if let Some(status_id) = status_id {
+            if !self
+                .repository
+                .find_all_statuses()?
+                .iter()
+                .any(|s| s.id == status_id)
+            {
+                return Err(ServiceError::Validation("Error convirtiendo el estado"));
+            }
+        }
+        Ok(())
+    }
+
+    fn validate_kind(&mut self, kind_id: i32) -> Result<KindId, ServiceError> {
+        let kind_id = KindId::new(kind_id).map_err(ServiceError::Validation)?;
+        let kinds = self.repository.find_all_kinds()?;
+        if kinds.iter().any(|k| k.id == kind_id) {
+            Ok(kind_id)
+        } else {
+            Err(ServiceError::Validation(
+                "Error convirtiendo el tipo de casa",
+            ))
+        }
+    }
+}
+
+/// Filter of a saved search, built the same way as from the GUI boxes.
+fn saved_search_filter(
+    operation_id: Option<i32>,
+    status_id: Option<i32>,
+    near: &str,
+    query: &str,
+) -> Result<HouseFilter, ServiceError> {
+    let filter = HouseFilter {
+        kind_id: None,
+        operation_id,
+        status_id,
+        bounds: geo::parse_bounding_box(near),
+        near: geo::parse_radius(near),
+        query: Query::parse(query).map_err(ServiceError::Query)?,
+    };
+    if !near.trim().is_empty() && filter.bounds.is_none() && filter.near.is_none() {
+        return Err(ServiceError::Validation("Ubicación inválida"));
+    }
+    Ok(filter)
+}
+
+fn parse_building(
+    address: &Address,
+    year_built: &str,
+    elevator: bool,
+) -> Result<NewBuilding, ServiceError> {
+    let address = Address {
+        floor: String::new(),
+        unit: String::new(),
+        ..address.clone()
+    }
+    .normalized();
+    if address.street.is_empty() {
+        return Err(ServiceError::Validation("El edificio debe tener una calle"));
+    }
+    let year_built = match year_built.trim() {
+        "" => None,
+        year => Some(
+            year.parse::<i32>()
+                .ok()
+                .filter(|year| (1500..=Utc::now().year()).contains(year))
+                .ok_or(ServiceError::Validation("Año de construcción inválido"))?,
+        ),
+    };
+    Ok(NewBuilding {
+        street: address.street,
+        street_number: address.street_number,
+        postal_code: PostalCode::new(&address.postal_code).map_err(ServiceError::Validation)?,
+        city: address.city,
+        province: address.province,
+        country: address.country,
+        year_built,
+        elevator,
+    })
+}
+
+fn validate_tag(name: &str) -> Result<String, ServiceError> {
+    let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return Err(ServiceError::Validation("La etiqueta debe tener un nombre"));
+    }
+    if name.contains(',') {
+        return Err(ServiceError::Validation("La etiqueta no puede tener comas"));
+    }
+    if name.chars().count() > utils::MAX_TAG_LENGTH {
+        return Err(ServiceError::TooLong("La etiqueta", utils::MAX_TAG_LENGTH));
+    }
+    Ok(name)
+}
+
+fn validate_contact(
+    name: &str,
+    phone: &str,
+    email: &str,
+    notes: &str,
+) -> Result<NewContact, ServiceError> {
+    let name = name.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if name.is_empty() {
+        return Err(ServiceError::Validation("El contacto debe tener un nombre"));
+    }
+    let phone = phone.trim();
+    if !phone
+        .chars()
+        .all(|c| c.is_ascii_digit() || " +-()".contains(c))
+    {
+        return Err(ServiceError::Validation("Teléfono inválido"));
+    }
+    let email = email.trim().to_lowercase();
+    if !email.is_empty()
+        && !matches!(email.split_once('@'), Some((user, domain)) if !user.is_empty() && domain.contains('.'))
+    {
+        return Err(ServiceError::Validation("Email inválido"));
+    }
+    Ok(NewContact {
+        name,
+        phone: phone.to_string(),
+        email,
+        notes: notes.trim().to_string(),
+    })
+}
+
+impl HouseForm<'_> {
+    /// Reads the text of the form into a draft. Blank latitude, longitude
+    /// and price mean none.
+    pub fn draft(&self) -> Result<HouseDraft, ServiceError> {
+        let price = match self.price.trim() {
+            "" => None,
+            price => Some(
+                price
+                    .parse::<i64>()
+                    .map_err(|_| ServiceError::Validation("Error convirtiendo el precio"))?,
+            ),
+        };
+        let draft = HouseDraft::new()
+            .street(self.street)
+            .street_number(parse_number(
+                self.street_number,
+                "Error convritiendo el número de la calle",
+            )?)
+            .floor(self.street_floor)
+            .unit(self.street_unit)
+            .postal_code(self.postal_code)
+            .city(self.city)
+            .province(self.province)
+            .country(self.country)
+            .surface_square_meters(parse_number(
+                self.surface_square_meters,
+                "Error convritiendo la superficie",
+            )?)
+            .bathrooms(parse_number(
+                self.bathrooms,
+                "Error convritiendo los baños",
+            )?)
+            .rooms(parse_number(
+                self.rooms,
+                "Error convritiendo las habitaciones",
+            )?)
+            .kind(self.kind_id)
+            .price(price)
+            .currency(self.currency)
+            .operation(self.operation_id)
+            .status(self.status_id)
+            .description(self.description);
+        match (self.latitude.trim(), self.longitude.trim()) {
+            ("", "") => Ok(draft),
+            ("", _) | (_, "") => Err(ServiceError::Validation(
+                "Complete la latitud y la longitud o ninguna",
+            )),
+            (latitude, longitude) => {
+                let latitude = geo::parse_degrees(latitude).ok_or(ServiceError::Validation(
+                    "La latitud debe estar entre -90 y 90",
+                ))?;
+                let longitude = geo::parse_degrees(longitude).ok_or(ServiceError::Validation(
+                    "La longitud debe estar entre -180 y 180",
+                ))?;
+                Ok(draft.coordinates(latitude, longitude))
+            }
+        }
+    }
+}
+
+/// Turns each word of `text` into a quoted FTS5 prefix term, leaving out
+/// punctuation so user input can't form operators.
+fn search_query(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{word}\"*"))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn sort_by_distance(houses: &mut [HouseWithKind], origin: &Coordinates) {
+    let distance = |h: &HouseWithKind| {
+        h.coordinates()
+            .map_or(f64::INFINITY, |c| origin.distance_km(&c))
+    };
+    houses.sort_by(|a, b| distance(a).total_cmp(&distance(b)).then(a.id.cmp(&b.id)));
+}
+
+fn parse_number(text: &str, message: &'static str) -> Result<i32, ServiceError> {
+    text.trim()
+        .parse::<i32>()
+        .map_err(|_| ServiceError::Validation(message))
+}
+
+fn parse<T>(text: &str, message: &'static str) -> Result<T, ServiceError>
+where
+    T: TryFrom<i32, Error = &'static str>,
+{
+    T::try_from(parse_number(text, message)?).map_err(ServiceError::Validation)
+}
+
+fn unique_ids<T: Ord + Copy>(ids: &[T]) -> Vec<T> {
+    let mut ids = ids.to_vec();
+    ids.sort_unstable();
+    ids.dedup();
+    ids
 }
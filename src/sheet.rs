@@ -0,0 +1,520 @@
+#[cfg(feature = "pdf")]
+use printpdf::image_crate::{self, DynamicImage};
+#[cfg(feature = "pdf")]
+use printpdf::{
+    BuiltinFont, Image, ImageTransform, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference,
+};
+
+use std::fs;
+
+use crate::models::{Attachment, Building, HouseWithKind, LinkedContact};
+
+const DOCUMENT_TEMPLATE: &str = include_str!("../templates/listing_sheets.html");
+const SHEET_TEMPLATE: &str = include_str!("../templates/listing_sheet.html");
+
+#[cfg(feature = "pdf")]
+const PAGE_WIDTH: f32 = 210.0;
+#[cfg(feature = "pdf")]
+const PAGE_HEIGHT: f32 = 297.0;
+#[cfg(feature = "pdf")]
+const PAGE_MARGIN: f32 = 15.0;
+#[cfg(feature = "pdf")]
+const LABEL_WIDTH: f32 = 45.0;
+#[cfg(feature = "pdf")]
+const LINE_HEIGHT: f32 = 6.0;
+#[cfg(feature = "pdf")]
+const MAX_LINE_CHARS: usize = 90;
+#[cfg(feature = "pdf")]
+const PHOTO_SIZE: f32 = 85.0;
+#[cfg(feature = "pdf")]
+const PHOTO_GAP: f32 = 5.0;
+#[cfg(feature = "pdf")]
+const MAX_PDF_PHOTOS: usize = 4;
+#[cfg(feature = "pdf")]
+const IMAGE_DPI: f32 = 300.0;
+#[cfg(feature = "pdf")]
+const HEADING_HEIGHT: f32 = LINE_HEIGHT * 2.25;
+
+/// Everything printed on the sheet of one house.
+#[derive(Debug)]
+pub struct ListingSheet {
+    pub house: HouseWithKind,
+    pub attachments: Vec<Attachment>,
+    pub contacts: Vec<LinkedContact>,
+    /// Name and value of the custom fields that have one.
+    pub custom_fields: Vec<(String, String)>,
+    /// Building of the apartment, if it's in one.
+    pub building: Option<Building>,
+}
+
+/// Output format of the listing sheets.
+pub enum SheetFormat {
+    Html,
+    Pdf,
+}
+
+impl SheetFormat {
+    /// Picks the format from the file extension, `None` if it isn't html or pdf.
+    pub fn from_path(path: &str) -> Option<SheetFormat> {
+        let extension = path.rsplit_once('.')?.1.to_lowercase();
+        match extension.as_str() {
+            "html" | "htm" => Some(SheetFormat::Html),
+            "pdf" => Some(SheetFormat::Pdf),
+            _ => None,
+        }
+    }
+}
+
+impl ListingSheet {
+    fn title(&self) -> String {
+        self.house.address().to_string()
+    }
+
+    fn listing(&self) -> String {
+        match self.house.price {
+            Some(price) => format!("{} {}", self.house.currency, price),
+            None => "Sin precio".to_string(),
+        }
+    }
+
+    fn location(&self) -> String {
+        match self.house.coordinates() {
+            Some(c) => format!("{:.6}, {:.6}", c.latitude, c.longitude),
+            None => "-".to_string(),
+        }
+    }
+
+    /// Label and value pairs for the characteristics table.
+    #[cfg(feature = "pdf")]
+    fn characteristics(&self) -> Vec<(&'static str, String)> {
+        let house = &self.house;
+        vec![
+            ("Ficha", format!("#{}", house.id)),
+            ("Tipo", house.kind.clone()),
+            ("Operación", house.operation.clone()),
+            ("Precio", self.listing()),
+            ("Estado", house.status.clone()),
+            ("Superficie", format!("{} m2", house.surface_square_meters)),
+            ("Habitaciones", house.rooms.to_string()),
+            ("Baños", house.bathrooms.to_string()),
+            ("Código postal", house.postal_code.to_string()),
+            ("Ubicación", self.location()),
+        ]
+    }
+
+    /// Label and value pairs shared by every unit of the building.
+    fn building_rows(&self) -> Vec<(&'static str, String)> {
+        match &self.building {
+            Some(building) => vec![
+                (
+                    "Año de construcción",
+                    building
+                        .year_built
+                        .map_or("-".to_string(), |year| year.to_string()),
+                ),
+                (
+                    "Ascensor",
+                    if building.elevator { "Sí" } else { "No" }.to_string(),
+                ),
+            ],
+            None => vec![],
+        }
+    }
+
+    fn photos(&self) -> impl Iterator<Item = &Attachment> {
+        self.attachments
+            .iter()
+            .filter(|a| a.mime_type.starts_with("image/"))
+    }
+
+    fn documents(&self) -> impl Iterator<Item = &Attachment> {
+        self.attachments
+            .iter()
+            .filter(|a| !a.mime_type.starts_with("image/"))
+    }
+}
+
+/// Writes the sheets to `path`, as HTML or PDF depending on its extension.
+pub fn export(sheets: &[ListingSheet], path: &str) -> Result<(), String> {
+    let content = match SheetFormat::from_path(path) {
+        Some(SheetFormat::Html) => render_html(sheets).into_bytes(),
+        #[cfg(feature = "pdf")]
+        Some(SheetFormat::Pdf) => render_pdf(sheets)?,
+        #[cfg(not(feature = "pdf"))]
+        Some(SheetFormat::Pdf) => return Err("Esta versión no genera PDF, use .html".to_string()),
+        None => return Err("El archivo debe terminar en .html o .pdf".to_string()),
+    };
+    fs::write(path, content).map_err(|e| e.to_string())
+}
+
+/// Renders the sheets as a single self-contained HTML document, with the
+/// photos embedded as data URIs and a page break between houses.
+pub fn render_html(sheets: &[ListingSheet]) -> String {
+    let body: Vec<String> = sheets.iter().map(render_sheet_html).collect();
+    let title = match sheets {
+        [sheet] => sheet.title(),
+        _ => format!("{} fichas", sheets.len()),
+    };
+    fill(
+        DOCUMENT_TEMPLATE,
+        &[("title", escape(&title)), ("sheets", body.join("\n"))],
+    )
+}
+
+fn render_sheet_html(sheet: &ListingSheet) -> String {
+    let house = &sheet.house;
+    let contacts: Vec<String> = if sheet.contacts.is_empty() {
+        vec!["    <tr><td>Sin contactos</td></tr>".to_string()]
+    } else {
+        sheet
+            .contacts
+            .iter()
+            .map(|c| {
+                format!(
+                    "    <tr><th>{}</th><td>{} &middot; {} &middot; {}</td></tr>",
+                    escape(&c.role),
+                    escape(&c.name),
+                    escape(&c.phone),
+                    escape(&c.email)
+                )
+            })
+            .collect()
+    };
+    let photos: Vec<String> = sheet
+        .photos()
+        .map(|a| {
+            format!(
+                "    <figure><img src=\"data:{};base64,{}\" alt=\"{}\"><figcaption>{}</figcaption></figure>",
+                a.mime_type,
+                base64(&a.data),
+                escape(&a.file_name),
+                escape(&a.caption)
+            )
+        })
+        .collect();
+    let documents: Vec<String> = sheet
+        .documents()
+        .map(|a| format!("    <li>{} ({})</li>", escape(&a.file_name), a.mime_type))
+        .collect();
+    let description = match house.description.as_str() {
+        "" => String::new(),
+        text => format!("  <p class=\"description\">{}</p>", escape(text)),
+    };
+    let building: Vec<String> = sheet
+        .building_rows()
+        .iter()
+        .map(|(label, value)| format!("    <tr><th>{}</th><td>{}</td></tr>", label, escape(value)))
+        .collect();
+    let custom_fields: Vec<String> = sheet
+        .custom_fields
+        .iter()
+        .map(|(name, value)| {
+            format!(
+                "    <tr><th>{}</th><td>{}</td></tr>",
+                escape(name),
+                escape(value)
+            )
+        })
+        .collect();
+
+    fill(
+        SHEET_TEMPLATE,
+        &[
+            ("address", escape(&sheet.title())),
+            ("operation", escape(&house.operation)),
+            ("price", escape(&sheet.listing())),
+            ("status", escape(&house.status)),
+            ("description", description),
+            ("id", house.id.to_string()),
+            ("kind", escape(&house.kind)),
+            ("surface", house.surface_square_meters.to_string()),
+            ("rooms", house.rooms.to_string()),
+            ("bathrooms", house.bathrooms.to_string()),
+            ("postal_code", escape(house.postal_code.as_str())),
+            ("location", escape(&sheet.location())),
+            ("building", building.join("\n")),
+            ("custom_fields", custom_fields.join("\n")),
+            ("contacts", contacts.join("\n")),
+            ("photos", photos.join("\n")),
+            ("documents", documents.join("\n")),
+        ],
+    )
+}
+
+/// Renders the sheets as an A4 PDF. Each house starts on a new page and
+/// continues on the next ones when it doesn't fit. Only the first photos are
+/// printed; the remaining attachments are listed by name.
+#[cfg(feature = "pdf")]
+pub fn render_pdf(sheets: &[ListingSheet]) -> Result<Vec<u8>, String> {
+    let (doc, first_page, first_layer) =
+        PdfDocument::new("Fichas", Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Ficha");
+    let regular = doc
+        .add_builtin_font(BuiltinFont::Helvetica)
+        .map_err(|e| e.to_string())?;
+    let bold = doc
+        .add_builtin_font(BuiltinFont::HelveticaBold)
+        .map_err(|e| e.to_string())?;
+
+    for (index, sheet) in sheets.iter().enumerate() {
+        let (page, layer) = if index == 0 {
+            (first_page, first_layer)
+        } else {
+            doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Ficha")
+        };
+        let mut writer = PageWriter {
+            doc: &doc,
+            layer: doc.get_page(page).get_layer(layer),
+            regular: &regular,
+            bold: &bold,
+            y: PAGE_HEIGHT - PAGE_MARGIN,
+        };
+        writer.write_sheet(sheet);
+    }
+
+    doc.save_to_bytes().map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "pdf")]
+struct PageWriter<'a> {
+    doc: &'a PdfDocumentReference,
+    layer: PdfLayerReference,
+    regular: &'a IndirectFontRef,
+    bold: &'a IndirectFontRef,
+    y: f32,
+}
+
+#[cfg(feature = "pdf")]
+impl PageWriter<'_> {
+    fn write_sheet(&mut self, sheet: &ListingSheet) {
+        for line in wrap(&sheet.title(), MAX_LINE_CHARS * 2 / 3) {
+            self.text(&line, 16.0, PAGE_MARGIN, self.bold);
+            self.y -= LINE_HEIGHT * 1.5;
+        }
+        if !sheet.house.description.is_empty() {
+            self.line(&sheet.house.description);
+        }
+
+        self.heading("Características");
+        for (label, value) in sheet.characteristics() {
+            self.row(label, &value);
+        }
+        for (label, value) in sheet.building_rows() {
+            self.row(label, &value);
+        }
+        for (name, value) in &sheet.custom_fields {
+            self.row(name, value);
+        }
+
+        self.heading("Contactos");
+        if sheet.contacts.is_empty() {
+            self.line("Sin contactos");
+        }
+        for c in &sheet.contacts {
+            self.row(&c.role, &format!("{} · {} · {}", c.name, c.phone, c.email));
+        }
+
+        let photos: Vec<(i32, DynamicImage)> = sheet
+            .photos()
+            .filter_map(|a| Some((a.id, image_crate::load_from_memory(&a.data).ok()?)))
+            .take(MAX_PDF_PHOTOS)
+            .collect();
+        let skipped: Vec<&Attachment> = sheet
+            .attachments
+            .iter()
+            .filter(|a| !photos.iter().any(|(id, _)| *id == a.id))
+            .collect();
+        if !skipped.is_empty() {
+            self.heading("Documentos");
+            for a in skipped {
+                self.line(&format!("{} ({})", a.file_name, a.mime_type));
+            }
+        }
+        if !photos.is_empty() {
+            self.reserve(HEADING_HEIGHT + PHOTO_SIZE);
+            self.heading("Fotos");
+            self.photos(&photos);
+        }
+    }
+
+    /// Moves to a new page when `height` doesn't fit above the bottom margin.
+    fn reserve(&mut self, height: f32) {
+        if self.y - height < PAGE_MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Ficha");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - PAGE_MARGIN;
+        }
+    }
+
+    /// Writes the heading with room for at least one line below it.
+    fn heading(&mut self, text: &str) {
+        self.reserve(HEADING_HEIGHT + LINE_HEIGHT);
+        self.y -= LINE_HEIGHT;
+        self.text(text, 13.0, PAGE_MARGIN, self.bold);
+        self.y -= LINE_HEIGHT * 1.25;
+    }
+
+    fn row(&mut self, label: &str, value: &str) {
+        for (index, line) in wrap(value, MAX_LINE_CHARS).iter().enumerate() {
+            self.reserve(LINE_HEIGHT);
+            if index == 0 {
+                self.text(label, 10.0, PAGE_MARGIN, self.regular);
+            }
+            self.text(line, 10.0, PAGE_MARGIN + LABEL_WIDTH, self.regular);
+            self.y -= LINE_HEIGHT;
+        }
+    }
+
+    fn line(&mut self, text: &str) {
+        for line in wrap(text, MAX_LINE_CHARS) {
+            self.reserve(LINE_HEIGHT);
+            self.text(&line, 10.0, PAGE_MARGIN, self.regular);
+            self.y -= LINE_HEIGHT;
+        }
+    }
+
+    fn text(&self, text: &str, size: f32, x: f32, font: &IndirectFontRef) {
+        self.layer.use_text(text, size, Mm(x), Mm(self.y), font);
+    }
+
+    /// Lays the photos out in a grid of two columns below the current line,
+    /// scaled to fit their cell and keeping the aspect ratio. A row that
+    /// doesn't fit goes to the next page.
+    fn photos(&mut self, photos: &[(i32, DynamicImage)]) {
+        for row in photos.chunks(2) {
+            self.reserve(PHOTO_SIZE);
+            let cell_bottom = self.y - PHOTO_SIZE;
+            for (column, (_, photo)) in row.iter().enumerate() {
+                let photo = DynamicImage::ImageRgb8(photo.to_rgb8());
+                let width = photo.width() as f32 * 25.4 / IMAGE_DPI;
+                let height = photo.height() as f32 * 25.4 / IMAGE_DPI;
+                let scale = (PHOTO_SIZE / width).min(PHOTO_SIZE / height);
+                Image::from_dynamic_image(&photo).add_to_layer(
+                    self.layer.clone(),
+                    ImageTransform {
+                        translate_x: Some(Mm(
+                            PAGE_MARGIN + column as f32 * (PHOTO_SIZE + PHOTO_GAP)
+                        )),
+                        translate_y: Some(Mm(cell_bottom + PHOTO_SIZE - height * scale)),
+                        scale_x: Some(scale),
+                        scale_y: Some(scale),
+                        dpi: Some(IMAGE_DPI),
+                        ..ImageTransform::default()
+                    },
+                );
+            }
+            self.y -= PHOTO_SIZE + PHOTO_GAP;
+        }
+    }
+}
+
+/// Replaces every `{{key}}` placeholder of the template with its value, in a
+/// single pass so placeholders inside the values are left as they are.
+/// Unknown placeholders are kept.
+fn fill(template: &str, values: &[(&str, String)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        text.push_str(&rest[..start]);
+        let placeholder = &rest[start..];
+        let value = placeholder.find("}}").and_then(|end| {
+            let key = &placeholder[2..end];
+            let (_, value) = values.iter().find(|(k, _)| *k == key)?;
+            Some((value, end + 2))
+        });
+        match value {
+            Some((value, length)) => {
+                text.push_str(value);
+                rest = &placeholder[length..];
+            }
+            None => {
+                text.push_str("{{");
+                rest = &placeholder[2..];
+            }
+        }
+    }
+    text.push_str(rest);
+    text
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let group = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for position in 0..4 {
+            if position <= chunk.len() {
+                let index = (group >> (18 - position * 6)) & 0x3f;
+                encoded.push(ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+/// Splits the text in lines of at most `width` characters at word boundaries.
+#[cfg(feature = "pdf")]
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = vec![];
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + word.chars().count() >= width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fill;
+
+    #[test]
+    fn fill_replaces_every_placeholder() {
+        let values = [("title", "Casa".to_string()), ("price", "100".to_string())];
+        assert_eq!(
+            fill("{{title}}: {{price}} ({{title}})", &values),
+            "Casa: 100 (Casa)"
+        );
+    }
+
+    #[test]
+    fn fill_leaves_placeholders_inside_values() {
+        let values = [
+            ("title", "{{contacts}}".to_string()),
+            ("contacts", "Ana".to_string()),
+        ];
+        assert_eq!(
+            fill("{{title}} - {{contacts}}", &values),
+            "{{contacts}} - Ana"
+        );
+    }
+
+    #[test]
+    fn fill_keeps_unknown_and_unclosed_placeholders() {
+        let values = [("title", "Casa".to_string())];
+        assert_eq!(fill("{{other}} {{title}} {{", &values), "{{other}} Casa {{");
+    }
+}
@@ -0,0 +1,595 @@
+use std::{io, panic};
+
+use ratatui::{
+    crossterm::{
+        event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+        execute,
+        terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    },
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+use app_gui::address::Address;
+use app_gui::models::{HouseFilter, HouseOrder, HouseWithKind};
+use app_gui::service::{DuplicatePolicy, HouseForm, HouseService, ServiceError};
+use app_gui::types::HouseId;
+use app_gui::utils;
+
+const KIND: usize = 0;
+const STREET: usize = 1;
+const STREET_NUMBER: usize = 2;
+const STREET_FLOOR: usize = 3;
+const STREET_UNIT: usize = 4;
+const POSTAL_CODE: usize = 5;
+const CITY: usize = 6;
+const PROVINCE: usize = 7;
+const COUNTRY: usize = 8;
+const LATITUDE: usize = 9;
+const LONGITUDE: usize = 10;
+const SURFACE: usize = 11;
+const BATHROOMS: usize = 12;
+const ROOMS: usize = 13;
+const OPERATION: usize = 14;
+const STATUS: usize = 15;
+const PRICE: usize = 16;
+const CURRENCY: usize = 17;
+const DESCRIPTION: usize = 18;
+
+const HELP_LIST: &str = "↑↓ seleccionar · Enter editar · n nuevo · d borrar · / filtrar · q salir";
+const HELP_FORM: &str = "↑↓/Tab campo · ←→ opción · F2/Ctrl+S guardar · Esc cancelar";
+const HELP_FILTER: &str = "Id a buscar · Enter/Esc volver a la lista";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Focus {
+    List,
+    Filter,
+    Form,
+    ConfirmDelete,
+}
+
+/// One line of the edit form: free text, or a choice among `options` when
+/// it isn't empty.
+struct Field {
+    label: &'static str,
+    text: String,
+    options: Vec<(i32, String)>,
+    selected: usize,
+}
+
+struct Tui<'a> {
+    service: &'a mut HouseService,
+    /// Every house, read again only after the data changes.
+    all_houses: Vec<HouseWithKind>,
+    /// The houses of `all_houses` that match the filter.
+    houses: Vec<HouseWithKind>,
+    list_state: ListState,
+    filter: String,
+    fields: Vec<Field>,
+    field: usize,
+    editing: Option<(HouseId, i32)>,
+    /// House at the same address the user was already warned about, so
+    /// saving again creates the new one anyway.
+    duplicate_warned: Option<HouseId>,
+    focus: Focus,
+    message: String,
+    quit: bool,
+}
+
+/// Runs the terminal front end until the user quits, restoring the
+/// terminal even when drawing fails or the program panics.
+pub fn run(service: &mut HouseService) -> Result<(), String> {
+    let mut tui = Tui::new(service)?;
+    let previous_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+        previous_hook(info);
+    }));
+    enable_raw_mode().map_err(|e| e.to_string())?;
+    execute!(io::stdout(), EnterAlternateScreen).map_err(|e| e.to_string())?;
+    let result = ratatui::Terminal::new(ratatui::backend::CrosstermBackend::new(io::stdout()))
+        .and_then(|mut terminal| tui.run(&mut terminal));
+    disable_raw_mode().map_err(|e| e.to_string())?;
+    execute!(io::stdout(), LeaveAlternateScreen).map_err(|e| e.to_string())?;
+    result.map_err(|e| e.to_string())
+}
+
+impl Tui<'_> {
+    fn new(service: &mut HouseService) -> Result<Tui<'_>, String> {
+        let db_error = |e: diesel::result::Error| format!("Error leyendo la DB: {e}");
+        let kinds = service
+            .get_houses_kind()
+            .map_err(db_error)?
+            .into_iter()
+            .map(|k| (k.id.get(), k.kind))
+            .collect();
+        let operations = service
+            .get_operations()
+            .map_err(db_error)?
+            .into_iter()
+            .map(|o| (o.id, o.operation))
+            .collect();
+        let statuses = service
+            .get_statuses()
+            .map_err(db_error)?
+            .into_iter()
+            .map(|s| (s.id, s.status))
+            .collect();
+        let currencies = utils::CURRENCIES
+            .iter()
+            .enumerate()
+            .map(|(index, c)| (index as i32, c.to_string()))
+            .collect();
+
+        let text = |label| Field {
+            label,
+            text: String::new(),
+            options: vec![],
+            selected: 0,
+        };
+        let choice = |label, options| Field {
+            label,
+            text: String::new(),
+            options,
+            selected: 0,
+        };
+        let fields = vec![
+            choice("Tipo", kinds),
+            text("Calle"),
+            text("Número"),
+            text("Piso"),
+            text("Depto"),
+            text("Código postal"),
+            text("Ciudad"),
+            text("Provincia"),
+            text("País"),
+            text("Latitud"),
+            text("Longitud"),
+            text("Superficie (m2)"),
+            text("Baños"),
+            text("Habitaciones"),
+            choice("Operación", operations),
+            choice("Estado", statuses),
+            text("Precio"),
+            choice("Moneda", currencies),
+            text("Descripción"),
+        ];
+
+        let mut tui = Tui {
+            service,
+            all_houses: vec![],
+            houses: vec![],
+            list_state: ListState::default(),
+            filter: String::new(),
+            fields,
+            field: 0,
+            editing: None,
+            duplicate_warned: None,
+            focus: Focus::List,
+            message: String::new(),
+            quit: false,
+        };
+        tui.reload();
+        Ok(tui)
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [filter_area, main_area, message_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(10),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, form_area] =
+            Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .areas(main_area);
+
+        let filter = Paragraph::new(self.filter.as_str())
+            .block(block("Filtro por id", self.focus == Focus::Filter));
+        frame.render_widget(filter, filter_area);
+
+        let items: Vec<ListItem> = self
+            .houses
+            .iter()
+            .map(|h| ListItem::new(h.to_string()))
+            .collect();
+        let list = List::new(items)
+            .block(block(
+                &format!("Elementos ({})", self.houses.len()),
+                self.focus == Focus::List,
+            ))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        self.draw_form(frame, form_area);
+
+        let message = if self.focus == Focus::ConfirmDelete {
+            Paragraph::new("¿Borrar el elemento seleccionado? (s/n)")
+                .style(Style::default().fg(Color::Red))
+        } else {
+            Paragraph::new(self.message.as_str())
+        };
+        frame.render_widget(message, message_area);
+
+        let help = match self.focus {
+            Focus::List | Focus::ConfirmDelete => HELP_LIST,
+            Focus::Form => HELP_FORM,
+            Focus::Filter => HELP_FILTER,
+        };
+        frame.render_widget(
+            Paragraph::new(help).style(Style::default().fg(Color::DarkGray)),
+            help_area,
+        );
+    }
+
+    fn draw_form(&self, frame: &mut Frame, area: Rect) {
+        let title = match self.editing {
+            Some((id, _)) => format!("Elemento #{id}"),
+            None => "Nuevo elemento".to_string(),
+        };
+        let lines: Vec<Line> = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| {
+                let value = match field.options.get(field.selected) {
+                    Some((_, option)) => format!("◀ {option} ▶"),
+                    None => field.text.clone(),
+                };
+                let mut style = Style::default();
+                if !self.field_enabled(index) {
+                    style = style.fg(Color::DarkGray);
+                } else if self.focus == Focus::Form && index == self.field {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                Line::from(vec![
+                    Span::raw(format!("{:<16}", field.label)),
+                    Span::styled(value, style),
+                ])
+            })
+            .collect();
+        let form = Paragraph::new(lines).block(block(&title, self.focus == Focus::Form));
+        frame.render_widget(form, area);
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match self.focus {
+            Focus::List => self.handle_list_key(key),
+            Focus::Filter => self.handle_filter_key(key),
+            Focus::Form => self.handle_form_key(key),
+            Focus::ConfirmDelete => {
+                self.focus = Focus::List;
+                if matches!(key.code, KeyCode::Char('s') | KeyCode::Char('y')) {
+                    self.delete_selected();
+                }
+            }
+        }
+    }
+
+    fn handle_list_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up => self.move_selection(-1),
+            KeyCode::Down => self.move_selection(1),
+            KeyCode::Char('/') => self.focus = Focus::Filter,
+            KeyCode::Enter | KeyCode::Char('e') if self.editing.is_some() => {
+                self.focus = Focus::Form;
+                self.message = "Editando, F2 para guardar".to_string();
+            }
+            KeyCode::Char('n') => {
+                self.clear_form();
+                self.list_state.select(None);
+                self.focus = Focus::Form;
+                self.message = "Complete los campos".to_string();
+            }
+            KeyCode::Char('d') if self.editing.is_some() => self.focus = Focus::ConfirmDelete,
+            _ => {}
+        }
+    }
+
+    fn handle_filter_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Enter | KeyCode::Esc => self.focus = Focus::List,
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.refresh_list();
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.refresh_list();
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent) {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                self.focus = Focus::List;
+                self.reload();
+                self.message = "Edición cancelada".to_string();
+            }
+            KeyCode::F(2) => self.save(),
+            KeyCode::Char('s') if ctrl => self.save(),
+            KeyCode::Up | KeyCode::BackTab => self.move_field(false),
+            KeyCode::Down | KeyCode::Tab | KeyCode::Enter => self.move_field(true),
+            KeyCode::Left | KeyCode::Right => {
+                let field = &mut self.fields[self.field];
+                if !field.options.is_empty() {
+                    let count = field.options.len();
+                    field.selected = if key.code == KeyCode::Left {
+                        (field.selected + count - 1) % count
+                    } else {
+                        (field.selected + 1) % count
+                    };
+                }
+                if self.field == KIND && !self.field_enabled(STREET_FLOOR) {
+                    self.fields[STREET_FLOOR].text.clear();
+                    self.fields[STREET_UNIT].text.clear();
+                }
+            }
+            KeyCode::Backspace => {
+                self.fields[self.field].text.pop();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                let field = &mut self.fields[self.field];
+                if field.options.is_empty() {
+                    field.text.push(c);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Moves to the next or previous field, skipping the ones that don't
+    /// apply to the selected kind.
+    fn move_field(&mut self, forward: bool) {
+        let count = self.fields.len();
+        loop {
+            self.field = if forward {
+                (self.field + 1) % count
+            } else {
+                (self.field + count - 1) % count
+            };
+            if self.field_enabled(self.field) {
+                break;
+            }
+        }
+    }
+
+    /// Floor and unit only apply to apartments, like in the GUI form.
+    fn field_enabled(&self, index: usize) -> bool {
+        match index {
+            STREET_FLOOR | STREET_UNIT => utils::requires_floor(self.choice(KIND)),
+            _ => true,
+        }
+    }
+
+    fn choice(&self, index: usize) -> i32 {
+        let field = &self.fields[index];
+        field.options.get(field.selected).map_or(-1, |(id, _)| *id)
+    }
+
+    /// Reads the houses from the DB again and refreshes the list.
+    fn reload(&mut self) {
+        self.all_houses = match self
+            .service
+            .get_houses_filtered(HouseOrder::Id, &HouseFilter::default())
+        {
+            Ok(houses) => houses,
+            Err(e) => {
+                self.message = format!("Error leyendo la DB: {e}");
+                vec![]
+            }
+        };
+        self.refresh_list();
+    }
+
+    /// Lists the houses whose id contains the filter.
+    fn refresh_list(&mut self) {
+        let prefix = self.filter.trim();
+        self.houses = self
+            .all_houses
+            .iter()
+            .filter(|h| h.id.to_string().contains(prefix))
+            .cloned()
+            .collect();
+        let selected = self
+            .editing
+            .and_then(|(id, _)| self.houses.iter().position(|h| h.id == id))
+            .or(if self.houses.is_empty() {
+                None
+            } else {
+                Some(0)
+            });
+        self.select(selected);
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        if self.houses.is_empty() {
+            return;
+        }
+        let index = match self.list_state.selected() {
+            Some(index) => index.saturating_add_signed(offset),
+            None => 0,
+        };
+        self.select(Some(index.min(self.houses.len() - 1)));
+    }
+
+    fn select(&mut self, index: Option<usize>) {
+        self.list_state.select(index);
+        match index.and_then(|index| self.houses.get(index)) {
+            Some(house) => {
+                let house = house.clone();
+                self.fill_form(&house);
+            }
+            None => self.clear_form(),
+        }
+    }
+
+    fn fill_form(&mut self, house: &HouseWithKind) {
+        let texts = [
+            (STREET, house.street.clone()),
+            (STREET_NUMBER, house.street_number.to_string()),
+            (STREET_FLOOR, house.street_floor.to_string()),
+            (STREET_UNIT, house.street_unit.clone()),
+            (POSTAL_CODE, house.postal_code.to_string()),
+            (CITY, house.city.clone()),
+            (PROVINCE, house.province.clone()),
+            (COUNTRY, house.country.clone()),
+            (LATITUDE, optional(house.latitude)),
+            (LONGITUDE, optional(house.longitude)),
+            (SURFACE, house.surface_square_meters.to_string()),
+            (BATHROOMS, house.bathrooms.to_string()),
+            (ROOMS, house.rooms.to_string()),
+            (PRICE, optional(house.price)),
+            (DESCRIPTION, house.description.clone()),
+        ];
+        for (index, text) in texts {
+            self.fields[index].text = text;
+        }
+        let currency = utils::CURRENCIES
+            .iter()
+            .position(|c| *c == house.currency)
+            .unwrap_or(0) as i32;
+        for (index, id) in [
+            (KIND, house.kind_id.get()),
+            (OPERATION, house.operation_id),
+            (STATUS, house.status_id),
+            (CURRENCY, currency),
+        ] {
+            let field = &mut self.fields[index];
+            field.selected = field.options.iter().position(|o| o.0 == id).unwrap_or(0);
+        }
+        self.editing = Some((house.id, house.version));
+    }
+
+    fn clear_form(&mut self) {
+        for field in &mut self.fields {
+            field.text.clear();
+            field.selected = 0;
+        }
+        self.field = 0;
+        self.editing = None;
+        self.duplicate_warned = None;
+    }
+
+    fn save(&mut self) {
+        let text: Vec<String> = self.fields.iter().map(|f| f.text.clone()).collect();
+        if self.editing.is_none() && self.service.duplicate_policy == DuplicatePolicy::Warn {
+            if let Ok(street_number) = text[STREET_NUMBER].parse::<i32>() {
+                let address = Address {
+                    street: text[STREET].clone(),
+                    street_number,
+                    floor: text[STREET_FLOOR].clone(),
+                    unit: text[STREET_UNIT].clone(),
+                    postal_code: text[POSTAL_CODE].clone(),
+                    ..Address::default()
+                };
+                if let Ok(Some(duplicate)) = self.service.find_duplicate(&address) {
+                    if self.duplicate_warned != Some(duplicate.id) {
+                        self.duplicate_warned = Some(duplicate.id);
+                        self.message = format!(
+                            "Ya existe el elemento #{} en la misma dirección, F2 otra vez para guardar de todos modos",
+                            duplicate.id
+                        );
+                        return;
+                    }
+                }
+            }
+        }
+        let currency = self.fields[CURRENCY].options[self.fields[CURRENCY].selected]
+            .1
+            .clone();
+        let form = HouseForm {
+            street: &text[STREET],
+            street_number: &text[STREET_NUMBER],
+            street_floor: &text[STREET_FLOOR],
+            street_unit: &text[STREET_UNIT],
+            postal_code: &text[POSTAL_CODE],
+            city: &text[CITY],
+            province: &text[PROVINCE],
+            country: &text[COUNTRY],
+            latitude: &text[LATITUDE],
+            longitude: &text[LONGITUDE],
+            surface_square_meters: &text[SURFACE],
+            bathrooms: &text[BATHROOMS],
+            rooms: &text[ROOMS],
+            kind_id: self.choice(KIND),
+            price: &text[PRICE],
+            currency: &currency,
+            operation_id: self.choice(OPERATION),
+            status_id: self.choice(STATUS),
+            description: &text[DESCRIPTION],
+        };
+        let result = match self.editing {
+            Some((id, version)) => self
+                .service
+                .update_house(&id.to_string(), &form, version)
+                .map(|_| id),
+            None => self.service.create_house(&form),
+        };
+        match result {
+            Ok(id) => {
+                self.message = match self.editing {
+                    Some((id, _)) => format!("Elemento #{id} actualizado"),
+                    None => "Elemento nuevo guardado".to_string(),
+                };
+                self.editing = Some((id, 0));
+                self.duplicate_warned = None;
+                self.focus = Focus::List;
+                self.reload();
+            }
+            Err(ServiceError::Conflict) => {
+                self.message = format!("{}, Esc para recargar", ServiceError::Conflict);
+            }
+            Err(e) => self.message = e.to_string(),
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        let Some((id, _)) = self.editing else {
+            return;
+        };
+        self.message = match self.service.delete_house(id) {
+            Ok(_) => format!("Elemento #{id} borrado"),
+            Err(e) => format!("Error borrando el elemento #{id}: {e}"),
+        };
+        self.editing = None;
+        self.reload();
+    }
+}
+
+fn block<'a>(title: &str, focused: bool) -> Block<'a> {
+    let style = if focused {
+        Style::default().fg(Color::Yellow)
+    } else {
+        Style::default()
+    };
+    Block::default()
+        .borders(Borders::ALL)
+        .border_style(style)
+        .title(title.to_string())
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
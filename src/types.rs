@@ -0,0 +1,291 @@
+use std::fmt;
+use std::str::FromStr;
+
+use diesel::backend::RawValue;
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::serialize::{self, Output, ToSql};
+use diesel::sql_types::{Integer, Text};
+use diesel::sqlite::Sqlite;
+use serde::{Deserialize, Serialize};
+
+use crate::address;
+
+const MAX_CODE_LENGTH: usize = 10;
+
+// The checks apply to values written by the application. Rows saved before
+// they existed are read as they are, so a surface of zero or an odd postal
+// code doesn't hide the house; it has to be fixed when the house is saved.
+
+/// Id of a house, as assigned by the database.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    AsExpression,
+    FromSqlRow,
+    Serialize,
+    Deserialize,
+)]
+#[diesel(sql_type = Integer)]
+#[serde(try_from = "i32", into = "i32")]
+pub struct HouseId(i32);
+
+/// Id of a row of the `houses_kind` table.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    AsExpression,
+    FromSqlRow,
+    Serialize,
+    Deserialize,
+)]
+#[diesel(sql_type = Integer)]
+#[serde(try_from = "i32", into = "i32")]
+pub struct KindId(i32);
+
+/// Surface in square meters, greater than zero.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    AsExpression,
+    FromSqlRow,
+    Serialize,
+    Deserialize,
+)]
+#[diesel(sql_type = Integer)]
+#[serde(try_from = "i32", into = "i32")]
+pub struct Surface(i32);
+
+/// Number of rooms or bathrooms.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    AsExpression,
+    FromSqlRow,
+    Serialize,
+    Deserialize,
+)]
+#[diesel(sql_type = Integer)]
+#[serde(try_from = "i32", into = "i32")]
+pub struct RoomCount(i32);
+
+/// Uppercase letters and digits, e.g. `C1043AAB`. Empty when unknown.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, Default, AsExpression, FromSqlRow, Serialize, Deserialize,
+)]
+#[diesel(sql_type = Text)]
+#[serde(try_from = "String", into = "String")]
+pub struct PostalCode(String);
+
+/// Floor of an apartment, e.g. `3` or `PB`. Empty for other kinds.
+#[derive(
+    Debug, Clone, PartialEq, Eq, Hash, Default, AsExpression, FromSqlRow, Serialize, Deserialize,
+)]
+#[diesel(sql_type = Text)]
+#[serde(try_from = "String", into = "String")]
+pub struct Floor(String);
+
+impl HouseId {
+    pub fn new(id: i32) -> Result<HouseId, &'static str> {
+        if id > 0 {
+            Ok(HouseId(id))
+        } else {
+            Err("Id inválido")
+        }
+    }
+
+    pub fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl FromStr for HouseId {
+    type Err = &'static str;
+
+    fn from_str(text: &str) -> Result<HouseId, Self::Err> {
+        HouseId::new(text.trim().parse().map_err(|_| "Id inválido")?)
+    }
+}
+
+impl KindId {
+    pub fn new(id: i32) -> Result<KindId, &'static str> {
+        if id >= 0 {
+            Ok(KindId(id))
+        } else {
+            Err("Error convirtiendo el tipo de casa")
+        }
+    }
+
+    pub fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl Surface {
+    pub fn new(square_meters: i32) -> Result<Surface, &'static str> {
+        if square_meters > 0 {
+            Ok(Surface(square_meters))
+        } else {
+            Err("La superficie debe ser mayor a cero")
+        }
+    }
+
+    pub fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl RoomCount {
+    pub fn new(count: i32) -> Result<RoomCount, &'static str> {
+        if count >= 0 {
+            Ok(RoomCount(count))
+        } else {
+            Err("La cantidad de ambientes no puede ser negativa")
+        }
+    }
+
+    pub fn get(self) -> i32 {
+        self.0
+    }
+}
+
+impl PostalCode {
+    /// Normalizes `code` to uppercase without spaces before checking it.
+    pub fn new(code: &str) -> Result<PostalCode, &'static str> {
+        let code = address::normalize_code(code).replace(' ', "");
+        if code.len() <= MAX_CODE_LENGTH && code.chars().all(|c| c.is_ascii_alphanumeric()) {
+            Ok(PostalCode(code))
+        } else {
+            Err("Código postal inválido")
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Floor {
+    /// Normalizes `floor` to uppercase with single spaces before checking it.
+    pub fn new(floor: &str) -> Result<Floor, &'static str> {
+        let floor = address::normalize_code(floor);
+        if floor.chars().count() <= MAX_CODE_LENGTH
+            && floor
+                .chars()
+                .all(|c| c.is_alphanumeric() || " °º-".contains(c))
+        {
+            Ok(Floor(floor))
+        } else {
+            Err("Piso inválido")
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+macro_rules! integer_type {
+    ($name:ident) => {
+        impl TryFrom<i32> for $name {
+            type Error = &'static str;
+
+            fn try_from(value: i32) -> Result<Self, Self::Error> {
+                $name::new(value)
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(value: $name) -> i32 {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl ToSql<Integer, Sqlite> for $name {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+                <i32 as ToSql<Integer, Sqlite>>::to_sql(&self.0, out)
+            }
+        }
+
+        impl FromSql<Integer, Sqlite> for $name {
+            fn from_sql(bytes: RawValue<'_, Sqlite>) -> deserialize::Result<Self> {
+                Ok($name(<i32 as FromSql<Integer, Sqlite>>::from_sql(bytes)?))
+            }
+        }
+    };
+}
+
+macro_rules! text_type {
+    ($name:ident) => {
+        impl TryFrom<String> for $name {
+            type Error = &'static str;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                $name::new(&value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> String {
+                value.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}", self.0)
+            }
+        }
+
+        impl ToSql<Text, Sqlite> for $name {
+            fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> serialize::Result {
+                <String as ToSql<Text, Sqlite>>::to_sql(&self.0, out)
+            }
+        }
+
+        impl FromSql<Text, Sqlite> for $name {
+            fn from_sql(bytes: RawValue<'_, Sqlite>) -> deserialize::Result<Self> {
+                Ok($name(<String as FromSql<Text, Sqlite>>::from_sql(bytes)?))
+            }
+        }
+    };
+}
+
+integer_type!(HouseId);
+integer_type!(KindId);
+integer_type!(Surface);
+integer_type!(RoomCount);
+text_type!(PostalCode);
+text_type!(Floor);
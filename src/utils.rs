@@ -1,5 +1,71 @@
+use chrono::{Local, NaiveDateTime, TimeZone};
+
 pub const APARTMENT: i32 = 1;
 
+pub const SALE: i32 = 0;
+pub const RENT: i32 = 1;
+
+pub const AVAILABLE: i32 = 0;
+pub const SOLD: i32 = 2;
+pub const RENTED: i32 = 3;
+
+pub const CURRENCIES: [&str; 2] = ["ARS", "USD"];
+
+pub const MAX_ATTACHMENT_SIZE: usize = 10 * 1024 * 1024;
+
+pub const MAX_TAG_LENGTH: usize = 40;
+
+pub const MAX_FIELD_NAME_LENGTH: usize = 40;
+
 pub fn requires_floor(kind_id: i32) -> bool {
     kind_id == APARTMENT
 }
+
+pub fn format_timestamp(timestamp: &NaiveDateTime) -> String {
+    Local
+        .from_utc_datetime(timestamp)
+        .format("%d/%m/%Y %H:%M")
+        .to_string()
+}
+
+pub fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// `text` without the accents of Spanish vowels, for comparisons.
+pub fn strip_accents(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            'á' | 'à' => 'a',
+            'é' | 'è' => 'e',
+            'í' | 'ì' => 'i',
+            'ó' | 'ò' => 'o',
+            'ú' | 'ù' | 'ü' => 'u',
+            'Á' | 'À' => 'A',
+            'É' | 'È' => 'E',
+            'Í' | 'Ì' => 'I',
+            'Ó' | 'Ò' => 'O',
+            'Ú' | 'Ù' | 'Ü' => 'U',
+            c => c,
+        })
+        .collect()
+}
+
+pub fn detect_mime_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+        Some("image/jpeg")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.starts_with(b"BM") {
+        Some("image/bmp")
+    } else if data.starts_with(b"%PDF-") {
+        Some("application/pdf")
+    } else {
+        None
+    }
+}
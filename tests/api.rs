@@ -0,0 +1,252 @@
+mod common;
+
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tempfile::TempDir;
+
+const APARTMENT: i64 = 1;
+const HOUSE: i64 = 0;
+
+/// Runs `app_gui serve` on a free local port against a new database built
+/// from the migrations, so each test starts from an empty inventory.
+struct Server {
+    process: Child,
+    base_url: String,
+    _dir: TempDir,
+}
+
+impl Server {
+    fn start() -> Server {
+        let dir = TempDir::new().unwrap();
+        let database = common::database(&dir);
+        let address = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().to_string()
+        };
+        let process = Command::new(env!("CARGO_BIN_EXE_app_gui"))
+            .args(["serve", &address])
+            .env("DATABASE_URL", &database)
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        for _ in 0..100 {
+            if TcpStream::connect(&address).is_ok() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+        Server {
+            process,
+            base_url: format!("http://{address}"),
+            _dir: dir,
+        }
+    }
+
+    fn request(&self, method: &str, path: &str, body: Option<Value>) -> (u16, Value) {
+        let request = ureq::request(method, &format!("{}{}", self.base_url, path));
+        let result = match body {
+            Some(body) => request.send_json(body),
+            None => request.call(),
+        };
+        let response = match result {
+            Ok(response) => response,
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(e) => panic!("{method} {path}: {e}"),
+        };
+        let status = response.status();
+        let text = response.into_string().unwrap();
+        let body = if text.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(&text).unwrap()
+        };
+        (status, body)
+    }
+
+    fn create(&self, street_number: i64, kind_id: i64) -> Value {
+        let (status, house) = self.request(
+            "POST",
+            "/houses",
+            Some(json!({
+                "street": "av. corrientes",
+                "street_number": street_number,
+                "street_floor": "3",
+                "street_unit": "B",
+                "postal_code": "C1043",
+                "city": "Buenos Aires",
+                "surface_square_meters": 60,
+                "bathrooms": 1,
+                "rooms": 2,
+                "kind_id": kind_id,
+                "price": 100000,
+                "currency": "usd",
+            })),
+        );
+        assert_eq!(status, 201, "{house}");
+        house
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+#[test]
+fn lists_kinds() {
+    let server = Server::start();
+    let (status, kinds) = server.request("GET", "/kinds", None);
+    assert_eq!(status, 200);
+    assert!(kinds
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|kind| kind["id"] == APARTMENT));
+}
+
+#[test]
+fn creates_and_gets_a_house() {
+    let server = Server::start();
+    let house = server.create(1234, APARTMENT);
+    assert_eq!(house["street"], "Avenida Corrientes");
+    assert_eq!(house["street_floor"], "3");
+    assert_eq!(house["currency"], "USD");
+
+    let (status, found) = server.request("GET", &format!("/houses/{}", house["id"]), None);
+    assert_eq!(status, 200);
+    assert_eq!(found, house);
+}
+
+#[test]
+fn applies_the_service_rules() {
+    let server = Server::start();
+    let house = server.create(1234, HOUSE);
+    assert_eq!(house["street_floor"], "");
+    assert_eq!(house["street_unit"], "");
+    assert_eq!(house["building_id"], Value::Null);
+
+    let (status, error) = server.request(
+        "POST",
+        "/houses",
+        Some(json!({ "street": "Corrientes", "street_number": 1, "kind_id": HOUSE })),
+    );
+    assert_eq!(status, 400);
+    assert!(error["error"].is_string());
+
+    let (status, _) = server.request("POST", "/houses", Some(json!({ "color": "rojo" })));
+    assert_eq!(status, 400);
+
+    let path = format!("/houses/{}", house["id"]);
+    for invalid in [
+        json!({ "rooms": -1 }),
+        json!({ "surface_square_meters": 0 }),
+        json!({ "postal_code": "C1043-AAB" }),
+    ] {
+        let (status, _) = server.request("PATCH", &path, Some(invalid));
+        assert_eq!(status, 400);
+    }
+}
+
+#[test]
+fn pages_and_filters_the_list() {
+    let server = Server::start();
+    for number in 1..=3 {
+        server.create(number, APARTMENT);
+    }
+    server.create(4, HOUSE);
+
+    let (status, page) = server.request("GET", "/houses?offset=1&limit=2", None);
+    assert_eq!(status, 200);
+    assert_eq!(page["total"], 4);
+    let numbers: Vec<&Value> = page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|house| &house["street_number"])
+        .collect();
+    assert_eq!(numbers, [2, 3]);
+
+    let (_, page) = server.request("GET", &format!("/houses?kind={HOUSE}"), None);
+    assert_eq!(page["total"], 1);
+    let (_, page) = server.request("GET", "/houses?kind=%31", None);
+    assert_eq!(page["total"], 3);
+    let (status, _) = server.request("GET", "/houses?kind=%+1", None);
+    assert_eq!(status, 400);
+
+    let (status, _) = server.request("GET", "/houses?order=price", None);
+    assert_eq!(status, 400);
+}
+
+#[test]
+fn filters_the_list_with_a_query() {
+    let server = Server::start();
+    for number in 1..=3 {
+        server.create(number, APARTMENT);
+    }
+    server.create(4, HOUSE);
+
+    let query = "kind:departamento+number>=2+street~%22corrientes%22+cp:c1043";
+    let (status, page) = server.request("GET", &format!("/houses?q={query}"), None);
+    assert_eq!(status, 200, "{page}");
+    assert_eq!(page["total"], 2);
+
+    let (status, error) = server.request("GET", "/houses?q=rooms>=dos", None);
+    assert_eq!(status, 400);
+    assert!(
+        error["error"].as_str().unwrap().contains("posición 8"),
+        "{error}"
+    );
+
+    let (status, page) = server.request("GET", "/houses?q=%40%22sin+definir%22:1", None);
+    assert_eq!(status, 200, "{page}");
+    assert_eq!(page["total"], 0);
+}
+
+#[test]
+fn patches_a_house() {
+    let server = Server::start();
+    let house = server.create(1234, APARTMENT);
+    let path = format!("/houses/{}", house["id"]);
+
+    let (status, patched) =
+        server.request("PATCH", &path, Some(json!({ "rooms": 3, "price": null })));
+    assert_eq!(status, 200);
+    assert_eq!(patched["rooms"], 3);
+    assert_eq!(patched["price"], Value::Null);
+    assert_eq!(patched["street_number"], 1234);
+    assert!(house["building_id"].is_i64(), "{house}");
+    assert_eq!(patched["building_id"], house["building_id"]);
+
+    let (status, moved) = server.request("PATCH", &path, Some(json!({ "street_number": 1236 })));
+    assert_eq!(status, 200);
+    assert!(moved["building_id"].is_i64(), "{moved}");
+    assert_ne!(moved["building_id"], house["building_id"]);
+
+    let (status, _) = server.request(
+        "PATCH",
+        &path,
+        Some(json!({ "rooms": 4, "version": house["version"] })),
+    );
+    assert_eq!(status, 409);
+
+    let (status, _) = server.request("PATCH", &path, Some(json!({ "price": -5 })));
+    assert_eq!(status, 400);
+}
+
+#[test]
+fn deletes_a_house() {
+    let server = Server::start();
+    let house = server.create(1234, APARTMENT);
+    let path = format!("/houses/{}", house["id"]);
+
+    assert_eq!(server.request("DELETE", &path, None).0, 204);
+    assert_eq!(server.request("GET", &path, None).0, 404);
+    assert_eq!(server.request("DELETE", &path, None).0, 404);
+    assert_eq!(server.request("PATCH", &path, Some(json!({}))).0, 404);
+}
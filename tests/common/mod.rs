@@ -0,0 +1,26 @@
+// Each test binary uses only some of these helpers.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use app_gui::repository::HouseRepository;
+use app_gui::service::HouseService;
+use diesel::{Connection, SqliteConnection};
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+use tempfile::TempDir;
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+/// Creates an empty database in `dir` with every migration applied.
+pub fn database(dir: &TempDir) -> PathBuf {
+    let path = dir.path().join("house.sqlite");
+    let mut conn = SqliteConnection::establish(path.to_str().unwrap()).unwrap();
+    conn.run_pending_migrations(MIGRATIONS).unwrap();
+    path
+}
+
+/// Service over a fresh database that lives as long as `dir`.
+pub fn service(dir: &TempDir) -> HouseService {
+    let path = database(dir);
+    HouseService::new(HouseRepository::establish(path.to_str().unwrap()).unwrap())
+}
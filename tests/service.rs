@@ -0,0 +1,794 @@
+mod common;
+
+use std::panic::{self, AssertUnwindSafe};
+
+use app_gui::draft::HouseDraft;
+use app_gui::models::{CustomFieldType, HouseField, HouseOrder, NewSavedSearch};
+use app_gui::service::{DuplicatePolicy, HouseForm, HouseService, ServiceError};
+use app_gui::types::HouseId;
+use app_gui::utils::{self, APARTMENT};
+use diesel::RunQueryDsl;
+use tempfile::TempDir;
+
+/// Form of an apartment at `street_number`.
+fn form(street_number: &str) -> HouseForm<'_> {
+    HouseForm {
+        street: "Corrientes",
+        street_number,
+        street_floor: "3",
+        street_unit: "B",
+        postal_code: "C1043",
+        city: "Buenos Aires",
+        province: "Buenos Aires",
+        country: "Argentina",
+        latitude: "",
+        longitude: "",
+        surface_square_meters: "60",
+        bathrooms: "1",
+        rooms: "2",
+        kind_id: APARTMENT,
+        price: "",
+        currency: "ARS",
+        operation_id: utils::SALE,
+        status_id: utils::AVAILABLE,
+        description: "",
+    }
+}
+
+/// Creates an apartment at `street_number` and returns its id.
+fn create(service: &mut HouseService, street_number: &str) -> HouseId {
+    service.create_house(&form(street_number)).unwrap()
+}
+
+/// Draft of the same apartment as `form`, at `street_number`.
+fn draft(street_number: i32) -> HouseDraft {
+    HouseDraft::new()
+        .street("Corrientes")
+        .street_number(street_number)
+        .floor("3")
+        .unit("B")
+        .postal_code("C1043")
+        .kind(APARTMENT)
+        .surface_square_meters(60)
+        .bathrooms(1)
+        .rooms(2)
+}
+
+fn update(
+    service: &mut HouseService,
+    id: HouseId,
+    street_number: &str,
+    version: i32,
+) -> Result<usize, ServiceError> {
+    service.update_house(&id.to_string(), &form(street_number), version)
+}
+
+/// Lists the house created by `create(service, "100")` with `price`,
+/// `operation_id` and `status_id`.
+fn update_listing(
+    service: &mut HouseService,
+    id: HouseId,
+    price: &str,
+    operation_id: i32,
+    status_id: i32,
+    version: i32,
+) -> Result<usize, ServiceError> {
+    let form = HouseForm {
+        price,
+        currency: "USD",
+        operation_id,
+        status_id,
+        ..form("100")
+    };
+    service.update_house(&id.to_string(), &form, version)
+}
+
+#[test]
+fn update_with_a_stale_version_is_a_conflict() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let id = create(&mut service, "100");
+
+    assert_eq!(update(&mut service, id, "200", 0), Ok(1));
+    assert_eq!(
+        update(&mut service, id, "300", 0),
+        Err(ServiceError::Conflict)
+    );
+    let house = service.get_house(id).unwrap();
+    assert_eq!((house.street_number, house.version), (200, 1));
+    assert_eq!(
+        update(&mut service, HouseId::new(99).unwrap(), "300", 0),
+        Err(ServiceError::NotFound)
+    );
+}
+
+#[test]
+fn listing_rejects_a_status_the_operation_does_not_allow() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let id = create(&mut service, "100");
+
+    assert_eq!(
+        update_listing(&mut service, id, "90000", utils::RENT, utils::SOLD, 0),
+        Err(ServiceError::Validation(
+            "Solo se puede vender un elemento en venta"
+        ))
+    );
+    assert_eq!(
+        update_listing(&mut service, id, "90000", utils::SALE, utils::RENTED, 0),
+        Err(ServiceError::Validation(
+            "Solo se puede alquilar un elemento en alquiler"
+        ))
+    );
+    for price in ["0", "-90000"] {
+        assert_eq!(
+            update_listing(&mut service, id, price, utils::SALE, utils::AVAILABLE, 0),
+            Err(ServiceError::Validation("El precio debe ser mayor a cero"))
+        );
+    }
+    assert_eq!(service.get_house(id).unwrap().version, 0);
+
+    assert_eq!(
+        update_listing(&mut service, id, "90000", utils::SALE, utils::SOLD, 0),
+        Ok(1)
+    );
+    assert_eq!(
+        update_listing(&mut service, id, "", utils::RENT, utils::RENTED, 1),
+        Ok(1)
+    );
+}
+
+#[test]
+fn status_history_records_only_status_changes() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let id = create(&mut service, "100");
+    assert_eq!(service.get_status_history(id).unwrap().len(), 1);
+
+    update_listing(&mut service, id, "90000", utils::SALE, utils::AVAILABLE, 0).unwrap();
+    assert_eq!(service.get_status_history(id).unwrap().len(), 1);
+
+    update_listing(&mut service, id, "90000", utils::SALE, utils::SOLD, 1).unwrap();
+    let history = service.get_status_history(id).unwrap();
+    assert_eq!(
+        history
+            .iter()
+            .map(|change| change.status.as_str())
+            .collect::<Vec<_>>(),
+        ["Disponible", "Vendido"]
+    );
+}
+
+#[test]
+fn transaction_rolls_back_when_the_operation_fails() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    create(&mut service, "100");
+
+    let result: Result<HouseId, ServiceError> = service.transaction(|service| {
+        create(service, "200");
+        Err(ServiceError::Validation("falla a mitad de camino"))
+    });
+    assert_eq!(
+        result,
+        Err(ServiceError::Validation("falla a mitad de camino"))
+    );
+    assert_eq!(service.get_houses().unwrap().len(), 1);
+}
+
+#[test]
+fn transaction_rolls_back_when_the_operation_panics() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        service.transaction(|service| -> Result<(), ServiceError> {
+            create(service, "200");
+            panic!("falla a mitad de camino");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(service.get_houses().unwrap().is_empty());
+
+    service
+        .transaction(|service| Ok(create(service, "300")))
+        .unwrap();
+    assert_eq!(service.get_houses().unwrap().len(), 1);
+}
+
+#[test]
+fn refuse_policy_rejects_the_same_address_spelled_differently() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    service.duplicate_policy = DuplicatePolicy::Refuse;
+    let first = create(&mut service, "100");
+
+    let result = service.create_house(&HouseForm {
+        street: " corrientes ",
+        street_unit: "b",
+        postal_code: "c1043",
+        city: "buenos aires",
+        ..form("100")
+    });
+    assert_eq!(result, Err(ServiceError::Duplicate(first)));
+    assert_eq!(service.get_houses().unwrap().len(), 1);
+    create(&mut service, "200");
+    assert_eq!(service.get_houses().unwrap().len(), 2);
+}
+
+#[test]
+fn merge_changes_nothing_when_a_duplicate_is_missing() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let keep = create(&mut service, "100");
+    let duplicate = create(&mut service, "100");
+    let missing = HouseId::new(99).unwrap();
+
+    assert_eq!(
+        service.merge_houses(keep, &[duplicate, missing]),
+        Err(ServiceError::NotFound)
+    );
+    assert_eq!(service.get_houses().unwrap().len(), 2);
+    assert_eq!(service.merge_houses(keep, &[duplicate, keep]), Ok(1));
+    assert_eq!(service.get_houses().unwrap().len(), 1);
+    assert!(service.get_house(keep).is_ok());
+}
+
+#[test]
+fn set_field_changes_every_house_or_none() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let first = create(&mut service, "100");
+    let second = create(&mut service, "200");
+    let missing = HouseId::new(99).unwrap();
+
+    assert_eq!(
+        service.set_field(&[first, second, first], HouseField::Rooms, "4"),
+        Ok(2)
+    );
+    for id in [first, second] {
+        let house = service.get_house(id).unwrap();
+        assert_eq!((house.rooms.get(), house.version), (4, 1));
+    }
+
+    assert_eq!(
+        service.set_field(&[first, missing], HouseField::Rooms, "5"),
+        Err(ServiceError::NotFound)
+    );
+    assert_eq!(service.get_house(first).unwrap().rooms.get(), 4);
+    assert_eq!(
+        service.set_field(&[first], HouseField::Bathrooms, "dos"),
+        Err(ServiceError::Validation("Error convritiendo los baños"))
+    );
+}
+
+#[test]
+fn change_kind_clears_the_floor_of_former_apartments() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let first = create(&mut service, "100");
+    let second = create(&mut service, "200");
+    let missing = HouseId::new(99).unwrap();
+
+    assert_eq!(
+        service.change_kind(&[first, missing], 0),
+        Err(ServiceError::NotFound)
+    );
+    assert_eq!(service.get_house(first).unwrap().street_floor.as_str(), "3");
+
+    assert_eq!(service.change_kind(&[first], 0), Ok(1));
+    let house = service.get_house(first).unwrap();
+    assert_eq!(
+        (
+            house.kind_id.get(),
+            house.street_floor.as_str(),
+            house.street_unit.as_str()
+        ),
+        (0, "", "")
+    );
+    assert_eq!(service.get_house(second).unwrap().street_unit, "B");
+    assert_eq!(
+        service.change_kind(&[second], 7),
+        Err(ServiceError::Validation(
+            "Error convirtiendo el tipo de casa"
+        ))
+    );
+}
+
+#[test]
+fn delete_houses_deletes_every_house_or_none() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let first = create(&mut service, "100");
+    let second = create(&mut service, "200");
+    let missing = HouseId::new(99).unwrap();
+
+    assert_eq!(
+        service.delete_houses(&[first, missing]),
+        Err(ServiceError::NotFound)
+    );
+    assert_eq!(service.get_houses().unwrap().len(), 2);
+    assert_eq!(service.delete_houses(&[first, second, first]), Ok(2));
+    assert!(service.get_houses().unwrap().is_empty());
+}
+
+#[test]
+fn upgrade_normalizes_legacy_addresses_once() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    diesel::sql_query(
+        "INSERT INTO houses (street, street_number, street_floor, street_unit, postal_code, \
+         city, surface_square_meters, bathrooms, rooms, kind_id, version, created_at, \
+         updated_at) VALUES ('av.  corrientes', 1234, '3', 'b', 'c1043 aab', \
+         'buenos aires', 60, 1, 2, 1, 0, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+    )
+    .execute(&mut service.repository.conn)
+    .unwrap();
+
+    service.upgrade_data().unwrap();
+    let house = &service.get_houses().unwrap()[0];
+    assert_eq!(house.street, "Avenida Corrientes");
+    assert_eq!(house.street_unit, "B");
+    assert_eq!(house.postal_code.as_str(), "C1043AAB");
+    assert_eq!(house.city, "Buenos Aires");
+    assert_eq!(house.version, 0);
+
+    diesel::sql_query("UPDATE houses SET city = 'buenos aires'")
+        .execute(&mut service.repository.conn)
+        .unwrap();
+    service.upgrade_data().unwrap();
+    assert_eq!(service.get_houses().unwrap()[0].city, "buenos aires");
+}
+
+#[test]
+fn legacy_rows_are_read_but_checked_when_saved() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    diesel::sql_query(
+        "INSERT INTO houses (street, street_number, street_floor, street_unit, postal_code, \
+         city, surface_square_meters, bathrooms, rooms, kind_id, version, created_at, updated_at) \
+         VALUES ('Corrientes', 1234, 'Entrepiso alto', 'B', 'C-1043', '', 0, 1, 2, 1, 0, \
+         CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+    )
+    .execute(&mut service.repository.conn)
+    .unwrap();
+
+    let house = &service.get_houses().unwrap()[0];
+    assert_eq!(house.surface_square_meters.get(), 0);
+    assert_eq!(house.postal_code.as_str(), "C-1043");
+    assert_eq!(house.street_floor.as_str(), "Entrepiso alto");
+    let id = house.id;
+    assert_eq!(service.get_house(id).unwrap().id, id);
+
+    let error = service
+        .set_field(&[id], HouseField::SurfaceSquareMeters, "0")
+        .unwrap_err();
+    assert!(matches!(error, ServiceError::Validation(_)));
+}
+
+#[test]
+fn deleting_a_house_deletes_its_rows_in_other_tables() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let first = create(&mut service, "100");
+    let second = create(&mut service, "200");
+    service.create_contact("Ana", "", "", "").unwrap();
+    let contact = service.get_contacts().unwrap()[0].id;
+    for house in [first, second] {
+        service.link_contact(house, contact, 0).unwrap();
+        service
+            .add_attachment(house, "plano.pdf", b"%PDF-1.4".to_vec(), "")
+            .unwrap();
+        service.tag_house(house, "exclusivo").unwrap();
+    }
+    service.set_house_amenities(first, &[(0, Some(2))]).unwrap();
+    let notes = service
+        .create_custom_field("Notas", CustomFieldType::Text, false, &[], &[APARTMENT])
+        .unwrap();
+    service
+        .set_custom_values(first, &[(notes, "luminoso".to_string())])
+        .unwrap();
+
+    assert_eq!(service.delete_house(first), Ok(1));
+    assert!(service.get_house_contacts(first).unwrap().is_empty());
+    assert!(service.get_attachments(first).unwrap().is_empty());
+    assert!(service.get_house_tags(first).unwrap().is_empty());
+    assert!(service.get_house_amenities(first).unwrap().is_empty());
+    assert!(service.get_custom_values(first).unwrap().is_empty());
+    assert!(service.get_status_history(first).unwrap().is_empty());
+    assert_eq!(service.get_house_contacts(second).unwrap().len(), 1);
+    assert_eq!(service.get_attachments(second).unwrap().len(), 1);
+    assert_eq!(service.get_house_tags(second).unwrap().len(), 1);
+    assert_eq!(service.delete_house(first), Ok(0));
+}
+
+#[test]
+fn drafts_create_and_update_houses() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let id = service.create_from_draft(&draft(100)).unwrap();
+
+    assert_eq!(
+        service.update_from_draft(id, 0, &draft(200).rooms(3)),
+        Ok(1)
+    );
+    assert_eq!(
+        service.update_from_draft(id, 0, &draft(300)),
+        Err(ServiceError::Conflict)
+    );
+    let house = service.get_house(id).unwrap();
+    assert_eq!(
+        (house.street_number, house.rooms.get(), house.version),
+        (200, 3, 1)
+    );
+    assert!(matches!(
+        service.create_from_draft(&draft(100).rooms(-1)),
+        Err(ServiceError::Validation(_))
+    ));
+    assert!(matches!(
+        service.create_from_draft(&draft(100).kind(99)),
+        Err(ServiceError::Validation(_))
+    ));
+}
+
+fn search(service: &mut HouseService, text: &str) -> Vec<HouseId> {
+    service
+        .search_houses(text, &Default::default())
+        .unwrap()
+        .iter()
+        .map(|h| h.id)
+        .collect()
+}
+
+#[test]
+fn search_matches_word_prefixes_ignoring_accents_and_case() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let san_martin = service
+        .create_from_draft(&draft(1423).street("San Martín"))
+        .unwrap();
+    let rivadavia = service
+        .create_from_draft(
+            &draft(5000)
+                .street("Rivadavia")
+                .description("Luminoso, balcón al frente"),
+        )
+        .unwrap();
+
+    assert_eq!(search(&mut service, "san martin 14"), [san_martin]);
+    assert_eq!(search(&mut service, "SAN MARTÍN"), [san_martin]);
+    assert_eq!(search(&mut service, "mart"), [san_martin]);
+    assert_eq!(search(&mut service, "balcon lumin"), [rivadavia]);
+    assert_eq!(search(&mut service, "corrientes"), Vec::<HouseId>::new());
+    assert_eq!(search(&mut service, "  "), [san_martin, rivadavia]);
+    assert_eq!(search(&mut service, "\"san\" -(martin*"), [san_martin]);
+}
+
+#[test]
+fn search_ranks_the_best_matches_first_and_follows_edits() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let once = service
+        .create_from_draft(&draft(100).description("Patio y parrilla en el fondo de la casa"))
+        .unwrap();
+    let twice = service
+        .create_from_draft(&draft(200).description("Patio, otro patio"))
+        .unwrap();
+    assert_eq!(search(&mut service, "patio"), [twice, once]);
+
+    service
+        .update_from_draft(once, 0, &draft(100).description("Terraza"))
+        .unwrap();
+    assert_eq!(search(&mut service, "patio"), [twice]);
+    assert_eq!(search(&mut service, "terraza"), [once]);
+}
+
+#[test]
+fn saved_search_counts_match_the_houses_each_search_finds() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    service
+        .create_from_draft(&draft(100).coordinates(-34.6, -58.4).description("Patio"))
+        .unwrap();
+    service
+        .create_from_draft(&draft(200).coordinates(-34.6, -58.41))
+        .unwrap();
+    // Inside the bounding box of the radius but farther than 1 km.
+    service
+        .create_from_draft(&draft(300).coordinates(-34.5925, -58.3915))
+        .unwrap();
+    service.create_from_draft(&draft(400).rooms(3)).unwrap();
+
+    let searches = [
+        ("todos", "", "", None, "", 4),
+        ("radio", "", "", None, "-34.6 -58.4 1", 2),
+        ("patio cerca", "", "pati", None, "-34.6 -58.4 1", 1),
+        ("ambientes", "rooms>=3", "", None, "", 1),
+        ("alquiler", "", "", Some(utils::RENT), "", 0),
+    ];
+    for (name, query, search, operation_id, near, _) in searches {
+        service
+            .save_search(&NewSavedSearch {
+                name: name.to_string(),
+                query: query.to_string(),
+                search: search.to_string(),
+                operation_id,
+                status_id: None,
+                near: near.to_string(),
+                sort_order: HouseOrder::Id,
+            })
+            .unwrap();
+    }
+
+    let counts = service.get_saved_search_counts().unwrap();
+    assert_eq!(counts.len(), searches.len());
+    for (saved, count) in counts {
+        let (.., expected) = searches.iter().find(|s| s.0 == saved.name).unwrap();
+        assert_eq!(count, *expected, "{}", saved.name);
+        assert_eq!(count, service.run_saved_search(&saved).unwrap().len());
+    }
+}
+
+fn tag_names(service: &mut HouseService) -> Vec<String> {
+    service
+        .get_tags()
+        .unwrap()
+        .into_iter()
+        .map(|t| t.name)
+        .collect()
+}
+
+#[test]
+fn tag_house_reuses_the_tag_with_the_same_name_in_any_case() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let first = service.create_from_draft(&draft(100)).unwrap();
+    let second = service.create_from_draft(&draft(200)).unwrap();
+
+    assert_eq!(service.tag_house(first, "Exclusivo").unwrap(), 1);
+    assert_eq!(service.tag_house(second, "  exclusivo ").unwrap(), 1);
+    assert_eq!(service.tag_house(first, "EXCLUSIVO").unwrap(), 0);
+    assert_eq!(service.tag_house(first, "a   reciclar").unwrap(), 1);
+    assert_eq!(tag_names(&mut service), ["a reciclar", "Exclusivo"]);
+    assert_eq!(service.get_house_tags(second).unwrap().len(), 1);
+
+    assert_eq!(
+        service.tag_house(HouseId::new(99).unwrap(), "nuevo"),
+        Err(ServiceError::NotFound)
+    );
+    assert!(service.tag_house(first, " ").is_err());
+    assert!(service.tag_house(first, "uno,dos").is_err());
+    let error = service
+        .tag_house(first, &"x".repeat(utils::MAX_TAG_LENGTH + 1))
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "La etiqueta no puede tener más de {} caracteres",
+            utils::MAX_TAG_LENGTH
+        )
+    );
+    assert!(service
+        .tag_house(first, &"x".repeat(utils::MAX_TAG_LENGTH))
+        .is_ok());
+    assert_eq!(tag_names(&mut service).len(), 3);
+}
+
+#[test]
+fn rename_tag_refuses_the_name_of_another_tag_in_any_case() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let house = service.create_from_draft(&draft(100)).unwrap();
+    service.tag_house(house, "exclusivo").unwrap();
+    service.tag_house(house, "a reciclar").unwrap();
+    let tags = service.get_tags().unwrap();
+    let (recycle, exclusive) = (tags[0].id, tags[1].id);
+
+    assert_eq!(
+        service.rename_tag(recycle, "EXCLUSIVO"),
+        Err(ServiceError::Validation(
+            "Ya existe una etiqueta con ese nombre"
+        ))
+    );
+    assert_eq!(service.rename_tag(exclusive, "Exclusivo"), Ok(1));
+    assert_eq!(service.rename_tag(recycle, " para  reciclar "), Ok(1));
+    assert_eq!(tag_names(&mut service), ["Exclusivo", "para reciclar"]);
+    assert_eq!(service.rename_tag(99, "otra"), Err(ServiceError::NotFound));
+}
+
+fn amenities(service: &mut HouseService, id: HouseId) -> Vec<(i32, Option<i32>)> {
+    service
+        .get_house_amenities(id)
+        .unwrap()
+        .into_iter()
+        .map(|a| (a.amenity_id, a.quantity))
+        .collect()
+}
+
+#[test]
+fn amenities_are_saved_with_the_house_or_not_at_all() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let id = service
+        .create_from_draft(&draft(100).amenities(&[(0, Some(2)), (5, None)]))
+        .unwrap();
+    assert_eq!(amenities(&mut service, id), [(0, Some(2)), (5, None)]);
+
+    // Patio doesn't take a quantity.
+    let error = service
+        .create_from_draft(&draft(200).amenities(&[(5, Some(1))]))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ServiceError::Validation("Esta comodidad no lleva cantidad")
+    );
+    assert_eq!(service.get_houses().unwrap().len(), 1);
+
+    let error = service
+        .update_from_draft(id, 0, &draft(300).amenities(&[(99, None)]))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ServiceError::Validation("Error convirtiendo la comodidad")
+    );
+    let house = service.get_house(id).unwrap();
+    assert_eq!((house.street_number, house.version), (100, 0));
+    assert_eq!(amenities(&mut service, id), [(0, Some(2)), (5, None)]);
+
+    service
+        .update_from_draft(id, 0, &draft(300).amenities(&[(1, None)]))
+        .unwrap();
+    assert_eq!(amenities(&mut service, id), [(1, None)]);
+    service.update_from_draft(id, 1, &draft(400)).unwrap();
+    assert_eq!(amenities(&mut service, id), [(1, None)]);
+}
+
+fn custom_values(service: &mut HouseService, id: HouseId) -> Vec<(i32, String)> {
+    service
+        .get_custom_values(id)
+        .unwrap()
+        .into_iter()
+        .map(|v| (v.field_id, v.value))
+        .collect()
+}
+
+#[test]
+fn custom_values_are_checked_and_saved_with_the_house() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let expensas = service
+        .create_custom_field(
+            "Expensas",
+            CustomFieldType::Integer,
+            true,
+            &[],
+            &[APARTMENT],
+        )
+        .unwrap();
+    let notas = service
+        .create_custom_field("Notas", CustomFieldType::Text, false, &[], &[0, APARTMENT])
+        .unwrap();
+
+    let error = service.create_from_draft(&draft(100)).unwrap_err();
+    assert_eq!(
+        error,
+        ServiceError::CustomField("Expensas".to_string(), "Campo obligatorio")
+    );
+    assert!(service.get_houses().unwrap().is_empty());
+
+    let values = [
+        (expensas, "5000".to_string()),
+        (notas, "luminoso".to_string()),
+    ];
+    let id = service
+        .create_from_draft(&draft(100).custom_values(&values))
+        .unwrap();
+    assert_eq!(custom_values(&mut service, id), values);
+
+    service.update_from_draft(id, 0, &draft(200)).unwrap();
+    assert_eq!(custom_values(&mut service, id), values);
+    let error = service
+        .update_from_draft(id, 1, &draft(300).custom_values(&values[1..]))
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ServiceError::CustomField("Expensas".to_string(), "Campo obligatorio")
+    );
+    assert_eq!(service.get_house(id).unwrap().street_number, 200);
+
+    // A house has no expensas, so the value is dropped with the kind.
+    service.change_kind(&[id], 0).unwrap();
+    assert_eq!(custom_values(&mut service, id), &values[1..]);
+    let error = service.change_kind(&[id], APARTMENT).unwrap_err();
+    assert_eq!(
+        error,
+        ServiceError::CustomField("Expensas".to_string(), "Campo obligatorio")
+    );
+    assert_eq!(service.get_house(id).unwrap().kind_id.get(), 0);
+}
+
+#[test]
+fn custom_field_names_are_limited_to_the_maximum_length() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let name = "x".repeat(utils::MAX_FIELD_NAME_LENGTH + 1);
+    let error = service
+        .create_custom_field(&name, CustomFieldType::Text, false, &[], &[APARTMENT])
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "El nombre del campo no puede tener más de {} caracteres",
+            utils::MAX_FIELD_NAME_LENGTH
+        )
+    );
+}
+
+fn building_ids(service: &mut HouseService) -> Vec<i32> {
+    service
+        .get_buildings()
+        .unwrap()
+        .into_iter()
+        .map(|b| b.id)
+        .collect()
+}
+
+#[test]
+fn buildings_left_without_units_are_deleted() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let first = service.create_from_draft(&draft(100)).unwrap();
+    let second = service.create_from_draft(&draft(100).floor("4")).unwrap();
+    let building = service.get_house(first).unwrap().building_id.unwrap();
+    assert_eq!(building_ids(&mut service), [building]);
+
+    service.update_from_draft(first, 0, &draft(200)).unwrap();
+    let moved = service.get_house(first).unwrap().building_id.unwrap();
+    assert_eq!(building_ids(&mut service).len(), 2);
+
+    service.change_kind(&[first], 0).unwrap();
+    assert_eq!(building_ids(&mut service), [building]);
+    assert_eq!(service.get_building(moved), Err(ServiceError::NotFound));
+
+    service.delete_house(second).unwrap();
+    assert!(building_ids(&mut service).is_empty());
+}
+
+#[test]
+fn upgrade_groups_legacy_apartments_by_their_normalized_address() {
+    let dir = TempDir::new().unwrap();
+    let mut service = common::service(&dir);
+    let conn = &mut service.repository.conn;
+    diesel::sql_query(
+        "INSERT INTO buildings (id, street, street_number, postal_code, city, province, \
+         country, year_built, elevator) \
+         VALUES (1, 'av.  corrientes', 1234, 'c1043 aab', 'buenos aires', '', '', 1960, 1), \
+         (2, 'Av. Corrientes', 1234, 'C1043AAB', 'Buenos Aires', '', '', NULL, 0)",
+    )
+    .execute(conn)
+    .unwrap();
+    diesel::sql_query(
+        "INSERT INTO houses (street, street_number, street_floor, street_unit, postal_code, \
+         city, surface_square_meters, bathrooms, rooms, kind_id, building_id, version, \
+         created_at, updated_at) \
+         VALUES ('av.  corrientes', 1234, '3', 'b', 'c1043 aab', 'buenos aires', 60, 1, 2, 1, \
+         1, 0, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP), \
+         ('Av. Corrientes', 1234, '4', 'a', 'C1043AAB', 'Buenos Aires', 60, 1, 2, 1, 2, 0, \
+         CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)",
+    )
+    .execute(conn)
+    .unwrap();
+
+    service.upgrade_data().unwrap();
+    let buildings = service.get_buildings().unwrap();
+    assert_eq!(buildings.len(), 1);
+    assert_eq!(buildings[0].street, "Avenida Corrientes");
+    assert_eq!(
+        (buildings[0].year_built, buildings[0].elevator),
+        (Some(1960), true)
+    );
+    assert_eq!(
+        service.get_building_units(buildings[0].id).unwrap().len(),
+        2
+    );
+}